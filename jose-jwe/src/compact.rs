@@ -0,0 +1,215 @@
+// SPDX-FileCopyrightText: 2022 Profian Inc. <opensource@profian.com>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use alloc::string::{String, ToString};
+use core::fmt::Display;
+use core::{convert::Infallible, str::FromStr};
+
+use jose_b64::base64ct::{Base64UrlUnpadded, Encoding};
+use jose_b64::serde::Bytes;
+use jose_b64::stream::Error;
+
+use crate::{Flattened, General, Jwe, Recipient};
+
+impl FromStr for Jwe {
+    type Err = Error<serde_json::Error>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Flattened::from_str(s)?.into())
+    }
+}
+
+impl FromStr for General {
+    type Err = Error<serde_json::Error>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Flattened::from_str(s)?.into())
+    }
+}
+
+/// Parses an optional part of the compact serialization, which is empty if
+/// absent.
+fn optional(part: &str) -> Result<Option<Bytes>, Error<serde_json::Error>> {
+    match part {
+        "" => Ok(None),
+        _ => Ok(Some(part.parse().map_err(|e: Error<Infallible>| e.cast())?)),
+    }
+}
+
+impl FromStr for Flattened {
+    type Err = Error<serde_json::Error>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut iter = s.split('.');
+
+        let prot = iter.next().ok_or(Error::Length)?;
+        let ekey = iter.next().ok_or(Error::Length)?;
+        let ivec = iter.next().ok_or(Error::Length)?;
+        let ciph = iter.next().ok_or(Error::Length)?;
+        let atag = iter.next().ok_or(Error::Length)?;
+        if iter.next().is_some() {
+            return Err(Error::Length);
+        }
+
+        Ok(Self {
+            protected: Some(prot.parse()?),
+            unprotected: None,
+            recipient: Recipient {
+                header: None,
+                encrypted_key: optional(ekey)?,
+            },
+            aad: None,
+            iv: optional(ivec)?,
+            ciphertext: ciph.parse().map_err(|e: Error<Infallible>| e.cast())?,
+            tag: optional(atag)?,
+        })
+    }
+}
+
+/// The parts of a JWE that the compact serialization represents.
+struct Compact<'a> {
+    protected: Option<&'a [u8]>,
+    encrypted_key: Option<&'a [u8]>,
+    iv: Option<&'a [u8]>,
+    ciphertext: &'a [u8],
+    tag: Option<&'a [u8]>,
+}
+
+impl Display for Compact<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let encode = |x: Option<&[u8]>| x.map(Base64UrlUnpadded::encode_string);
+
+        let prot = encode(self.protected).unwrap_or_default();
+        let ekey = encode(self.encrypted_key).unwrap_or_default();
+        let ivec = encode(self.iv).unwrap_or_default();
+        let ciph = Base64UrlUnpadded::encode_string(self.ciphertext);
+        let atag = encode(self.tag).unwrap_or_default();
+        write!(f, "{prot}.{ekey}.{ivec}.{ciph}.{atag}")
+    }
+}
+
+/// Formats the compact serialization.
+///
+/// The unprotected headers and the AAD cannot be represented and are
+/// omitted. Use [`Flattened::to_compact`] to detect this.
+impl Display for Flattened {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        Compact {
+            protected: self.protected.as_ref().map(|p| p.as_ref()),
+            encrypted_key: self.recipient.encrypted_key.as_ref().map(AsRef::as_ref),
+            iv: self.iv.as_ref().map(AsRef::as_ref),
+            ciphertext: &self.ciphertext,
+            tag: self.tag.as_ref().map(AsRef::as_ref),
+        }
+        .fmt(f)
+    }
+}
+
+/// An error producing the compact serialization.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CompactError {
+    /// There is not exactly one recipient.
+    Recipients(usize),
+
+    /// There is an unprotected header, which cannot be represented.
+    Unprotected,
+
+    /// There is no protected header.
+    Protected,
+
+    /// There is additional authenticated data, which cannot be represented.
+    Aad,
+}
+
+impl Display for CompactError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Recipients(n) => write!(f, "expected exactly one recipient, found {n}"),
+            Self::Unprotected => f.write_str("the unprotected header cannot be represented"),
+            Self::Protected => f.write_str("the protected header is missing"),
+            Self::Aad => f.write_str("the additional authenticated data cannot be represented"),
+        }
+    }
+}
+
+/// Checks that the compact serialization represents the JWE without loss.
+fn lossless(
+    protected: bool,
+    unprotected: bool,
+    recipient: &Recipient,
+    aad: bool,
+) -> Result<(), CompactError> {
+    if unprotected || recipient.header.is_some() {
+        return Err(CompactError::Unprotected);
+    }
+
+    if !protected {
+        return Err(CompactError::Protected);
+    }
+
+    if aad {
+        return Err(CompactError::Aad);
+    }
+
+    Ok(())
+}
+
+impl Flattened {
+    /// Returns the compact serialization.
+    ///
+    /// Unlike [`Display`], this fails if the JWE cannot be represented
+    /// without loss.
+    pub fn to_compact(&self) -> Result<String, CompactError> {
+        lossless(
+            self.protected.is_some(),
+            self.unprotected.is_some(),
+            &self.recipient,
+            self.aad.is_some(),
+        )?;
+
+        Ok(self.to_string())
+    }
+}
+
+impl General {
+    /// Returns the compact serialization.
+    ///
+    /// This fails unless there is exactly one recipient and the JWE can be
+    /// represented without loss.
+    pub fn to_compact(&self) -> Result<String, CompactError> {
+        let recipient = match &self.recipients[..] {
+            [recipient] => recipient,
+            all => return Err(CompactError::Recipients(all.len())),
+        };
+
+        lossless(
+            self.protected.is_some(),
+            self.unprotected.is_some(),
+            recipient,
+            self.aad.is_some(),
+        )?;
+
+        let compact = Compact {
+            protected: self.protected.as_ref().map(|p| p.as_ref()),
+            encrypted_key: recipient.encrypted_key.as_ref().map(AsRef::as_ref),
+            iv: self.iv.as_ref().map(AsRef::as_ref),
+            ciphertext: &self.ciphertext,
+            tag: self.tag.as_ref().map(AsRef::as_ref),
+        };
+
+        Ok(compact.to_string())
+    }
+}
+
+impl Jwe {
+    /// Returns the compact serialization.
+    ///
+    /// See [`General::to_compact`].
+    pub fn to_compact(&self) -> Result<String, CompactError> {
+        match self {
+            Self::General(general) => general.to_compact(),
+            Self::Flattened(flattened) => flattened.to_compact(),
+        }
+    }
+}
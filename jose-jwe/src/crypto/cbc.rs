@@ -0,0 +1,124 @@
+// SPDX-FileCopyrightText: 2022 Profian Inc. <opensource@profian.com>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! AES_CBC_HMAC_SHA2 (RFC 7518 Section 5.2)
+
+#![cfg(feature = "aes-cbc-hs")]
+
+use alloc::vec::Vec;
+
+use aes::{Aes128, Aes192, Aes256};
+use cbc::cipher::block_padding::Pkcs7;
+use cbc::cipher::{BlockCipher, BlockDecryptMut, BlockEncryptMut, KeyInit, KeyIvInit};
+use cbc::{Decryptor, Encryptor};
+use hmac::digest::KeyInit as MacInit;
+use hmac::{Hmac, Mac};
+use jose_jwa::Encryption;
+use sha2::{Sha256, Sha384, Sha512};
+
+use super::Error;
+
+/// Computes the tag over the AAD, the IV and the ciphertext (RFC 7518
+/// Section 5.2.2.1).
+///
+/// The MAC key is the first half of the key. The tag is the first half of
+/// the MAC.
+fn mac<M: Mac + MacInit>(key: &[u8], iv: &[u8], aad: &[u8], ciphertext: &[u8]) -> Result<M, Error> {
+    let len = (aad.len() as u64 * 8).to_be_bytes();
+
+    let mut mac = <M as Mac>::new_from_slice(key).map_err(|_| Error::Length)?;
+    mac.update(aad);
+    mac.update(iv);
+    mac.update(ciphertext);
+    mac.update(&len);
+    Ok(mac)
+}
+
+fn seal<C, M>(
+    cek: &[u8],
+    iv: &[u8],
+    aad: &[u8],
+    plaintext: &[u8],
+) -> Result<(Vec<u8>, Vec<u8>), Error>
+where
+    C: BlockCipher + BlockEncryptMut + KeyInit,
+    M: Mac + MacInit,
+{
+    let (mac_key, enc_key) = cek.split_at(cek.len() / 2);
+    if cek.len() != C::key_size() * 2 {
+        return Err(Error::Length);
+    }
+
+    let cipher = Encryptor::<C>::new_from_slices(enc_key, iv).map_err(|_| Error::Length)?;
+    let ciphertext = cipher.encrypt_padded_vec_mut::<Pkcs7>(plaintext);
+
+    let tag = mac::<M>(mac_key, iv, aad, &ciphertext)?
+        .finalize()
+        .into_bytes();
+    Ok((ciphertext, tag[..mac_key.len()].to_vec()))
+}
+
+fn open<C, M>(
+    cek: &[u8],
+    iv: &[u8],
+    aad: &[u8],
+    ciphertext: &[u8],
+    tag: &[u8],
+) -> Result<Vec<u8>, Error>
+where
+    C: BlockCipher + BlockDecryptMut + KeyInit,
+    M: Mac + MacInit,
+{
+    let (mac_key, enc_key) = cek.split_at(cek.len() / 2);
+    if cek.len() != C::key_size() * 2 {
+        return Err(Error::Length);
+    }
+
+    let cipher = Decryptor::<C>::new_from_slices(enc_key, iv).map_err(|_| Error::Length)?;
+
+    // The tag is checked in constant time before anything is decrypted.
+    if tag.len() != mac_key.len() {
+        return Err(Error::Invalid);
+    }
+
+    mac::<M>(mac_key, iv, aad, ciphertext)?
+        .verify_truncated_left(tag)
+        .map_err(|_| Error::Invalid)?;
+
+    cipher
+        .decrypt_padded_vec_mut::<Pkcs7>(ciphertext)
+        .map_err(|_| Error::Invalid)
+}
+
+/// Encrypts the plaintext, returning the ciphertext and the tag.
+pub(super) fn encrypt(
+    enc: Encryption,
+    cek: &[u8],
+    iv: &[u8],
+    aad: &[u8],
+    plaintext: &[u8],
+) -> Result<(Vec<u8>, Vec<u8>), Error> {
+    match enc {
+        Encryption::A128CbcHs256 => seal::<Aes128, Hmac<Sha256>>(cek, iv, aad, plaintext),
+        Encryption::A192CbcHs384 => seal::<Aes192, Hmac<Sha384>>(cek, iv, aad, plaintext),
+        Encryption::A256CbcHs512 => seal::<Aes256, Hmac<Sha512>>(cek, iv, aad, plaintext),
+        _ => Err(Error::Unsupported),
+    }
+}
+
+/// Decrypts the ciphertext after checking the tag.
+pub(super) fn decrypt(
+    enc: Encryption,
+    cek: &[u8],
+    iv: &[u8],
+    aad: &[u8],
+    ciphertext: &[u8],
+    tag: &[u8],
+) -> Result<Vec<u8>, Error> {
+    match enc {
+        Encryption::A128CbcHs256 => open::<Aes128, Hmac<Sha256>>(cek, iv, aad, ciphertext, tag),
+        Encryption::A192CbcHs384 => open::<Aes192, Hmac<Sha384>>(cek, iv, aad, ciphertext, tag),
+        Encryption::A256CbcHs512 => open::<Aes256, Hmac<Sha512>>(cek, iv, aad, ciphertext, tag),
+        _ => Err(Error::Unsupported),
+    }
+}
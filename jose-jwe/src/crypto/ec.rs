@@ -0,0 +1,81 @@
+// SPDX-FileCopyrightText: 2022 Profian Inc. <opensource@profian.com>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! ECDH-ES with the NIST curves
+
+#![cfg(any(feature = "p256", feature = "p384", feature = "p521"))]
+
+use alloc::vec::Vec;
+
+use elliptic_curve::ecdh::{diffie_hellman, EphemeralSecret};
+use elliptic_curve::{CurveArithmetic, PublicKey, SecretKey};
+use jose_jwk::{crypto::KeyInfo, Ec, Jwk};
+use rand_core::CryptoRngCore;
+
+use super::{ecdh, supported, Cek, DecryptingKey, EncryptingKey, Error};
+use crate::Protected;
+
+impl<C> EncryptingKey for PublicKey<C>
+where
+    C: CurveArithmetic,
+    PublicKey<C>: KeyInfo,
+    for<'a> Ec: From<&'a PublicKey<C>>,
+{
+    fn encrypt_key(
+        &self,
+        head: &mut Protected,
+        rng: &mut impl CryptoRngCore,
+    ) -> Result<(Cek, Option<Vec<u8>>), Error> {
+        supported(self, ecdh::algorithm(head)?)?;
+
+        let secret = EphemeralSecret::<C>::random(rng);
+        head.oth.epk = Some(Jwk {
+            key: Ec::from(&secret.public_key()).into(),
+            prm: Default::default(),
+        });
+
+        let z = secret.diffie_hellman(self);
+        ecdh::seal(z.raw_secret_bytes(), head, rng)
+    }
+}
+
+impl<C> EncryptingKey for SecretKey<C>
+where
+    C: CurveArithmetic,
+    PublicKey<C>: EncryptingKey,
+{
+    fn encrypt_key(
+        &self,
+        head: &mut Protected,
+        rng: &mut impl CryptoRngCore,
+    ) -> Result<(Cek, Option<Vec<u8>>), Error> {
+        self.public_key().encrypt_key(head, rng)
+    }
+}
+
+impl<C> DecryptingKey for SecretKey<C>
+where
+    C: CurveArithmetic,
+    SecretKey<C>: KeyInfo,
+    for<'a> PublicKey<C>: TryFrom<&'a Ec>,
+{
+    fn decrypt_key(
+        &self,
+        head: &Protected,
+        ekey: Option<&[u8]>,
+        _rng: &mut impl CryptoRngCore,
+    ) -> Result<Cek, Error> {
+        supported(self, ecdh::algorithm(head)?)?;
+
+        // The conversion checks that the ephemeral public key is a point on
+        // the curve of this key, which prevents invalid-curve attacks.
+        let epk = match head.oth.epk.as_ref().map(|epk| &epk.key) {
+            Some(jose_jwk::Key::Ec(epk)) => PublicKey::<C>::try_from(epk),
+            _ => return Err(Error::Epk),
+        };
+
+        let epk = epk.map_err(|_| Error::Epk)?;
+        let z = diffie_hellman(self.to_nonzero_scalar(), epk.as_affine());
+        ecdh::open(z.raw_secret_bytes(), head, ekey)
+    }
+}
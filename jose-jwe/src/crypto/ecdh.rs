@@ -0,0 +1,118 @@
+// SPDX-FileCopyrightText: 2022 Profian Inc. <opensource@profian.com>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! ECDH-ES key agreement (RFC 7518 Section 4.6)
+//!
+//! The curves are P-256, P-384, P-521 and X25519. X448 is not supported,
+//! since there is no X448 implementation for this crate to build on.
+
+#![cfg(any(
+    feature = "p256",
+    feature = "p384",
+    feature = "p521",
+    feature = "x25519"
+))]
+
+use alloc::{string::ToString, vec, vec::Vec};
+
+use jose_jwa::KeyManagement;
+use rand_core::CryptoRngCore;
+use sha2::Sha256;
+use zeroize::Zeroizing;
+
+use super::{algorithms, Cek, Error};
+use crate::Protected;
+
+/// Returns the key management algorithm if it is an ECDH-ES algorithm.
+pub(super) fn algorithm(head: &Protected) -> Result<KeyManagement, Error> {
+    match algorithms(head)?.0 {
+        alg @ (KeyManagement::EcdhEs
+        | KeyManagement::EcdhEsA128Kw
+        | KeyManagement::EcdhEsA192Kw
+        | KeyManagement::EcdhEsA256Kw) => Ok(alg),
+        _ => Err(jose_jwk::crypto::Error::Unsupported.into()),
+    }
+}
+
+/// Derives a key from the shared secret with the Concat KDF (RFC 7518
+/// Section 4.6.2).
+///
+/// For direct key agreement, the key is the CEK. Otherwise, it wraps the
+/// CEK.
+fn derive(z: &[u8], head: &Protected) -> Result<(KeyManagement, Cek, usize), Error> {
+    let (alg, len) = algorithms(head)?;
+
+    let (id, size) = match alg {
+        KeyManagement::EcdhEs => (head.oth.enc.ok_or(Error::MissingEnc)?.to_string(), len),
+        KeyManagement::EcdhEsA128Kw => (alg.to_string(), 16),
+        KeyManagement::EcdhEsA192Kw => (alg.to_string(), 24),
+        KeyManagement::EcdhEsA256Kw => (alg.to_string(), 32),
+        _ => return Err(Error::Unsupported),
+    };
+
+    let apu = head.oth.apu.as_ref().map_or(&[][..], |x| x.as_ref());
+    let apv = head.oth.apv.as_ref().map_or(&[][..], |x| x.as_ref());
+
+    let mut info = Vec::new();
+    for field in [id.as_bytes(), apu, apv] {
+        let n = u32::try_from(field.len()).map_err(|_| Error::Length)?;
+        info.extend_from_slice(&n.to_be_bytes());
+        info.extend_from_slice(field);
+    }
+
+    let bits = u32::try_from(size * 8).map_err(|_| Error::Length)?;
+    info.extend_from_slice(&bits.to_be_bytes());
+
+    let mut key = Zeroizing::new(vec![0; size]);
+    concat_kdf::derive_key_into::<Sha256>(z, &info, &mut key).map_err(|_| Error::Length)?;
+    Ok((alg, key, len))
+}
+
+/// Determines the CEK and the encrypted key from the shared secret.
+#[cfg_attr(not(feature = "aes-kw"), allow(unused_variables))]
+pub(super) fn seal(
+    z: &[u8],
+    head: &Protected,
+    rng: &mut impl CryptoRngCore,
+) -> Result<(Cek, Option<Vec<u8>>), Error> {
+    match derive(z, head)? {
+        (KeyManagement::EcdhEs, cek, _) => Ok((cek, None)),
+
+        #[cfg(feature = "aes-kw")]
+        (_, kek, len) => {
+            let mut cek = Zeroizing::new(vec![0; len]);
+            rng.fill_bytes(&mut cek);
+            let ekey = super::kw::wrap(&kek, &cek)?;
+            Ok((cek, Some(ekey)))
+        }
+
+        #[allow(unreachable_patterns)]
+        _ => Err(Error::Unsupported),
+    }
+}
+
+/// Recovers the CEK from the shared secret and the encrypted key.
+#[cfg_attr(not(feature = "aes-kw"), allow(unused_variables))]
+pub(super) fn open(z: &[u8], head: &Protected, ekey: Option<&[u8]>) -> Result<Cek, Error> {
+    let (alg, key, len) = derive(z, head)?;
+
+    match (alg, ekey) {
+        // RFC 7516 Section 5.2, step 10: the encrypted key must be empty.
+        (KeyManagement::EcdhEs, None | Some([])) => Ok(key),
+        (KeyManagement::EcdhEs, Some(_)) => Err(Error::Invalid),
+
+        #[cfg(feature = "aes-kw")]
+        (_, ekey) => {
+            let cek = super::kw::unwrap(&key, ekey.ok_or(Error::Invalid)?)?;
+            let cek = Zeroizing::new(cek);
+            if cek.len() != len {
+                return Err(Error::Length);
+            }
+
+            Ok(cek)
+        }
+
+        #[allow(unreachable_patterns)]
+        _ => Err(Error::Unsupported),
+    }
+}
@@ -0,0 +1,100 @@
+// SPDX-FileCopyrightText: 2022 Profian Inc. <opensource@profian.com>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! AES GCM (RFC 7518 Section 5.3)
+
+#![cfg(feature = "aes-gcm")]
+
+use alloc::vec::Vec;
+
+use aes_gcm::aead::consts::{U12, U16};
+use aes_gcm::aead::{AeadCore, AeadInPlace, KeyInit, Nonce, Tag};
+use aes_gcm::aes::{Aes128, Aes192, Aes256};
+use aes_gcm::AesGcm;
+use jose_jwa::Encryption;
+
+use super::Error;
+
+/// The size of the initialization vector.
+const IV: usize = 12;
+
+/// The size of the authentication tag.
+const TAG: usize = 16;
+
+fn seal<C>(cek: &[u8], iv: &[u8], aad: &[u8], plaintext: &[u8]) -> Result<(Vec<u8>, Vec<u8>), Error>
+where
+    C: AeadInPlace + KeyInit + AeadCore<NonceSize = U12, TagSize = U16>,
+{
+    if iv.len() != IV {
+        return Err(Error::Length);
+    }
+
+    let cipher = C::new_from_slice(cek).map_err(|_| Error::Length)?;
+    let mut buf = plaintext.to_vec();
+    let tag = cipher
+        .encrypt_in_place_detached(<&Nonce<C>>::from(iv), aad, &mut buf)
+        .map_err(|_| Error::Length)?;
+
+    Ok((buf, tag.to_vec()))
+}
+
+fn open<C>(
+    cek: &[u8],
+    iv: &[u8],
+    aad: &[u8],
+    ciphertext: &[u8],
+    tag: &[u8],
+) -> Result<Vec<u8>, Error>
+where
+    C: AeadInPlace + KeyInit + AeadCore<NonceSize = U12, TagSize = U16>,
+{
+    if iv.len() != IV {
+        return Err(Error::Length);
+    }
+
+    if tag.len() != TAG {
+        return Err(Error::Invalid);
+    }
+
+    let cipher = C::new_from_slice(cek).map_err(|_| Error::Length)?;
+    let nonce = <&Nonce<C>>::from(iv);
+    let mut buf = ciphertext.to_vec();
+    cipher
+        .decrypt_in_place_detached(nonce, aad, &mut buf, <&Tag<C>>::from(tag))
+        .map_err(|_| Error::Invalid)?;
+
+    Ok(buf)
+}
+
+/// Encrypts the plaintext, returning the ciphertext and the tag.
+pub(super) fn encrypt(
+    enc: Encryption,
+    cek: &[u8],
+    iv: &[u8],
+    aad: &[u8],
+    plaintext: &[u8],
+) -> Result<(Vec<u8>, Vec<u8>), Error> {
+    match enc {
+        Encryption::A128Gcm => seal::<AesGcm<Aes128, U12>>(cek, iv, aad, plaintext),
+        Encryption::A192Gcm => seal::<AesGcm<Aes192, U12>>(cek, iv, aad, plaintext),
+        Encryption::A256Gcm => seal::<AesGcm<Aes256, U12>>(cek, iv, aad, plaintext),
+        _ => Err(Error::Unsupported),
+    }
+}
+
+/// Decrypts the ciphertext after checking the tag.
+pub(super) fn decrypt(
+    enc: Encryption,
+    cek: &[u8],
+    iv: &[u8],
+    aad: &[u8],
+    ciphertext: &[u8],
+    tag: &[u8],
+) -> Result<Vec<u8>, Error> {
+    match enc {
+        Encryption::A128Gcm => open::<AesGcm<Aes128, U12>>(cek, iv, aad, ciphertext, tag),
+        Encryption::A192Gcm => open::<AesGcm<Aes192, U12>>(cek, iv, aad, ciphertext, tag),
+        Encryption::A256Gcm => open::<AesGcm<Aes256, U12>>(cek, iv, aad, ciphertext, tag),
+        _ => Err(Error::Unsupported),
+    }
+}
@@ -0,0 +1,98 @@
+// SPDX-FileCopyrightText: 2022 Profian Inc. <opensource@profian.com>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use alloc::vec::Vec;
+
+use jose_jwk::crypto::{Key, Kind};
+use rand_core::CryptoRngCore;
+
+use super::{Cek, DecryptingKey, EncryptingKey, Error};
+use crate::Protected;
+
+impl<P: EncryptingKey, S: EncryptingKey> EncryptingKey for Kind<P, S> {
+    fn encrypt_key(
+        &self,
+        head: &mut Protected,
+        rng: &mut impl CryptoRngCore,
+    ) -> Result<(Cek, Option<Vec<u8>>), Error> {
+        match self {
+            Self::Public(key) => key.encrypt_key(head, rng),
+            Self::Secret(key) => key.encrypt_key(head, rng),
+        }
+    }
+}
+
+impl<P, S: DecryptingKey> DecryptingKey for Kind<P, S> {
+    fn decrypt_key(
+        &self,
+        head: &Protected,
+        ekey: Option<&[u8]>,
+        rng: &mut impl CryptoRngCore,
+    ) -> Result<Cek, Error> {
+        match self {
+            Self::Public(..) => Err(jose_jwk::crypto::Error::NotPrivate.into()),
+            Self::Secret(key) => key.decrypt_key(head, ekey, rng),
+        }
+    }
+}
+
+impl EncryptingKey for Key {
+    fn encrypt_key(
+        &self,
+        head: &mut Protected,
+        rng: &mut impl CryptoRngCore,
+    ) -> Result<(Cek, Option<Vec<u8>>), Error> {
+        match self {
+            Self::Oct(key) => key.encrypt_key(head, rng),
+
+            #[cfg(feature = "rsa")]
+            Self::Rsa(key) => key.encrypt_key(head, rng),
+
+            #[cfg(feature = "p256")]
+            Self::P256(key) => key.encrypt_key(head, rng),
+
+            #[cfg(feature = "p384")]
+            Self::P384(key) => key.encrypt_key(head, rng),
+
+            #[cfg(feature = "p521")]
+            Self::P521(key) => key.encrypt_key(head, rng),
+
+            #[cfg(feature = "x25519")]
+            Self::X25519(key) => key.encrypt_key(head, rng),
+
+            #[allow(unreachable_patterns)]
+            _ => Err(Error::Unsupported),
+        }
+    }
+}
+
+impl DecryptingKey for Key {
+    fn decrypt_key(
+        &self,
+        head: &Protected,
+        ekey: Option<&[u8]>,
+        rng: &mut impl CryptoRngCore,
+    ) -> Result<Cek, Error> {
+        match self {
+            Self::Oct(key) => key.decrypt_key(head, ekey, rng),
+
+            #[cfg(feature = "rsa")]
+            Self::Rsa(key) => key.decrypt_key(head, ekey, rng),
+
+            #[cfg(feature = "p256")]
+            Self::P256(key) => key.decrypt_key(head, ekey, rng),
+
+            #[cfg(feature = "p384")]
+            Self::P384(key) => key.decrypt_key(head, ekey, rng),
+
+            #[cfg(feature = "p521")]
+            Self::P521(key) => key.decrypt_key(head, ekey, rng),
+
+            #[cfg(feature = "x25519")]
+            Self::X25519(key) => key.decrypt_key(head, ekey, rng),
+
+            #[allow(unreachable_patterns)]
+            _ => Err(Error::Unsupported),
+        }
+    }
+}
@@ -0,0 +1,48 @@
+// SPDX-FileCopyrightText: 2022 Profian Inc. <opensource@profian.com>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! AES Key Wrap (RFC 7518 Section 4.4)
+
+#![cfg(feature = "aes-kw")]
+
+use alloc::vec::Vec;
+
+use aes_kw::{KekAes128, KekAes192, KekAes256};
+
+use super::Error;
+
+/// Wraps the content encryption key with the key encryption key.
+///
+/// The AES key size is selected by the size of the key encryption key.
+pub(super) fn wrap(kek: &[u8], cek: &[u8]) -> Result<Vec<u8>, Error> {
+    match kek.len() {
+        16 => KekAes128::try_from(kek)
+            .map_err(|_| Error::Length)?
+            .wrap_vec(cek),
+        24 => KekAes192::try_from(kek)
+            .map_err(|_| Error::Length)?
+            .wrap_vec(cek),
+        32 => KekAes256::try_from(kek)
+            .map_err(|_| Error::Length)?
+            .wrap_vec(cek),
+        _ => return Err(Error::Length),
+    }
+    .map_err(|_| Error::Length)
+}
+
+/// Unwraps the content encryption key, checking its integrity.
+pub(super) fn unwrap(kek: &[u8], ekey: &[u8]) -> Result<Vec<u8>, Error> {
+    match kek.len() {
+        16 => KekAes128::try_from(kek)
+            .map_err(|_| Error::Length)?
+            .unwrap_vec(ekey),
+        24 => KekAes192::try_from(kek)
+            .map_err(|_| Error::Length)?
+            .unwrap_vec(ekey),
+        32 => KekAes256::try_from(kek)
+            .map_err(|_| Error::Length)?
+            .unwrap_vec(ekey),
+        _ => return Err(Error::Length),
+    }
+    .map_err(|_| Error::Invalid)
+}
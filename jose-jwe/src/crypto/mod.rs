@@ -0,0 +1,373 @@
+// SPDX-FileCopyrightText: 2022 Profian Inc. <opensource@profian.com>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! JWE Cryptographic Implementation
+//!
+//! The algorithms are enabled by crate features:
+//!
+//! - `aes-cbc-hs` and `aes-gcm`: content encryption
+//! - `aes-kw`: AES key wrap
+//! - `p256`, `p384`, `p521` and `x25519`: ECDH-ES key agreement
+//! - `rsa`: RSA-OAEP and RSA-OAEP-256
+//! - `rsa1_5`: RSA1_5, which is not enabled by default
+//!
+//! Direct encryption with a shared symmetric key needs no feature of its own.
+//!
+//! # RSA1_5
+//!
+//! The PKCS#1 v1.5 decryption of the `rsa` crate is not constant time
+//! (RUSTSEC-2023-0071, the Marvin attack). An attacker who can time many
+//! decryptions with a private key may decrypt ciphertexts of their choosing,
+//! and thus recover the content encryption key of other messages, despite
+//! the random key substitution of RFC 7516 Section 11.5. Only enable the
+//! `rsa1_5` feature when RSA1_5 must be accepted, and prefer RSA-OAEP.
+
+#![cfg(any(feature = "aes-cbc-hs", feature = "aes-gcm"))]
+
+mod cbc;
+mod ec;
+mod ecdh;
+mod gcm;
+mod key;
+mod kw;
+mod oct;
+mod rsa;
+mod x25519;
+
+use alloc::{vec, vec::Vec};
+
+use jose_b64::base64ct::{Base64UrlUnpadded, Encoding};
+use jose_b64::serde::{Bytes, Json};
+use jose_jwa::{Encryption, KeyManagement};
+use jose_jwk::crypto::KeyInfo;
+use rand_core::CryptoRngCore;
+use zeroize::Zeroizing;
+
+use crate::{Flattened, General, HeaderError, Protected, Recipient};
+
+/// A JWE cryptographic error
+#[derive(Debug, Default)]
+#[non_exhaustive]
+pub enum Error {
+    /// The ciphertext or the authentication tag is invalid.
+    #[default]
+    Invalid,
+
+    /// The header does not specify a key management algorithm.
+    MissingAlg,
+
+    /// The header does not specify a content encryption algorithm.
+    MissingEnc,
+
+    /// A key or the initialization vector has the wrong length for the
+    /// algorithm.
+    Length,
+
+    /// The algorithm is not supported.
+    Unsupported,
+
+    /// The key cannot be used with the algorithm.
+    Key(jose_jwk::crypto::Error),
+
+    /// The protected header could not be serialized.
+    Json(serde_json::Error),
+
+    /// The ephemeral public key is missing, malformed or not on the curve of
+    /// the key of the recipient.
+    Epk,
+
+    /// The headers are malformed or not disjoint.
+    Header(HeaderError),
+
+    /// The `crit` header parameter lists an extension that is not
+    /// understood.
+    Critical,
+}
+
+impl From<jose_jwk::crypto::Error> for Error {
+    fn from(value: jose_jwk::crypto::Error) -> Self {
+        Self::Key(value)
+    }
+}
+
+impl From<HeaderError> for Error {
+    fn from(value: HeaderError) -> Self {
+        Self::Header(value)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(value: serde_json::Error) -> Self {
+        Self::Json(value)
+    }
+}
+
+/// A content encryption key
+pub type Cek = Zeroizing<Vec<u8>>;
+
+/// A key that determines the content encryption key for a recipient.
+pub trait EncryptingKey {
+    /// Returns the content encryption key and, unless the key management
+    /// algorithm leaves it empty, the JWE Encrypted Key.
+    ///
+    /// The algorithms are taken from the header, to which the key may add
+    /// the parameters of the key management algorithm.
+    fn encrypt_key(
+        &self,
+        head: &mut Protected,
+        rng: &mut impl CryptoRngCore,
+    ) -> Result<(Cek, Option<Vec<u8>>), Error>;
+}
+
+/// A key that recovers the content encryption key of a recipient.
+pub trait DecryptingKey {
+    /// Returns the content encryption key from the JWE Encrypted Key.
+    ///
+    /// The algorithms are taken from the header, which is the union of the
+    /// headers that apply to the recipient.
+    fn decrypt_key(
+        &self,
+        head: &Protected,
+        ekey: Option<&[u8]>,
+        rng: &mut impl CryptoRngCore,
+    ) -> Result<Cek, Error>;
+}
+
+/// Returns the key management algorithm and the size of the content
+/// encryption key from the header.
+fn algorithms(head: &Protected) -> Result<(KeyManagement, usize), Error> {
+    let alg = head.oth.alg.ok_or(Error::MissingAlg)?;
+    let enc = head.oth.enc.ok_or(Error::MissingEnc)?;
+    Ok((alg, lengths(enc)?.0))
+}
+
+/// Checks that the key is suitable for the key management algorithm.
+fn supported(key: &(impl KeyInfo + ?Sized), alg: KeyManagement) -> Result<(), Error> {
+    if !key.is_supported(&alg.into()) {
+        return Err(jose_jwk::crypto::Error::Unsupported.into());
+    }
+
+    Ok(())
+}
+
+/// Returns the sizes of the content encryption key and of the
+/// initialization vector for the algorithm.
+pub(crate) fn lengths(enc: Encryption) -> Result<(usize, usize), Error> {
+    Ok(match enc {
+        Encryption::A128CbcHs256 => (32, 16),
+        Encryption::A192CbcHs384 => (48, 16),
+        Encryption::A256CbcHs512 => (64, 16),
+        Encryption::A128Gcm => (16, 12),
+        Encryption::A192Gcm => (24, 12),
+        Encryption::A256Gcm => (32, 12),
+        _ => return Err(Error::Unsupported),
+    })
+}
+
+/// Checks the sizes of the content encryption key and the initialization
+/// vector.
+fn check(enc: Encryption, cek: &[u8], iv: &[u8]) -> Result<(), Error> {
+    if lengths(enc)? != (cek.len(), iv.len()) {
+        return Err(Error::Length);
+    }
+
+    Ok(())
+}
+
+/// Encrypts the plaintext with the content encryption key (RFC 7518
+/// Section 5).
+///
+/// The ciphertext and the authentication tag are returned.
+pub fn encrypt(
+    enc: Encryption,
+    cek: &[u8],
+    iv: &[u8],
+    aad: &[u8],
+    plaintext: &[u8],
+) -> Result<(Vec<u8>, Vec<u8>), Error> {
+    check(enc, cek, iv)?;
+
+    match enc {
+        #[cfg(feature = "aes-cbc-hs")]
+        Encryption::A128CbcHs256 | Encryption::A192CbcHs384 | Encryption::A256CbcHs512 => {
+            cbc::encrypt(enc, cek, iv, aad, plaintext)
+        }
+
+        #[cfg(feature = "aes-gcm")]
+        Encryption::A128Gcm | Encryption::A192Gcm | Encryption::A256Gcm => {
+            gcm::encrypt(enc, cek, iv, aad, plaintext)
+        }
+
+        #[allow(unreachable_patterns)]
+        _ => Err(Error::Unsupported),
+    }
+}
+
+/// Decrypts the ciphertext with the content encryption key (RFC 7518
+/// Section 5).
+///
+/// The authentication tag is checked before the plaintext is returned.
+pub fn decrypt(
+    enc: Encryption,
+    cek: &[u8],
+    iv: &[u8],
+    aad: &[u8],
+    ciphertext: &[u8],
+    tag: &[u8],
+) -> Result<Vec<u8>, Error> {
+    check(enc, cek, iv)?;
+
+    match enc {
+        #[cfg(feature = "aes-cbc-hs")]
+        Encryption::A128CbcHs256 | Encryption::A192CbcHs384 | Encryption::A256CbcHs512 => {
+            cbc::decrypt(enc, cek, iv, aad, ciphertext, tag)
+        }
+
+        #[cfg(feature = "aes-gcm")]
+        Encryption::A128Gcm | Encryption::A192Gcm | Encryption::A256Gcm => {
+            gcm::decrypt(enc, cek, iv, aad, ciphertext, tag)
+        }
+
+        #[allow(unreachable_patterns)]
+        _ => Err(Error::Unsupported),
+    }
+}
+
+/// Returns the additional authenticated data of the content encryption
+/// (RFC 7516 Section 5.1, step 14).
+fn aad(protected: Option<&Json<Protected>>, aad: Option<&Bytes>) -> Vec<u8> {
+    let prot = protected.map_or(&[][..], |p| p.as_ref());
+    let mut all = Base64UrlUnpadded::encode_string(prot).into_bytes();
+
+    if let Some(aad) = aad {
+        all.push(b'.');
+        all.extend_from_slice(Base64UrlUnpadded::encode_string(aad).as_bytes());
+    }
+
+    all
+}
+
+/// Checks that the header requires no processing that is not implemented.
+///
+/// Compression (RFC 7516 Section 4.1.3) is not supported and no extension
+/// is understood, so any `crit` entry (Section 4.1.13) is rejected.
+fn processable(head: Protected) -> Result<Protected, Error> {
+    if head.zip.is_some() {
+        return Err(Error::Unsupported);
+    }
+
+    match head.crit {
+        Some(_) => Err(Error::Critical),
+        None => Ok(head),
+    }
+}
+
+impl Flattened {
+    /// Encrypts the plaintext for a single recipient.
+    ///
+    /// The protected header must specify the `alg` and `enc` parameters.
+    /// The initialization vector is random. Compression and critical
+    /// extensions are not supported, so the `zip` and `crit` parameters must
+    /// be unset: the result could not be decrypted otherwise.
+    pub fn encrypt(
+        key: &(impl EncryptingKey + ?Sized),
+        prot: Protected,
+        plaintext: &[u8],
+        rng: &mut impl CryptoRngCore,
+    ) -> Result<Self, Error> {
+        let mut prot = processable(prot)?;
+        let (cek, ekey) = key.encrypt_key(&mut prot, rng)?;
+        let enc = prot.oth.enc.ok_or(Error::MissingEnc)?;
+
+        let mut iv = vec![0; lengths(enc)?.1];
+        rng.fill_bytes(&mut iv);
+
+        let prot = Json::new(prot)?;
+        let aad = aad(Some(&prot), None);
+        let (ciphertext, tag) = encrypt(enc, &cek, &iv, &aad, plaintext)?;
+
+        Ok(Self {
+            protected: Some(prot),
+            unprotected: None,
+            recipient: Recipient {
+                header: None,
+                encrypted_key: ekey.map(Bytes::from),
+            },
+            aad: None,
+            iv: Some(iv.into()),
+            ciphertext: ciphertext.into(),
+            tag: Some(tag.into()),
+        })
+    }
+
+    /// Decrypts the content with the key of the recipient.
+    ///
+    /// The random number generator is used by the key management algorithms
+    /// that need it, such as for RSA blinding. Content that is compressed or
+    /// that lists a critical extension is rejected.
+    pub fn decrypt(
+        &self,
+        key: &(impl DecryptingKey + ?Sized),
+        rng: &mut impl CryptoRngCore,
+    ) -> Result<Vec<u8>, Error> {
+        let ekey = self.recipient.encrypted_key.as_ref().map(AsRef::as_ref);
+        let cek = key.decrypt_key(&processable(self.header()?)?, ekey, rng)?;
+        self.decrypt_content(&cek)
+    }
+
+    /// Decrypts the content with the content encryption key.
+    ///
+    /// The algorithm is the `enc` header parameter. The key is normally
+    /// obtained by decrypting the encrypted key of the recipient.
+    pub fn decrypt_content(&self, cek: &[u8]) -> Result<Vec<u8>, Error> {
+        let enc = processable(self.header()?)?
+            .oth
+            .enc
+            .ok_or(Error::MissingEnc)?;
+        let aad = aad(self.protected.as_ref(), self.aad.as_ref());
+        let iv = self.iv.as_ref().map_or(&[][..], |iv| iv.as_ref());
+        let tag = self.tag.as_ref().map_or(&[][..], |tag| tag.as_ref());
+        decrypt(enc, cek, iv, &aad, &self.ciphertext, tag)
+    }
+}
+
+impl General {
+    /// Decrypts the content with the key of one of the recipients.
+    ///
+    /// The recipients are tried in order. If none succeeds, the error of the
+    /// last one is returned.
+    pub fn decrypt(
+        &self,
+        key: &(impl DecryptingKey + ?Sized),
+        rng: &mut impl CryptoRngCore,
+    ) -> Result<Vec<u8>, Error> {
+        let mut error = Error::Invalid;
+
+        for recipient in &self.recipients {
+            let ekey = recipient.encrypted_key.as_ref().map(AsRef::as_ref);
+            let head = self.header(recipient).map_err(Error::from);
+            let cek = head
+                .and_then(processable)
+                .and_then(|head| key.decrypt_key(&head, ekey, rng));
+            match cek.and_then(|cek| self.decrypt_content(&cek)) {
+                Ok(plaintext) => return Ok(plaintext),
+                Err(e) => error = e,
+            }
+        }
+
+        Err(error)
+    }
+
+    /// Decrypts the content with the content encryption key.
+    ///
+    /// See [`Flattened::decrypt_content`]. The algorithm must be specified in
+    /// the protected header or the shared unprotected header.
+    pub fn decrypt_content(&self, cek: &[u8]) -> Result<Vec<u8>, Error> {
+        let enc = processable(self.header(&Recipient::default())?)?.oth.enc;
+        let enc = enc.ok_or(Error::MissingEnc)?;
+        let aad = aad(self.protected.as_ref(), self.aad.as_ref());
+        let iv = self.iv.as_ref().map_or(&[][..], |iv| iv.as_ref());
+        let tag = self.tag.as_ref().map_or(&[][..], |tag| tag.as_ref());
+        decrypt(enc, cek, iv, &aad, &self.ciphertext, tag)
+    }
+}
@@ -0,0 +1,94 @@
+// SPDX-FileCopyrightText: 2022 Profian Inc. <opensource@profian.com>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use alloc::vec::Vec;
+
+use jose_jwa::KeyManagement;
+use jose_jwk::Oct;
+use rand_core::CryptoRngCore;
+use zeroize::Zeroizing;
+
+use super::{algorithms, supported, Cek, DecryptingKey, EncryptingKey, Error};
+use crate::Protected;
+
+impl EncryptingKey for [u8] {
+    #[cfg_attr(not(feature = "aes-kw"), allow(unused_variables))]
+    fn encrypt_key(
+        &self,
+        head: &mut Protected,
+        rng: &mut impl CryptoRngCore,
+    ) -> Result<(Cek, Option<Vec<u8>>), Error> {
+        let (alg, len) = algorithms(head)?;
+        supported(self, alg)?;
+
+        match alg {
+            KeyManagement::Direct if self.len() != len => Err(Error::Length),
+            KeyManagement::Direct => Ok((Zeroizing::new(self.to_vec()), None)),
+
+            #[cfg(feature = "aes-kw")]
+            KeyManagement::A128Kw | KeyManagement::A192Kw | KeyManagement::A256Kw => {
+                let mut cek = Zeroizing::new(alloc::vec![0; len]);
+                rng.fill_bytes(&mut cek);
+                let ekey = super::kw::wrap(self, &cek)?;
+                Ok((cek, Some(ekey)))
+            }
+
+            _ => Err(Error::Unsupported),
+        }
+    }
+}
+
+impl DecryptingKey for [u8] {
+    fn decrypt_key(
+        &self,
+        head: &Protected,
+        ekey: Option<&[u8]>,
+        _rng: &mut impl CryptoRngCore,
+    ) -> Result<Cek, Error> {
+        let (alg, len) = algorithms(head)?;
+        supported(self, alg)?;
+
+        let cek = match (alg, ekey) {
+            // RFC 7516 Section 5.2, step 10: the encrypted key must be empty.
+            (KeyManagement::Direct, None | Some([])) => Zeroizing::new(self.to_vec()),
+            (KeyManagement::Direct, Some(_)) => return Err(Error::Invalid),
+
+            #[cfg(feature = "aes-kw")]
+            (KeyManagement::A128Kw | KeyManagement::A192Kw | KeyManagement::A256Kw, ekey) => {
+                let ekey = ekey.ok_or(Error::Invalid)?;
+                Zeroizing::new(super::kw::unwrap(self, ekey)?)
+            }
+
+            _ => return Err(Error::Unsupported),
+        };
+
+        if cek.len() != len {
+            return Err(Error::Length);
+        }
+
+        Ok(cek)
+    }
+}
+
+impl EncryptingKey for Oct {
+    fn encrypt_key(
+        &self,
+        head: &mut Protected,
+        rng: &mut impl CryptoRngCore,
+    ) -> Result<(Cek, Option<Vec<u8>>), Error> {
+        let k: &[u8] = &self.k;
+        k.encrypt_key(head, rng)
+    }
+}
+
+impl DecryptingKey for Oct {
+    fn decrypt_key(
+        &self,
+        head: &Protected,
+        ekey: Option<&[u8]>,
+        rng: &mut impl CryptoRngCore,
+    ) -> Result<Cek, Error> {
+        let k: &[u8] = &self.k;
+        k.decrypt_key(head, ekey, rng)
+    }
+}
@@ -0,0 +1,96 @@
+// SPDX-FileCopyrightText: 2022 Profian Inc. <opensource@profian.com>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! RSAES-OAEP and RSAES-PKCS1-v1_5 (RFC 7518 Sections 4.2 and 4.3)
+
+#![cfg(feature = "rsa")]
+
+use alloc::{vec, vec::Vec};
+
+use jose_jwa::KeyManagement;
+use rand_core::CryptoRngCore;
+use rsa::{Oaep, RsaPrivateKey, RsaPublicKey};
+use zeroize::Zeroizing;
+
+use super::{algorithms, supported, Cek, DecryptingKey, EncryptingKey, Error};
+use crate::Protected;
+
+impl EncryptingKey for RsaPublicKey {
+    fn encrypt_key(
+        &self,
+        head: &mut Protected,
+        rng: &mut impl CryptoRngCore,
+    ) -> Result<(Cek, Option<Vec<u8>>), Error> {
+        let (alg, len) = algorithms(head)?;
+        supported(self, alg)?;
+
+        let mut cek = Zeroizing::new(vec![0; len]);
+        rng.fill_bytes(&mut cek);
+
+        let ekey = match alg {
+            KeyManagement::RsaOaep => self.encrypt(rng, Oaep::new::<sha1::Sha1>(), &cek),
+            KeyManagement::RsaOaep256 => self.encrypt(rng, Oaep::new::<sha2::Sha256>(), &cek),
+
+            #[cfg(feature = "rsa1_5")]
+            KeyManagement::Rsa1_5 => self.encrypt(rng, rsa::Pkcs1v15Encrypt, &cek),
+
+            _ => return Err(Error::Unsupported),
+        };
+
+        Ok((cek, Some(ekey.map_err(|_| Error::Length)?)))
+    }
+}
+
+impl EncryptingKey for RsaPrivateKey {
+    fn encrypt_key(
+        &self,
+        head: &mut Protected,
+        rng: &mut impl CryptoRngCore,
+    ) -> Result<(Cek, Option<Vec<u8>>), Error> {
+        self.to_public_key().encrypt_key(head, rng)
+    }
+}
+
+impl DecryptingKey for RsaPrivateKey {
+    fn decrypt_key(
+        &self,
+        head: &Protected,
+        ekey: Option<&[u8]>,
+        rng: &mut impl CryptoRngCore,
+    ) -> Result<Cek, Error> {
+        let (alg, len) = algorithms(head)?;
+        supported(self, alg)?;
+        let ekey = ekey.ok_or(Error::Invalid)?;
+
+        let cek = match alg {
+            KeyManagement::RsaOaep => self.decrypt_blinded(rng, Oaep::new::<sha1::Sha1>(), ekey),
+            KeyManagement::RsaOaep256 => {
+                self.decrypt_blinded(rng, Oaep::new::<sha2::Sha256>(), ekey)
+            }
+
+            // RFC 7516 Section 11.5: a malformed encrypted key is replaced
+            // by a random CEK. The failure then surfaces only as an invalid
+            // authentication tag, so it cannot serve as a padding oracle.
+            #[cfg(feature = "rsa1_5")]
+            KeyManagement::Rsa1_5 => {
+                let mut random = Zeroizing::new(vec![0; len]);
+                rng.fill_bytes(&mut random);
+
+                let cek = self.decrypt_blinded(rng, rsa::Pkcs1v15Encrypt, ekey);
+                return Ok(match cek.map(Zeroizing::new) {
+                    Ok(cek) if cek.len() == len => cek,
+                    _ => random,
+                });
+            }
+
+            _ => return Err(Error::Unsupported),
+        };
+
+        let cek = Zeroizing::new(cek.map_err(|_| Error::Invalid)?);
+        if cek.len() != len {
+            return Err(Error::Length);
+        }
+
+        Ok(cek)
+    }
+}
@@ -0,0 +1,79 @@
+// SPDX-FileCopyrightText: 2022 Profian Inc. <opensource@profian.com>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! ECDH-ES with X25519 (RFC 8037 Section 3.2)
+
+#![cfg(feature = "x25519")]
+
+use alloc::vec::Vec;
+
+use jose_jwk::{Jwk, Okp, OkpCurves};
+use rand_core::CryptoRngCore;
+use x25519_dalek::{EphemeralSecret, PublicKey, StaticSecret};
+
+use super::{ecdh, supported, Cek, DecryptingKey, EncryptingKey, Error};
+use crate::Protected;
+
+impl EncryptingKey for PublicKey {
+    fn encrypt_key(
+        &self,
+        head: &mut Protected,
+        rng: &mut impl CryptoRngCore,
+    ) -> Result<(Cek, Option<Vec<u8>>), Error> {
+        supported(self, ecdh::algorithm(head)?)?;
+
+        let secret = EphemeralSecret::random_from_rng(&mut *rng);
+        head.oth.epk = Some(Jwk {
+            key: Okp {
+                crv: OkpCurves::X25519,
+                x: PublicKey::from(&secret).as_bytes().to_vec().into(),
+                d: None,
+            }
+            .into(),
+            prm: Default::default(),
+        });
+
+        // RFC 7748 Section 6.1: a low order public key gives an all-zero
+        // shared secret.
+        let z = secret.diffie_hellman(self);
+        if !z.was_contributory() {
+            return Err(jose_jwk::crypto::Error::Invalid.into());
+        }
+
+        ecdh::seal(z.as_bytes(), head, rng)
+    }
+}
+
+impl EncryptingKey for StaticSecret {
+    fn encrypt_key(
+        &self,
+        head: &mut Protected,
+        rng: &mut impl CryptoRngCore,
+    ) -> Result<(Cek, Option<Vec<u8>>), Error> {
+        PublicKey::from(self).encrypt_key(head, rng)
+    }
+}
+
+impl DecryptingKey for StaticSecret {
+    fn decrypt_key(
+        &self,
+        head: &Protected,
+        ekey: Option<&[u8]>,
+        _rng: &mut impl CryptoRngCore,
+    ) -> Result<Cek, Error> {
+        supported(self, ecdh::algorithm(head)?)?;
+
+        let epk = match head.oth.epk.as_ref().map(|epk| &epk.key) {
+            Some(jose_jwk::Key::Okp(epk)) if epk.crv == OkpCurves::X25519 => epk,
+            _ => return Err(Error::Epk),
+        };
+
+        let epk: [u8; 32] = epk.x.as_ref().try_into().map_err(|_| Error::Epk)?;
+        let z = self.diffie_hellman(&PublicKey::from(epk));
+        if !z.was_contributory() {
+            return Err(Error::Epk);
+        }
+
+        ecdh::open(z.as_bytes(), head, ekey)
+    }
+}
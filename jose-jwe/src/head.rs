@@ -0,0 +1,233 @@
+// SPDX-FileCopyrightText: 2022 Profian Inc. <opensource@profian.com>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use alloc::vec::Vec;
+use alloc::{boxed::Box, string::String};
+use core::fmt;
+
+use jose_b64::base64ct::Base64;
+use jose_b64::serde::Bytes;
+use jose_jwa::{Encryption, KeyManagement};
+use jose_jwk::{Jwk, Thumbprint};
+use serde::de::{IgnoredAny, MapAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{Map, Value};
+
+/// The JWE Protected Header
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Protected {
+    /// RFC 7516 Section 4.1.13
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub crit: Option<Vec<String>>,
+
+    /// RFC 7516 Section 4.1.3
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub zip: Option<String>,
+
+    /// Other values that may appear in the protected header.
+    #[serde(flatten)]
+    pub oth: Unprotected,
+}
+
+/// The JWE Shared Unprotected Header or Per-Recipient Unprotected Header
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Unprotected {
+    /// RFC 7516 Section 4.1.1
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub alg: Option<KeyManagement>,
+
+    /// RFC 7516 Section 4.1.2
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub enc: Option<Encryption>,
+
+    /// RFC 7516 Section 4.1.4
+    #[cfg(feature = "url")]
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub jku: Option<url::Url>,
+
+    /// RFC 7516 Section 4.1.5
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub jwk: Option<Jwk>,
+
+    /// RFC 7516 Section 4.1.6
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub kid: Option<String>,
+
+    /// RFC 7516 Section 4.1.7
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    #[cfg(feature = "url")]
+    pub x5u: Option<url::Url>,
+
+    /// RFC 7516 Section 4.1.8
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub x5c: Option<Vec<Bytes<Box<[u8]>, Base64>>>, // base64, not base64url
+
+    /// RFC 7516 Section 4.1.9-10
+    #[serde(flatten)]
+    pub x5t: Thumbprint,
+
+    /// RFC 7516 Section 4.1.11
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub typ: Option<String>,
+
+    /// RFC 7516 Section 4.1.12
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub cty: Option<String>,
+
+    /// RFC 7518 Section 4.6.1.1
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub epk: Option<Jwk>,
+
+    /// RFC 7518 Section 4.6.1.2
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub apu: Option<Bytes>,
+
+    /// RFC 7518 Section 4.6.1.3
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub apv: Option<Bytes>,
+
+    /// Extension parameters, such as those defined by other specifications.
+    ///
+    /// This collects the members that are not otherwise represented. It
+    /// should not contain the names of the other parameters.
+    #[serde(flatten)]
+    pub ext: Map<String, Value>,
+}
+
+impl Unprotected {
+    /// Fills in the parameters that are unset in `self` from `other`.
+    pub(crate) fn merge(&mut self, other: &Self) {
+        let other = other.clone();
+
+        self.alg = self.alg.or(other.alg);
+        self.enc = self.enc.or(other.enc);
+        self.jwk = self.jwk.take().or(other.jwk);
+        self.kid = self.kid.take().or(other.kid);
+        self.x5c = self.x5c.take().or(other.x5c);
+        self.x5t.s1 = self.x5t.s1.take().or(other.x5t.s1);
+        self.x5t.s256 = self.x5t.s256.take().or(other.x5t.s256);
+        self.typ = self.typ.take().or(other.typ);
+        self.cty = self.cty.take().or(other.cty);
+        self.epk = self.epk.take().or(other.epk);
+        self.apu = self.apu.take().or(other.apu);
+        self.apv = self.apv.take().or(other.apv);
+
+        for (name, value) in other.ext {
+            self.ext.entry(name).or_insert(value);
+        }
+
+        #[cfg(feature = "url")]
+        {
+            self.jku = self.jku.take().or(other.jku);
+            self.x5u = self.x5u.take().or(other.x5u);
+        }
+    }
+}
+
+/// An error in the JOSE header of an encryption.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum HeaderError {
+    /// A parameter appears more than once in the protected header.
+    Duplicate(String),
+
+    /// A parameter appears in more than one of the protected, shared
+    /// unprotected and per-recipient headers (RFC 7516 Section 7.2.1).
+    Overlap(String),
+
+    /// A parameter that must be integrity protected, such as `crit` or
+    /// `zip`, appears in an unprotected header.
+    Unprotected(String),
+
+    /// A header is not a valid JSON object.
+    Json(serde_json::Error),
+}
+
+impl fmt::Display for HeaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Duplicate(name) => write!(f, "duplicate header parameter: {name}"),
+            Self::Overlap(name) => write!(f, "header parameter appears in two headers: {name}"),
+            Self::Unprotected(name) => write!(f, "header parameter must be protected: {name}"),
+            Self::Json(e) => write!(f, "invalid header: {e}"),
+        }
+    }
+}
+
+impl From<serde_json::Error> for HeaderError {
+    fn from(value: serde_json::Error) -> Self {
+        Self::Json(value)
+    }
+}
+
+/// The member names of a JSON object and the first duplicate name, if any.
+//
+// This and `disjoint` mirror `jose-jws/src/head.rs`: fix bugs in both.
+struct Names(Vec<String>, Option<String>);
+
+impl<'de> Deserialize<'de> for Names {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct NamesVisitor;
+
+        impl<'de> Visitor<'de> for NamesVisitor {
+            type Value = Names;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a JSON object")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Names, A::Error> {
+                let mut names = Vec::new();
+                let mut dup = None;
+
+                while let Some(name) = map.next_key::<String>()? {
+                    map.next_value::<IgnoredAny>()?;
+
+                    match names.contains(&name) {
+                        true => dup = dup.or(Some(name)),
+                        false => names.push(name),
+                    }
+                }
+
+                Ok(Names(names, dup))
+            }
+        }
+
+        deserializer.deserialize_map(NamesVisitor)
+    }
+}
+
+/// Checks that the protected header has no duplicate member names, that the
+/// protected and unprotected headers are pairwise disjoint and that `crit`
+/// and `zip` appear only in the protected header.
+pub(crate) fn disjoint(
+    prot: Option<&[u8]>,
+    heads: [Option<&Unprotected>; 2],
+) -> Result<(), HeaderError> {
+    let mut seen = match prot.map(serde_json::from_slice).transpose()? {
+        Some(Names(_, Some(dup))) => return Err(HeaderError::Duplicate(dup)),
+        Some(Names(names, None)) => names,
+        None => Vec::new(),
+    };
+
+    for head in heads.into_iter().flatten() {
+        let head = match serde_json::to_value(head)? {
+            Value::Object(head) => head,
+            _ => continue,
+        };
+
+        for name in head.keys() {
+            if name == "crit" || name == "zip" {
+                return Err(HeaderError::Unprotected(name.clone()));
+            }
+
+            if seen.contains(name) {
+                return Err(HeaderError::Overlap(name.clone()));
+            }
+        }
+
+        seen.extend(head.into_iter().map(|(name, _)| name));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,1088 @@
+// SPDX-FileCopyrightText: 2022 Profian Inc. <opensource@profian.com>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+/// A protected header selecting the key management and content encryption
+/// algorithms.
+#[cfg(any(
+    all(feature = "aes-kw", feature = "aes-cbc-hs", feature = "aes-gcm"),
+    all(feature = "rsa", feature = "aes-cbc-hs", feature = "aes-gcm"),
+    all(feature = "p256", feature = "aes-gcm"),
+    all(feature = "x25519", feature = "aes-gcm"),
+))]
+fn prot(alg: jose_jwa::KeyManagement, enc: jose_jwa::Encryption) -> jose_jwe::Protected {
+    jose_jwe::Protected {
+        oth: jose_jwe::Unprotected {
+            alg: Some(alg),
+            enc: Some(enc),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+/// Parses a JWK into a key.
+#[cfg(any(
+    all(feature = "rsa", feature = "aes-cbc-hs", feature = "aes-gcm"),
+    all(feature = "p256", feature = "aes-gcm"),
+))]
+fn key(jwk: serde_json::Value) -> jose_jwk::crypto::Key {
+    let jwk: jose_jwk::Jwk = serde_json::from_value(jwk).unwrap();
+    jose_jwk::crypto::Key::try_from(&jwk.key).unwrap()
+}
+
+#[cfg(test)]
+mod rfc7516 {
+    use jose_jwa::{Encryption, KeyManagement};
+    use jose_jwe::{CompactError, Flattened, General, HeaderError, Jwe};
+
+    // RFC 7516 Appendix A.3
+    const COMPACT: &str = concat!(
+        "eyJhbGciOiJBMTI4S1ciLCJlbmMiOiJBMTI4Q0JDLUhTMjU2In0",
+        ".",
+        "6KB707dM9YTIgHtLvtgWQ8mKwboJW3of9locizkDTHzBC2IlrT1oOQ",
+        ".",
+        "AxY8DCtDaGlsbGljb3RoZQ",
+        ".",
+        "KDlTtXchhZTGufMYmOYGS4HffxPSUrfmqCHXaI9wOGY",
+        ".",
+        "U0m_YmjN04DJvceFICbCVQ",
+    );
+
+    // RFC 7516 Appendix A.5
+    fn json() -> serde_json::Value {
+        serde_json::json!({
+            "protected": "eyJlbmMiOiJBMTI4Q0JDLUhTMjU2In0",
+            "unprotected": { "jku": "https://server.example.com/keys.jwks" },
+            "header": { "alg": "A128KW", "kid": "7" },
+            "encrypted_key": "6KB707dM9YTIgHtLvtgWQ8mKwboJW3of9locizkDTHzBC2IlrT1oOQ",
+            "iv": "AxY8DCtDaGlsbGljb3RoZQ",
+            "ciphertext": "KDlTtXchhZTGufMYmOYGS4HffxPSUrfmqCHXaI9wOGY",
+            "tag": "Mz-VPPyU4RlcuYv1IwIvzw",
+        })
+    }
+
+    #[test]
+    fn compact() {
+        let jwe: Flattened = COMPACT.parse().unwrap();
+
+        let header = jwe.header().unwrap();
+        assert_eq!(header.oth.alg, Some(KeyManagement::A128Kw));
+        assert_eq!(header.oth.enc, Some(Encryption::A128CbcHs256));
+        assert_eq!(jwe.recipient.encrypted_key.as_ref().unwrap().len(), 40);
+        assert_eq!(jwe.iv.as_ref().unwrap().len(), 16);
+        assert_eq!(jwe.ciphertext.len(), 32);
+        assert_eq!(jwe.tag.as_ref().unwrap().len(), 16);
+
+        assert_eq!(jwe.to_string(), COMPACT);
+        assert_eq!(jwe.to_compact().unwrap(), COMPACT);
+
+        let jwe: Jwe = COMPACT.parse().unwrap();
+        assert_eq!(jwe.to_compact().unwrap(), COMPACT);
+    }
+
+    #[test]
+    fn compact_parts() {
+        // Key agreement and direct encryption leave the encrypted key empty.
+        let dir =
+            "eyJhbGciOiJkaXIiLCJlbmMiOiJBMTI4R0NNIn0..AAAAAAAAAAAAAAAA.AAAA.AAAAAAAAAAAAAAAAAAAAAA";
+        let jwe: Flattened = dir.parse().unwrap();
+        assert!(jwe.recipient.encrypted_key.is_none());
+        assert_eq!(jwe.to_string(), dir);
+
+        for bad in [
+            "eyJhbGciOiJkaXIiLCJlbmMiOiJBMTI4R0NNIn0..AAAAAAAAAAAAAAAA.AAAA",
+            "eyJhbGciOiJkaXIiLCJlbmMiOiJBMTI4R0NNIn0..AAAAAAAAAAAAAAAA.AAAA.AAAA.AAAA",
+            "e30..AAAA.AAA*.AAAA",
+            "..AAAA.AAAA.AAAA",
+        ] {
+            assert!(bad.parse::<Flattened>().is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn flattened() {
+        let jwe: Jwe = serde_json::from_value(json()).unwrap();
+        let Jwe::Flattened(jwe) = jwe else {
+            panic!("expected the flattened serialization");
+        };
+
+        // The headers are merged for the recipient.
+        let header = jwe.header().unwrap();
+        assert_eq!(header.oth.alg, Some(KeyManagement::A128Kw));
+        assert_eq!(header.oth.enc, Some(Encryption::A128CbcHs256));
+        assert_eq!(header.oth.kid.as_deref(), Some("7"));
+
+        assert_eq!(serde_json::to_value(&jwe).unwrap(), json());
+        assert_eq!(jwe.to_compact(), Err(CompactError::Unprotected));
+    }
+
+    #[test]
+    fn general() {
+        let jwe: Flattened = serde_json::from_value(json()).unwrap();
+        let mut jwe = General::from(jwe);
+
+        let mut other = jwe.recipients[0].clone();
+        other.header.as_mut().unwrap().kid = Some("8".into());
+        jwe.recipients.push(other);
+
+        let json = serde_json::to_value(&jwe).unwrap();
+        assert_eq!(json["recipients"][1]["header"]["kid"], "8");
+        assert_eq!(json["recipients"][1]["header"]["alg"], "A128KW");
+        assert!(json.get("header").is_none());
+
+        let Jwe::General(jwe) = serde_json::from_value(json).unwrap() else {
+            panic!("expected the general serialization");
+        };
+
+        assert_eq!(
+            jwe.header(&jwe.recipients[1]).unwrap().oth.kid.as_deref(),
+            Some("8")
+        );
+        assert_eq!(jwe.to_compact(), Err(CompactError::Recipients(2)));
+
+        let jwe = Flattened::try_from(jwe).unwrap_err();
+        assert_eq!(jwe.recipients.len(), 2);
+    }
+
+    #[test]
+    fn disjoint() {
+        let header = |json: serde_json::Value| {
+            let jwe: Flattened = serde_json::from_value(json).unwrap();
+            jwe.header().unwrap_err()
+        };
+
+        let mut value = json();
+        value["header"]["enc"] = "A128GCM".into();
+        assert!(matches!(header(value), HeaderError::Overlap(name) if name == "enc"));
+
+        let mut value = json();
+        value["unprotected"]["typ"] = "JWE".into();
+        value["header"]["typ"] = "JWE".into();
+        assert!(matches!(header(value), HeaderError::Overlap(name) if name == "typ"));
+
+        let mut value = json();
+        value["protected"] = "eyJlbmMiOiJBMTI4Q0JDLUhTMjU2IiwiZXh0IjoxLCJleHQiOjJ9".into();
+        assert!(matches!(header(value), HeaderError::Duplicate(name) if name == "ext"));
+
+        for name in ["crit", "zip"] {
+            let mut value = json();
+            value["unprotected"][name] = "DEF".into();
+            assert!(matches!(header(value), HeaderError::Unprotected(n) if n == name));
+
+            let mut value = json();
+            value["header"][name] = "DEF".into();
+            assert!(matches!(header(value), HeaderError::Unprotected(n) if n == name));
+        }
+    }
+
+    #[test]
+    fn lossless() {
+        let mut jwe: Flattened = COMPACT.parse().unwrap();
+        jwe.aad = Some(b"aad".to_vec().into());
+        assert_eq!(jwe.to_compact(), Err(CompactError::Aad));
+
+        jwe.aad = None;
+        jwe.protected = None;
+        assert_eq!(jwe.to_compact(), Err(CompactError::Protected));
+
+        let general = General::from(jwe);
+        assert_eq!(general.to_compact(), Err(CompactError::Protected));
+    }
+}
+
+#[cfg(all(test, any(feature = "aes-cbc-hs", feature = "aes-gcm")))]
+mod content {
+    use jose_b64::base64ct::{Base64UrlUnpadded, Encoding};
+    use jose_jwa::Encryption;
+    use jose_jwe::crypto::{decrypt, encrypt, Error};
+    use jose_jwe::Flattened;
+
+    fn b64(s: &str) -> Vec<u8> {
+        Base64UrlUnpadded::decode_vec(s).unwrap()
+    }
+
+    // RFC 7516 Appendix A.3
+    #[cfg(feature = "aes-cbc-hs")]
+    mod cbc {
+        pub const PROTECTED: &str = "eyJhbGciOiJBMTI4S1ciLCJlbmMiOiJBMTI4Q0JDLUhTMjU2In0";
+        pub const CIPHERTEXT: &str = "KDlTtXchhZTGufMYmOYGS4HffxPSUrfmqCHXaI9wOGY";
+        pub const TAG: &str = "U0m_YmjN04DJvceFICbCVQ";
+        pub const PLAINTEXT: &[u8] = b"Live long and prosper.";
+
+        pub const CEK: [u8; 32] = [
+            4, 211, 31, 197, 84, 157, 252, 254, 11, 100, 157, 250, 63, 170, 106, 206, 107, 124,
+            212, 45, 111, 107, 9, 219, 200, 177, 0, 240, 143, 156, 44, 207,
+        ];
+
+        pub const IV: [u8; 16] = [
+            3, 22, 60, 12, 43, 67, 104, 105, 108, 108, 105, 99, 111, 116, 104, 101,
+        ];
+    }
+
+    #[cfg(feature = "aes-cbc-hs")]
+    #[test]
+    fn aes_cbc_hs() {
+        use cbc::*;
+
+        let enc = Encryption::A128CbcHs256;
+        let aad = PROTECTED.as_bytes();
+        let (ct, tag) = encrypt(enc, &CEK, &IV, aad, PLAINTEXT).unwrap();
+        assert_eq!(ct, b64(CIPHERTEXT));
+        assert_eq!(tag, b64(TAG));
+
+        let pt = decrypt(enc, &CEK, &IV, aad, &ct, &tag).unwrap();
+        assert_eq!(pt, PLAINTEXT);
+
+        let compact = format!(
+            "{PROTECTED}..{}.{CIPHERTEXT}.{TAG}",
+            Base64UrlUnpadded::encode_string(&IV)
+        );
+        let mut jwe: Flattened = compact.parse().unwrap();
+        assert_eq!(jwe.decrypt_content(&CEK).unwrap(), PLAINTEXT);
+
+        // The protected header already specifies the algorithm.
+        jwe.unprotected = serde_json::from_value(serde_json::json!({ "alg": "A128KW" })).unwrap();
+        let err = jwe.decrypt_content(&CEK).unwrap_err();
+        assert!(
+            matches!(err, Error::Header(jose_jwe::HeaderError::Overlap(name)) if name == "alg")
+        );
+    }
+
+    #[cfg(feature = "aes-cbc-hs")]
+    #[test]
+    fn aes_cbc_hs_tampered() {
+        use cbc::*;
+
+        let enc = Encryption::A128CbcHs256;
+        let aad = PROTECTED.as_bytes();
+        let (ct, tag) = (b64(CIPHERTEXT), b64(TAG));
+
+        let mut bad = tag.clone();
+        bad[0] ^= 1;
+        let err = decrypt(enc, &CEK, &IV, aad, &ct, &bad).unwrap_err();
+        assert!(matches!(err, Error::Invalid));
+
+        let err = decrypt(enc, &CEK, &IV, aad, &ct, &tag[..8]).unwrap_err();
+        assert!(matches!(err, Error::Invalid));
+
+        let mut bad = ct.clone();
+        bad[0] ^= 1;
+        let err = decrypt(enc, &CEK, &IV, aad, &bad, &tag).unwrap_err();
+        assert!(matches!(err, Error::Invalid));
+
+        let err = decrypt(enc, &CEK, &IV, b"", &ct, &tag).unwrap_err();
+        assert!(matches!(err, Error::Invalid));
+
+        let err = decrypt(enc, &CEK[..16], &IV, aad, &ct, &tag).unwrap_err();
+        assert!(matches!(err, Error::Length));
+
+        let err = decrypt(enc, &CEK, &IV[..12], aad, &ct, &tag).unwrap_err();
+        assert!(matches!(err, Error::Length));
+    }
+
+    #[cfg(feature = "aes-cbc-hs")]
+    #[test]
+    fn aes_cbc_hs_roundtrip() {
+        for (enc, len) in [
+            (Encryption::A128CbcHs256, 32),
+            (Encryption::A192CbcHs384, 48),
+            (Encryption::A256CbcHs512, 64),
+        ] {
+            let cek = vec![7u8; len];
+            let (ct, tag) = encrypt(enc, &cek, &[1; 16], b"aad", b"plaintext").unwrap();
+            assert_eq!(tag.len(), len / 2);
+
+            let pt = decrypt(enc, &cek, &[1; 16], b"aad", &ct, &tag).unwrap();
+            assert_eq!(pt, b"plaintext");
+        }
+    }
+
+    // RFC 7518 Appendix B.2 and B.3, with the octets base64url-encoded.
+    #[cfg(feature = "aes-cbc-hs")]
+    #[test]
+    fn aes_cbc_hs_rfc7518() {
+        const PLAINTEXT: &[u8] = b"A cipher system must not be required to be secret, and it \
+            must be able to fall into the hands of the enemy without inconvenience";
+        const AAD: &[u8] = b"The second principle of Auguste Kerckhoffs";
+        const IV: [u8; 16] = [
+            0x1a, 0xf3, 0x8c, 0x2d, 0xc2, 0xb9, 0x6f, 0xfd, 0xd8, 0x66, 0x94, 0x09, 0x23, 0x41,
+            0xbc, 0x04,
+        ];
+
+        for (enc, len, ciphertext, tag) in [
+            (
+                Encryption::A192CbcHs384,
+                48,
+                concat!(
+                    "6mXaa1nmHttBm-YtGXEq5dMD7rUAUtDf1ml_dyJMjtsADSeb3BTBByZUvTCUQjDGV77UygyfSoRm8isi",
+                    "bRdGIUv4z8JACt2fUSbkeWY_yQs77Xh6Lw_8vzkEvipkHVwhBb_lkbriOx10SeUy7vYKmsi7bGsB011J",
+                    "eHvNV-9ISSfygK3JGsDE55x7Ee_GAFTj",
+                ),
+                "hJCsDliUm_5Rh11zP5OsIHUWgDnMxzPX",
+            ),
+            (
+                Encryption::A256CbcHs512,
+                64,
+                concat!(
+                    "Sv-qrbeMMcXaSxtZDRD_vT3Y1dMCQjUmkS2gN-y8x72CLDAd1nw3O8y1hK0-knnC5tEqE3S3fwd1U9-C",
+                    "lBBEazbr2XBmKWrmQn6nXC4IRqEaCcz1Nw3IC_7LrSjHPwmzo7deZiollEEK5Jay4uZgnjHm4CzIN_BT",
+                    "0h83_09RlQu-JjjQndekkwkwgG0HA7H2",
+                ),
+                "TdO0wIin9FwhaDlkWyASvy5iaajFaoFtvBsmd2GVW8U",
+            ),
+        ] {
+            let cek: Vec<u8> = (0..len).collect();
+            let (ct, t) = encrypt(enc, &cek, &IV, AAD, PLAINTEXT).unwrap();
+            assert_eq!(ct, b64(ciphertext));
+            assert_eq!(t, b64(tag));
+
+            let pt = decrypt(enc, &cek, &IV, AAD, &ct, &t).unwrap();
+            assert_eq!(pt, PLAINTEXT);
+        }
+    }
+
+    // RFC 7516 Appendix A.1
+    #[cfg(feature = "aes-gcm")]
+    #[test]
+    fn aes_gcm() {
+        const PROTECTED: &str = "eyJhbGciOiJSU0EtT0FFUCIsImVuYyI6IkEyNTZHQ00ifQ";
+        const CIPHERTEXT: &str = concat!(
+            "5eym8TW_c8SuK0ltJ3rpYIzOeDQz7TALvtu6UG9oMo4vpzs9tX_EFShS8iB7j6ji",
+            "SdiwkIr3ajwQzaBtQD_A",
+        );
+        const TAG: &str = "XFBoMYUZodetZdvTiFvSkQ";
+        const PLAINTEXT: &[u8] = b"The true sign of intelligence is not knowledge but imagination.";
+
+        const CEK: [u8; 32] = [
+            177, 161, 244, 128, 84, 143, 225, 115, 63, 180, 3, 255, 107, 154, 212, 246, 138, 7,
+            110, 91, 112, 46, 34, 105, 47, 130, 203, 46, 122, 234, 64, 252,
+        ];
+
+        const IV: [u8; 12] = [227, 197, 117, 252, 2, 219, 233, 68, 180, 225, 77, 219];
+
+        let enc = Encryption::A256Gcm;
+        let aad = PROTECTED.as_bytes();
+        let (ct, tag) = encrypt(enc, &CEK, &IV, aad, PLAINTEXT).unwrap();
+        assert_eq!(ct, b64(CIPHERTEXT));
+        assert_eq!(tag, b64(TAG));
+
+        let pt = decrypt(enc, &CEK, &IV, aad, &ct, &tag).unwrap();
+        assert_eq!(pt, PLAINTEXT);
+
+        let compact = format!(
+            "{PROTECTED}..{}.{CIPHERTEXT}.{TAG}",
+            Base64UrlUnpadded::encode_string(&IV)
+        );
+        let jwe: Flattened = compact.parse().unwrap();
+        assert_eq!(jwe.decrypt_content(&CEK).unwrap(), PLAINTEXT);
+
+        let mut bad = tag.clone();
+        bad[15] ^= 1;
+        let err = decrypt(enc, &CEK, &IV, aad, &ct, &bad).unwrap_err();
+        assert!(matches!(err, Error::Invalid));
+
+        let err = decrypt(enc, &CEK[..16], &IV, aad, &ct, &tag).unwrap_err();
+        assert!(matches!(err, Error::Length));
+    }
+
+    #[cfg(feature = "aes-gcm")]
+    #[test]
+    fn aes_gcm_roundtrip() {
+        for (enc, len) in [
+            (Encryption::A128Gcm, 16),
+            (Encryption::A192Gcm, 24),
+            (Encryption::A256Gcm, 32),
+        ] {
+            let cek = vec![7u8; len];
+            let (ct, tag) = encrypt(enc, &cek, &[1; 12], b"aad", b"plaintext").unwrap();
+            let pt = decrypt(enc, &cek, &[1; 12], b"aad", &ct, &tag).unwrap();
+            assert_eq!(pt, b"plaintext");
+        }
+    }
+}
+
+#[cfg(all(test, feature = "aes-kw", feature = "aes-cbc-hs", feature = "aes-gcm"))]
+mod keys {
+    use jose_b64::serde::Json;
+    use jose_jwa::{Encryption, KeyManagement};
+    use jose_jwe::crypto::Error;
+    use jose_jwe::{Flattened, General, Protected};
+    use jose_jwk::Oct;
+    use rand_core::OsRng;
+
+    use super::prot;
+
+    // RFC 7516 Appendix A.3
+    const KEK: &str = r#"{"k": "GawgguFyGrWKav7AX4VKUg"}"#;
+    const COMPACT: &str = concat!(
+        "eyJhbGciOiJBMTI4S1ciLCJlbmMiOiJBMTI4Q0JDLUhTMjU2In0",
+        ".",
+        "6KB707dM9YTIgHtLvtgWQ8mKwboJW3of9locizkDTHzBC2IlrT1oOQ",
+        ".",
+        "AxY8DCtDaGlsbGljb3RoZQ",
+        ".",
+        "KDlTtXchhZTGufMYmOYGS4HffxPSUrfmqCHXaI9wOGY",
+        ".",
+        "U0m_YmjN04DJvceFICbCVQ",
+    );
+
+    fn oct(k: &[u8]) -> Oct {
+        Oct {
+            k: k.to_vec().into(),
+        }
+    }
+
+    #[test]
+    fn a128kw() {
+        let kek: Oct = serde_json::from_str(KEK).unwrap();
+
+        let jwe: Flattened = COMPACT.parse().unwrap();
+        assert_eq!(
+            jwe.decrypt(&kek, &mut OsRng).unwrap(),
+            b"Live long and prosper."
+        );
+
+        let jwe: General = COMPACT.parse().unwrap();
+        assert_eq!(
+            jwe.decrypt(&kek, &mut OsRng).unwrap(),
+            b"Live long and prosper."
+        );
+
+        let wrong = oct(&[0; 16]);
+        let err = jwe.decrypt(&wrong, &mut OsRng).unwrap_err();
+        assert!(matches!(err, Error::Invalid));
+    }
+
+    // RFC 7516 Appendix A.5
+    #[test]
+    fn a128kw_flattened() {
+        let kek: Oct = serde_json::from_str(KEK).unwrap();
+
+        let jwe: Flattened = serde_json::from_value(serde_json::json!({
+            "protected": "eyJlbmMiOiJBMTI4Q0JDLUhTMjU2In0",
+            "header": { "alg": "A128KW", "kid": "7" },
+            "encrypted_key": "6KB707dM9YTIgHtLvtgWQ8mKwboJW3of9locizkDTHzBC2IlrT1oOQ",
+            "iv": "AxY8DCtDaGlsbGljb3RoZQ",
+            "ciphertext": "KDlTtXchhZTGufMYmOYGS4HffxPSUrfmqCHXaI9wOGY",
+            "tag": "Mz-VPPyU4RlcuYv1IwIvzw",
+        }))
+        .unwrap();
+
+        assert_eq!(
+            jwe.decrypt(&kek, &mut OsRng).unwrap(),
+            b"Live long and prosper."
+        );
+    }
+
+    #[test]
+    fn roundtrip() {
+        use Encryption::*;
+        use KeyManagement::*;
+
+        for (alg, len) in [(A128Kw, 16), (A192Kw, 24), (A256Kw, 32)] {
+            for enc in [
+                A128CbcHs256,
+                A192CbcHs384,
+                A256CbcHs512,
+                A128Gcm,
+                A192Gcm,
+                A256Gcm,
+            ] {
+                let key = oct(&vec![7; len]);
+                let jwe = Flattened::encrypt(&key, prot(alg, enc), b"hello", &mut OsRng).unwrap();
+                assert!(jwe.recipient.encrypted_key.is_some());
+
+                let jwe: Flattened = jwe.to_compact().unwrap().parse().unwrap();
+                assert_eq!(jwe.decrypt(&key, &mut OsRng).unwrap(), b"hello");
+            }
+        }
+
+        for (enc, len) in [(A128CbcHs256, 32), (A256CbcHs512, 64), (A128Gcm, 16)] {
+            let key = oct(&vec![7; len]);
+            let jwe = Flattened::encrypt(&key, prot(Direct, enc), b"hello", &mut OsRng).unwrap();
+            assert!(jwe.recipient.encrypted_key.is_none());
+
+            let jwe: Flattened = jwe.to_compact().unwrap().parse().unwrap();
+            assert_eq!(jwe.decrypt(&key, &mut OsRng).unwrap(), b"hello");
+        }
+    }
+
+    #[test]
+    fn mismatch() {
+        use Encryption::*;
+        use KeyManagement::*;
+
+        // The shared key is the CEK, so its size must match `enc`.
+        let key = oct(&[7; 16]);
+        let err = Flattened::encrypt(&key, prot(Direct, A128CbcHs256), b"", &mut OsRng);
+        assert!(matches!(err.unwrap_err(), Error::Length));
+
+        // The key encryption key must match `alg`.
+        let err = Flattened::encrypt(&key, prot(A256Kw, A128Gcm), b"", &mut OsRng);
+        assert!(matches!(err.unwrap_err(), Error::Key(_)));
+
+        // The unwrapped CEK of RFC 7516 Appendix A.3 is 32 bytes, which is
+        // too long for A128GCM.
+        let kek: Oct = serde_json::from_str(KEK).unwrap();
+        let mut jwe: Flattened = COMPACT.parse().unwrap();
+        jwe.protected = None;
+        jwe.unprotected = Some(prot(A128Kw, A128Gcm).oth);
+        let err = jwe.decrypt(&kek, &mut OsRng).unwrap_err();
+        assert!(matches!(err, Error::Length));
+
+        // Direct encryption must not have an encrypted key.
+        let key = oct(&[7; 16]);
+        let mut jwe = Flattened::encrypt(&key, prot(Direct, A128Gcm), b"", &mut OsRng).unwrap();
+        jwe.recipient.encrypted_key = Some(vec![1].into());
+        assert!(matches!(
+            jwe.decrypt(&key, &mut OsRng).unwrap_err(),
+            Error::Invalid
+        ));
+
+        let err = Flattened::encrypt(&key, Protected::default(), b"", &mut OsRng);
+        assert!(matches!(err.unwrap_err(), Error::MissingAlg));
+    }
+
+    #[test]
+    fn unsupported() {
+        use Encryption::*;
+        use KeyManagement::*;
+
+        let key = oct(&[7; 16]);
+
+        // Compression is not implemented.
+        let mut zip = prot(A128Kw, A128Gcm);
+        zip.zip = Some("DEF".into());
+        let err = Flattened::encrypt(&key, zip.clone(), b"", &mut OsRng);
+        assert!(matches!(err.unwrap_err(), Error::Unsupported));
+
+        let mut jwe = Flattened::encrypt(&key, prot(A128Kw, A128Gcm), b"", &mut OsRng).unwrap();
+        jwe.protected = Some(Json::new(zip).unwrap());
+        let err = jwe.decrypt(&key, &mut OsRng).unwrap_err();
+        assert!(matches!(err, Error::Unsupported));
+
+        // No extension is understood.
+        let mut crit = prot(A128Kw, A128Gcm);
+        crit.crit = Some(vec!["exp".into()]);
+        crit.oth.ext.insert("exp".into(), 1.into());
+        let err = Flattened::encrypt(&key, crit.clone(), b"", &mut OsRng);
+        assert!(matches!(err.unwrap_err(), Error::Critical));
+
+        let mut jwe = Flattened::encrypt(&key, prot(A128Kw, A128Gcm), b"", &mut OsRng).unwrap();
+        jwe.protected = Some(Json::new(crit).unwrap());
+        let err = jwe.decrypt(&key, &mut OsRng).unwrap_err();
+        assert!(matches!(err, Error::Critical));
+
+        let err = jwe.decrypt_content(&[7; 16]).unwrap_err();
+        assert!(matches!(err, Error::Critical));
+
+        let err = General::from(jwe).decrypt(&key, &mut OsRng).unwrap_err();
+        assert!(matches!(err, Error::Critical));
+    }
+}
+
+#[cfg(all(test, feature = "rsa", feature = "aes-cbc-hs", feature = "aes-gcm"))]
+mod rsa {
+    use jose_jwa::{Encryption, KeyManagement};
+    use jose_jwe::crypto::Error;
+    use jose_jwe::Flattened;
+    use jose_jwk::crypto::Key;
+    use rand_core::OsRng;
+
+    use super::{key, prot};
+
+    // RFC 7516 Appendix A.1.3
+    fn a1() -> Key {
+        key(serde_json::json!({
+            "kty": "RSA",
+            "n": concat!(
+                "oahUIoWw0K0usKNuOR6H4wkf4oBUXHTxRvgb48E-BVvxkeDNjbC4he8rUWcJoZmds2h7M70imEVhRU5djINXtqll",
+                "XI4DFqcI1DgjT9LewND8MW2Krf3Spsk_ZkoFnilakGygTwpZ3uesH-PFABNIUYpOiN15dsQRkgr0vEhxN92i2a",
+                "sbOenSZeyaxziK72UwxrrKoExv6kc5twXTq4h-QChLOln0_mtUZwfsRaMStPs6mS6XrgxnxbWhojf663tuEQue",
+                "GC-FCMfra36C9knDFGzKsNa7LZK2djYgyD3JR_MB_4NUJW_TqOQtwHYbxevoJArm-L5StowjzGy-_bq6Gw",
+            ),
+            "e": "AQAB",
+            "d": concat!(
+                "kLdtIj6GbDks_ApCSTYQtelcNttlKiOyPzMrXHeI-yk1F7-kpDxY4-WY5NWV5KntaEeXS1j82E375xxhWMHXyv",
+                "jYecPT9fpwR_M9gV8n9Hrh2anTpTD93Dt62ypW3yDsJzBnTnrYu1iwWRgBKrEYY46qAZIrA2xAwnm2X7uGR1hg",
+                "hkqDp0Vqj3kbSCz1XyfCs6_LehBwtxHIyh8Ripy40p24moOAbgxVw3rxT_vlt3UVe4WO3JkJOzlpUf-KTVI2Pt",
+                "gm-dARxTEtE-id-4OJr0h-K-VFs3VSndVTIznSxfyrj8ILL6MG_Uv8YAu7VILSB3lOW085-4qE3DzgrTjgyQ",
+            ),
+            "p": concat!(
+                "1r52Xk46c-LsfB5P442p7atdPUrxQSy4mti_tZI3Mgf2EuFVbUoDBvaRQ-SWxkbkmoEzL7JXroSBjSrK3YIQgY",
+                "dMgyAEPTPjXv_hI2_1eTSPVZfzL0lffNn03IXqWF5MDFuoUYE0hzb2vhrlN_rKrbfDIwUbTrjjgieRbwC6Cl0",
+            ),
+            "q": concat!(
+                "wLb35x7hmQWZsWJmB_vle87ihgZ19S8lBEROLIsZG4ayZVe9Hi9gDVCOBmUDdaDYVTSNx_8Fyw1YYa9XGrGnDe",
+                "w00J28cRUoeBB_jKI1oma0Orv1T9aXIWxKwd4gvxFImOWr3QRL9KEBRzk2RatUBnmDZJTIAfwTs0g68UZHvtc",
+            ),
+            "dp": concat!(
+                "ZK-YwE7diUh0qR1tR7w8WHtolDx3MZ_OTowiFvgfeQ3SiresXjm9gZ5KLhMXvo-uz-KUJWDxS5pFQ_M0evdo1d",
+                "KiRTjVw_x4NyqyXPM5nULPkcpU827rnpZzAJKpdhWAgqrXGKAECQH0Xt4taznjnd_zVpAmZZq60WPMBMfKcuE",
+            ),
+            "dq": concat!(
+                "Dq0gfgJ1DdFGXiLvQEZnuKEN0UUmsJBxkjydc3j4ZYdBiMRAy86x0vHCjywcMlYYg4yoC4YZa9hNVcsjqA3Fei",
+                "L19rk8g6Qn29Tt0cj8qqyFpz9vNDBUfCAiJVeESOjJDZPYHdHY8v1b-o-Z2X5tvLx-TCekf7oxyeKDUqKWjis",
+            ),
+            "qi": concat!(
+                "VIMpMYbPf47dT1w_zDUXfPimsSegnMOA1zTaX7aGk_8urY6R8-ZW1FxU7AlWAyLWybqq6t16VFd7hQd0y6flUK",
+                "4SlOydB61gwanOsXGOAOv82cHq0E3eL4HrtZkUuKvnPrMnsUUFlfUdybVzxyjz9JF_XyaY14ardLSjf4L_FNY",
+            ),
+        }))
+    }
+
+    // RFC 7516 Appendix A.2.3
+    fn a2() -> Key {
+        key(serde_json::json!({
+            "kty": "RSA",
+            "n": concat!(
+                "sXchDaQebHnPiGvyDOAT4saGEUetSyo9MKLOoWFsueri23bOdgWp4Dy1WlUzewbgBHod5pcM9H95GQRV3JDXbo",
+                "IRROSBigeC5yjU1hGzHHyXss8UDprecbAYxknTcQkhslANGRUZmdTOQ5qTRsLAt6BTYuyvVRdhS8exSZEy_c4g",
+                "s_7svlJJQ4H9_NxsiIoLwAEk7-Q3UXERGYw_75IDrGA84-lA_-Ct4eTlXHBIY2EaV7t7LjJaynVJCpkv4LKjTT",
+                "AumiGUIuQhrNhZLuF_RJLqHpM2kgWFLU7-VTdL1VbC2tejvcI2BlMkEpk1BzBZI0KQB0GaDWFLN-aEAw3vRw",
+            ),
+            "e": "AQAB",
+            "d": concat!(
+                "VFCWOqXr8nvZNyaaJLXdnNPXZKRaWCjkU5Q2egQQpTBMwhprMzWzpR8Sxq1OPThh_J6MUD8Z35wky9b8eEO0pw",
+                "NS8xlh1lOFRRBoNqDIKVOku0aZb-rynq8cxjDTLZQ6Fz7jSjR1Klop-YKaUHc9GsEofQqYruPhzSA-QgajZGPb",
+                "E_0ZaVDJHfyd7UUBUKunFMScbflYAAOYJqVIVwaYR5zWEEceUjNnTNo_CVSj-VvXLO5VZfCUAVLgW4dpf1SrtZ",
+                "jSt34YLsRarSb127reG_DUwg9Ch-KyvjT1SkHgUWRVGcyly7uvVGRSDwsXypdrNinPA4jlhoNdizK2zF2CWQ",
+            ),
+            "p": concat!(
+                "9gY2w6I6S6L0juEKsbeDAwpd9WMfgqFoeA9vEyEUuk4kLwBKcoe1x4HG68ik918hdDSE9vDQSccA3xXHOAFOPJ",
+                "8R9EeIAbTi1VwBYnbTp87X-xcPWlEPkrdoUKW60tgs1aNd_Nnc9LEVVPMS390zbFxt8TN_biaBgelNgbC95sM",
+            ),
+            "q": concat!(
+                "uKlCKvKv_ZJMVcdIs5vVSU_6cPtYI1ljWytExV_skstvRSNi9r66jdd9-yBhVfuG4shsp2j7rGnIio901RBeHo",
+                "6TPKWVVykPu1iYhQXw1jIABfw-MVsN-3bQ76WLdt2SDxsHs7q7zPyUyHXmps7ycZ5c72wGkUwNOjYelmkiNS0",
+            ),
+            "dp": concat!(
+                "w0kZbV63cVRvVX6yk3C8cMxo2qCM4Y8nsq1lmMSYhG4EcL6FWbX5h9yuvngs4iLEFk6eALoUS4vIWEwcL4txw9",
+                "LsWH_zKI-hwoReoP77cOdSL4AVcraHawlkpyd2TWjE5evgbhWtOxnZee3cXJBkAi64Ik6jZxbvk-RR3pEhnCs",
+            ),
+            "dq": concat!(
+                "o_8V14SezckO6CNLKs_btPdFiO9_kC1DsuUTd2LAfIIVeMZ7jn1Gus_Ff7B7IVx3p5KuBGOVF8L-qifLb6nQnL",
+                "ysgHDh132NDioZkhH7mI7hPG-PYE_odApKdnqECHWw0J-F0JWnUd6D2B_1TvF9mXA2Qx-iGYn8OVV1Bsmp6qU",
+            ),
+            "qi": concat!(
+                "eNho5yRBEBxhGBtQRww9QirZsB66TrfFReG_CcteI1aCneT0ELGhYlRlCtUkTRclIfuEPmNsNDPbLoLqqCVznF",
+                "bvdB7x-Tl-m0l_eFTj2KiqwGqE9PZB9nNTwMVvH3VRRSLWACvPnSiwP8N5Usy-WRXS-V7TbpxIhvepTfE0NNo",
+            ),
+        }))
+    }
+
+    // RFC 7516 Appendix A.1
+    #[test]
+    fn rsa_oaep() {
+        let jwe: Flattened = concat!(
+            "eyJhbGciOiJSU0EtT0FFUCIsImVuYyI6IkEyNTZHQ00ifQ",
+            ".",
+            "OKOawDo13gRp2ojaHV7LFpZcgV7T6DVZKTyKOMTYUmKoTCVJRgckCL9kiMT03JGe",
+            "ipsEdY3mx_etLbbWSrFr05kLzcSr4qKAq7YN7e9jwQRb23nfa6c9d-StnImGyFDb",
+            "Sv04uVuxIp5Zms1gNxKKK2Da14B8S4rzVRltdYwam_lDp5XnZAYpQdb76FdIKLaV",
+            "mqgfwX7XWRxv2322i-vDxRfqNzo_tETKzpVLzfiwQyeyPGLBIO56YJ7eObdv0je8",
+            "1860ppamavo35UgoRdbYaBcoh9QcfylQr66oc6vFWXRcZ_ZT2LawVCWTIy3brGPi",
+            "6UklfCpIMfIjf7iGdXKHzg",
+            ".",
+            "48V1_ALb6US04U3b",
+            ".",
+            "5eym8TW_c8SuK0ltJ3rpYIzOeDQz7TALvtu6UG9oMo4vpzs9tX_EFShS8iB7j6ji",
+            "SdiwkIr3ajwQzaBtQD_A",
+            ".",
+            "XFBoMYUZodetZdvTiFvSkQ",
+        )
+        .parse()
+        .unwrap();
+
+        assert_eq!(
+            jwe.decrypt(&a1(), &mut OsRng).unwrap(),
+            b"The true sign of intelligence is not knowledge but imagination."
+        );
+    }
+
+    // RFC 7516 Appendix A.2
+    #[cfg(feature = "rsa1_5")]
+    const A2: &str = concat!(
+        "eyJhbGciOiJSU0ExXzUiLCJlbmMiOiJBMTI4Q0JDLUhTMjU2In0",
+        ".",
+        "UGhIOguC7IuEvf_NPVaXsGMoLOmwvc1GyqlIKOK1nN94nHPoltGRhWhw7Zx0-kFm",
+        "1NJn8LE9XShH59_i8J0PH5ZZyNfGy2xGdULU7sHNF6Gp2vPLgNZ__deLKxGHZ7Pc",
+        "HALUzoOegEI-8E66jX2E4zyJKx-YxzZIItRzC5hlRirb6Y5Cl_p-ko3YvkkysZIF",
+        "NPccxRU7qve1WYPxqbb2Yw8kZqa2rMWI5ng8OtvzlV7elprCbuPhcCdZ6XDP0_F8",
+        "rkXds2vE4X-ncOIM8hAYHHi29NX0mcKiRaD0-D-ljQTP-cFPgwCp6X-nZZd9OHBv",
+        "-B3oWh2TbqmScqXMR4gp_A",
+        ".",
+        "AxY8DCtDaGlsbGljb3RoZQ",
+        ".",
+        "KDlTtXchhZTGufMYmOYGS4HffxPSUrfmqCHXaI9wOGY",
+        ".",
+        "9hH0vgRfYgPnAHOd8stkvw",
+    );
+
+    #[cfg(feature = "rsa1_5")]
+    #[test]
+    fn rsa1_5() {
+        let jwe: Flattened = A2.parse().unwrap();
+        assert_eq!(
+            jwe.decrypt(&a2(), &mut OsRng).unwrap(),
+            b"Live long and prosper."
+        );
+    }
+
+    #[cfg(feature = "rsa1_5")]
+    #[test]
+    fn rsa1_5_countermeasure() {
+        // A malformed encrypted key fails just like a bad tag does.
+        let mut jwe: Flattened = A2.parse().unwrap();
+        let mut ekey = jwe.recipient.encrypted_key.take().unwrap().to_vec();
+        ekey[0] ^= 1;
+        jwe.recipient.encrypted_key = Some(ekey.into());
+        assert!(matches!(
+            jwe.decrypt(&a2(), &mut OsRng),
+            Err(Error::Invalid)
+        ));
+
+        let mut jwe: Flattened = A2.parse().unwrap();
+        jwe.tag = Some(vec![0; 16].into());
+        assert!(matches!(
+            jwe.decrypt(&a2(), &mut OsRng),
+            Err(Error::Invalid)
+        ));
+    }
+
+    #[test]
+    fn roundtrip() {
+        use Encryption::*;
+        use KeyManagement::*;
+
+        let key = a1();
+
+        for alg in [RsaOaep, RsaOaep256] {
+            for enc in [A128CbcHs256, A256CbcHs512, A128Gcm, A256Gcm] {
+                let jwe = Flattened::encrypt(&key, prot(alg, enc), b"hi", &mut OsRng).unwrap();
+                let jwe: Flattened = jwe.to_compact().unwrap().parse().unwrap();
+                assert_eq!(jwe.decrypt(&key, &mut OsRng).unwrap(), b"hi");
+            }
+        }
+
+        // The other key cannot decrypt it.
+        let jwe = Flattened::encrypt(&key, prot(RsaOaep, A128Gcm), b"", &mut OsRng).unwrap();
+        let err = jwe.decrypt(&a2(), &mut OsRng).unwrap_err();
+        assert!(matches!(err, Error::Invalid));
+    }
+
+    #[test]
+    fn public() {
+        let Key::Rsa(jose_jwk::crypto::Kind::Secret(secret)) = a1() else {
+            panic!("expected a private RSA key");
+        };
+        let public = Key::from(secret.to_public_key());
+
+        let prot = prot(KeyManagement::RsaOaep256, Encryption::A128Gcm);
+        let jwe = Flattened::encrypt(&public, prot, b"hi", &mut OsRng).unwrap();
+
+        let err = jwe.decrypt(&public, &mut OsRng).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Key(jose_jwk::crypto::Error::NotPrivate)
+        ));
+        assert_eq!(jwe.decrypt(&a1(), &mut OsRng).unwrap(), b"hi");
+    }
+
+    #[cfg(not(feature = "rsa1_5"))]
+    #[test]
+    fn rsa1_5_disabled() {
+        let prot = prot(KeyManagement::Rsa1_5, Encryption::A128CbcHs256);
+        let err = Flattened::encrypt(&a1(), prot, b"", &mut OsRng).unwrap_err();
+        assert!(matches!(err, Error::Unsupported));
+    }
+}
+
+#[cfg(all(test, feature = "p256", feature = "aes-gcm"))]
+mod ecdh {
+    use jose_b64::base64ct::{Base64UrlUnpadded, Encoding};
+    use jose_jwa::{Encryption, KeyManagement};
+    use jose_jwe::crypto::{DecryptingKey, Error};
+    use jose_jwe::{Flattened, Protected};
+    use jose_jwk::crypto::Key;
+    use rand_core::OsRng;
+
+    use super::{key, prot};
+
+    fn algorithms() -> Vec<KeyManagement> {
+        #[allow(unused_mut)]
+        let mut all = vec![KeyManagement::EcdhEs];
+
+        #[cfg(feature = "aes-kw")]
+        all.extend([
+            KeyManagement::EcdhEsA128Kw,
+            KeyManagement::EcdhEsA192Kw,
+            KeyManagement::EcdhEsA256Kw,
+        ]);
+
+        all
+    }
+
+    // RFC 7518 Appendix C
+    fn bob() -> Key {
+        key(serde_json::json!({
+            "kty": "EC",
+            "crv": "P-256",
+            "x": "weNJy2HscCSM6AEDTDg04biOvhFhyyWvOHQfeF_PxMQ",
+            "y": "e8lnCO-AlStT-NJVX-crhB7QRYhiix03illJOVAOyck",
+            "d": "VEmDZpDXXK8p8N0Cndsxs924q6nS1RXFASRl6BfUqdw",
+        }))
+    }
+
+    fn header() -> serde_json::Value {
+        serde_json::json!({
+            "alg": "ECDH-ES",
+            "enc": "A128GCM",
+            "apu": "QWxpY2U",
+            "apv": "Qm9i",
+            "epk": {
+                "kty": "EC",
+                "crv": "P-256",
+                "x": "gI0GAILBdu7T53akrFmMyGcsF3n5dO7MmwNBHKW5SV0",
+                "y": "SLW_xSffzlPWrHEVI30DHM_4egVwt3NQqeUD7nMFpps",
+            },
+        })
+    }
+
+    #[test]
+    fn rfc7518() {
+        let head: Protected = serde_json::from_value(header()).unwrap();
+        let cek = bob().decrypt_key(&head, None, &mut OsRng).unwrap();
+        assert_eq!(
+            *cek,
+            Base64UrlUnpadded::decode_vec("VqqN6vgjbSBcIijNcacQGg").unwrap()
+        );
+
+        // The derived key depends on the party information.
+        let mut head = head;
+        head.oth.apv = None;
+        let cek = bob().decrypt_key(&head, None, &mut OsRng).unwrap();
+        assert_ne!(
+            *cek,
+            Base64UrlUnpadded::decode_vec("VqqN6vgjbSBcIijNcacQGg").unwrap()
+        );
+    }
+
+    #[test]
+    fn invalid_curve() {
+        // A point which is not on P-256.
+        let mut head = header();
+        head["epk"]["y"] = "SLW_xSffzlPWrHEVI30DHM_4egVwt3NQqeUD7nMFppw".into();
+        let head: Protected = serde_json::from_value(head).unwrap();
+        let err = bob().decrypt_key(&head, None, &mut OsRng).unwrap_err();
+        assert!(matches!(err, Error::Epk));
+
+        // A point on another curve.
+        let mut head = header();
+        head["epk"]["crv"] = "P-384".into();
+        let head: Protected = serde_json::from_value(head).unwrap();
+        let err = bob().decrypt_key(&head, None, &mut OsRng).unwrap_err();
+        assert!(matches!(err, Error::Epk));
+
+        // A key of another type.
+        let mut head = header();
+        head["epk"] = serde_json::json!({
+            "kty": "OKP",
+            "crv": "X25519",
+            "x": "3p7bfXt9wbTTW2HC7OQ1Nz-DQ8hbeGdNrfx-FG-IK08",
+        });
+        let head: Protected = serde_json::from_value(head).unwrap();
+        let err = bob().decrypt_key(&head, None, &mut OsRng).unwrap_err();
+        assert!(matches!(err, Error::Epk));
+
+        let mut head: Protected = serde_json::from_value(header()).unwrap();
+        head.oth.epk = None;
+        let err = bob().decrypt_key(&head, None, &mut OsRng).unwrap_err();
+        assert!(matches!(err, Error::Epk));
+    }
+
+    fn roundtrip(key: &Key) {
+        use Encryption::*;
+
+        for alg in algorithms() {
+            for enc in [A128Gcm, A256Gcm] {
+                let mut prot = prot(alg, enc);
+                prot.oth.apu = Some(b"Alice".to_vec().into());
+
+                let jwe = Flattened::encrypt(key, prot, b"hi", &mut OsRng).unwrap();
+                assert_eq!(
+                    jwe.recipient.encrypted_key.is_some(),
+                    alg != KeyManagement::EcdhEs
+                );
+
+                // The ephemeral key is public.
+                let epk = jwe.header().unwrap().oth.epk.unwrap();
+                assert!(matches!(epk.key, jose_jwk::Key::Ec(ref ec) if ec.d.is_none()));
+
+                let jwe: Flattened = jwe.to_compact().unwrap().parse().unwrap();
+                assert_eq!(jwe.decrypt(key, &mut OsRng).unwrap(), b"hi");
+            }
+        }
+    }
+
+    #[test]
+    fn p256() {
+        roundtrip(&bob());
+    }
+
+    #[cfg(feature = "p384")]
+    #[test]
+    fn p384() {
+        roundtrip(&Key::from(p384::SecretKey::random(&mut OsRng)));
+    }
+
+    #[cfg(feature = "p521")]
+    #[test]
+    fn p521() {
+        roundtrip(&Key::from(p521::SecretKey::random(&mut OsRng)));
+    }
+
+    #[test]
+    fn mismatch() {
+        let prot = prot(KeyManagement::EcdhEs, Encryption::A128Gcm);
+        let jwe = Flattened::encrypt(&bob(), prot, b"hi", &mut OsRng).unwrap();
+
+        // The ephemeral key is on P-256, the recipient's key is not.
+        #[cfg(feature = "p384")]
+        {
+            let other = Key::from(p384::SecretKey::random(&mut OsRng));
+            let err = jwe.decrypt(&other, &mut OsRng).unwrap_err();
+            assert!(matches!(err, Error::Epk));
+        }
+
+        let err = jwe.decrypt(&bob_public(), &mut OsRng).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Key(jose_jwk::crypto::Error::NotPrivate)
+        ));
+
+        let prot = self::prot(KeyManagement::RsaOaep, Encryption::A128Gcm);
+        let err = Flattened::encrypt(&bob(), prot, b"hi", &mut OsRng).unwrap_err();
+        assert!(matches!(err, Error::Key(_)));
+    }
+
+    fn bob_public() -> Key {
+        key(serde_json::json!({
+            "kty": "EC",
+            "crv": "P-256",
+            "x": "weNJy2HscCSM6AEDTDg04biOvhFhyyWvOHQfeF_PxMQ",
+            "y": "e8lnCO-AlStT-NJVX-crhB7QRYhiix03illJOVAOyck",
+        }))
+    }
+}
+
+#[cfg(all(test, feature = "x25519", feature = "aes-gcm"))]
+mod x25519 {
+    use jose_jwa::{Encryption, KeyManagement};
+    use jose_jwe::crypto::Error;
+    use jose_jwe::Flattened;
+    use jose_jwk::crypto::Key;
+    use rand_core::OsRng;
+    use x25519_dalek::{PublicKey, StaticSecret};
+
+    use super::prot;
+
+    fn algorithms() -> Vec<KeyManagement> {
+        #[allow(unused_mut)]
+        let mut all = vec![KeyManagement::EcdhEs];
+
+        #[cfg(feature = "aes-kw")]
+        all.extend([
+            KeyManagement::EcdhEsA128Kw,
+            KeyManagement::EcdhEsA192Kw,
+            KeyManagement::EcdhEsA256Kw,
+        ]);
+
+        all
+    }
+
+    #[test]
+    fn roundtrip() {
+        let secret = StaticSecret::random_from_rng(OsRng);
+        let public = PublicKey::from(&secret);
+
+        for alg in algorithms() {
+            let jwe =
+                Flattened::encrypt(&public, prot(alg, Encryption::A256Gcm), b"hi", &mut OsRng)
+                    .unwrap();
+            let epk = jwe.header().unwrap().oth.epk.unwrap();
+            assert!(matches!(epk.key, jose_jwk::Key::Okp(ref okp) if okp.d.is_none()));
+
+            let jwe: Flattened = jwe.to_compact().unwrap().parse().unwrap();
+            assert_eq!(jwe.decrypt(&secret, &mut OsRng).unwrap(), b"hi");
+        }
+    }
+
+    #[test]
+    fn low_order() {
+        let secret = StaticSecret::random_from_rng(OsRng);
+        let public = PublicKey::from(&secret);
+        let mut jwe = Flattened::encrypt(
+            &public,
+            prot(KeyManagement::EcdhEs, Encryption::A256Gcm),
+            b"",
+            &mut OsRng,
+        )
+        .unwrap();
+
+        // The identity point gives an all-zero shared secret.
+        let mut head = jwe.header().unwrap();
+        let Some(jose_jwk::Key::Okp(okp)) = head.oth.epk.as_mut().map(|epk| &mut epk.key) else {
+            panic!("expected an OKP ephemeral key");
+        };
+        okp.x = vec![0; 32].into();
+        jwe.protected = None;
+        jwe.unprotected = Some(head.oth);
+
+        let err = jwe.decrypt(&secret, &mut OsRng).unwrap_err();
+        assert!(matches!(err, Error::Epk));
+
+        let err = Flattened::encrypt(
+            &PublicKey::from([0; 32]),
+            prot(KeyManagement::EcdhEs, Encryption::A256Gcm),
+            b"",
+            &mut OsRng,
+        );
+        assert!(matches!(err.unwrap_err(), Error::Key(_)));
+    }
+
+    // Bob's key of RFC 7748 Section 6.1, as in RFC 8037 Appendix A.6
+    #[test]
+    fn jwk() {
+        let jwk: jose_jwk::Jwk = serde_json::from_value(serde_json::json!({
+            "kty": "OKP",
+            "crv": "X25519",
+            "kid": "Bob",
+            "d": "XasIfmJKikt54X-Lg4AO5m87sSkmGLb9HC-LJ_-I4Os",
+            "x": "3p7bfXt9wbTTW2HC7OQ1Nz-DQ8hbeGdNrfx-FG-IK08",
+        }))
+        .unwrap();
+
+        let secret = Key::try_from(&jwk.key).unwrap();
+        let mut public = jwk.key.clone();
+        if let jose_jwk::Key::Okp(okp) = &mut public {
+            okp.d = None;
+        }
+        let public = Key::try_from(&public).unwrap();
+
+        for alg in algorithms() {
+            let jwe =
+                Flattened::encrypt(&public, prot(alg, Encryption::A256Gcm), b"hi", &mut OsRng)
+                    .unwrap();
+            assert_eq!(jwe.decrypt(&secret, &mut OsRng).unwrap(), b"hi");
+
+            let err = jwe.decrypt(&public, &mut OsRng).unwrap_err();
+            assert!(matches!(
+                err,
+                Error::Key(jose_jwk::crypto::Error::NotPrivate)
+            ));
+        }
+
+        assert_eq!(jose_jwk::Key::from(&secret), jwk.key);
+    }
+}
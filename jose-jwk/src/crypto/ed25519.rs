@@ -0,0 +1,108 @@
+// SPDX-FileCopyrightText: 2022 Profian Inc. <opensource@profian.com>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+#![cfg(feature = "ed25519")]
+
+use ed25519_dalek::{SigningKey, VerifyingKey};
+
+use jose_jwa::{Algorithm, Algorithm::Signing, Signing::*};
+
+use super::Error;
+use super::KeyInfo;
+use crate::{Okp, OkpCurves};
+
+impl KeyInfo for VerifyingKey {
+    fn strength(&self) -> usize {
+        16
+    }
+
+    fn is_supported(&self, algo: &Algorithm) -> bool {
+        matches!(algo, Signing(EdDsa))
+    }
+}
+
+impl KeyInfo for SigningKey {
+    fn strength(&self) -> usize {
+        16
+    }
+
+    fn is_supported(&self, algo: &Algorithm) -> bool {
+        matches!(algo, Signing(EdDsa))
+    }
+}
+
+impl From<&VerifyingKey> for Okp {
+    fn from(pk: &VerifyingKey) -> Self {
+        Self {
+            crv: OkpCurves::Ed25519,
+            x: pk.to_bytes().to_vec().into(),
+            d: None,
+        }
+    }
+}
+
+impl From<VerifyingKey> for Okp {
+    fn from(pk: VerifyingKey) -> Self {
+        (&pk).into()
+    }
+}
+
+impl TryFrom<&Okp> for VerifyingKey {
+    type Error = Error;
+
+    fn try_from(value: &Okp) -> Result<Self, Self::Error> {
+        if value.crv != OkpCurves::Ed25519 {
+            return Err(Error::AlgMismatch);
+        }
+
+        let x = value.x.as_ref().try_into().map_err(|_| Error::Invalid)?;
+        Self::from_bytes(x).map_err(|_| Error::Invalid)
+    }
+}
+
+impl TryFrom<Okp> for VerifyingKey {
+    type Error = Error;
+
+    fn try_from(value: Okp) -> Result<Self, Self::Error> {
+        (&value).try_into()
+    }
+}
+
+impl From<&SigningKey> for Okp {
+    fn from(sk: &SigningKey) -> Self {
+        let mut key: Self = sk.verifying_key().into();
+        key.d = Some(sk.to_bytes().to_vec().into());
+        key
+    }
+}
+
+impl From<SigningKey> for Okp {
+    fn from(sk: SigningKey) -> Self {
+        (&sk).into()
+    }
+}
+
+impl TryFrom<&Okp> for SigningKey {
+    type Error = Error;
+
+    fn try_from(value: &Okp) -> Result<Self, Self::Error> {
+        if value.crv != OkpCurves::Ed25519 {
+            return Err(Error::AlgMismatch);
+        }
+
+        if let Some(d) = value.d.as_ref() {
+            let d = d.as_ref().try_into().map_err(|_| Error::Invalid)?;
+            return Ok(Self::from_bytes(d));
+        }
+
+        Err(Error::NotPrivate)
+    }
+}
+
+impl TryFrom<Okp> for SigningKey {
+    type Error = Error;
+
+    fn try_from(value: Okp) -> Result<Self, Self::Error> {
+        (&value).try_into()
+    }
+}
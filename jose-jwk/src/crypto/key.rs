@@ -34,6 +34,22 @@ pub enum Key {
     /// A P-384 key.
     #[cfg(feature = "p384")]
     P384(super::Kind<p384::PublicKey, p384::SecretKey>),
+
+    /// A P-521 key.
+    #[cfg(feature = "p521")]
+    P521(super::Kind<p521::PublicKey, p521::SecretKey>),
+
+    /// A secp256k1 key.
+    #[cfg(feature = "k256")]
+    P256K(super::Kind<k256::PublicKey, k256::SecretKey>),
+
+    /// An Ed25519 key.
+    #[cfg(feature = "ed25519")]
+    Ed25519(super::Kind<ed25519_dalek::VerifyingKey, ed25519_dalek::SigningKey>),
+
+    /// An X25519 key.
+    #[cfg(feature = "x25519")]
+    X25519(super::Kind<x25519_dalek::PublicKey, x25519_dalek::StaticSecret>),
 }
 
 impl KeyInfo for Key {
@@ -49,6 +65,18 @@ impl KeyInfo for Key {
 
             #[cfg(feature = "p384")]
             Self::P384(k) => k.strength(),
+
+            #[cfg(feature = "p521")]
+            Self::P521(k) => k.strength(),
+
+            #[cfg(feature = "k256")]
+            Self::P256K(k) => k.strength(),
+
+            #[cfg(feature = "ed25519")]
+            Self::Ed25519(k) => k.strength(),
+
+            #[cfg(feature = "x25519")]
+            Self::X25519(k) => k.strength(),
         }
     }
 
@@ -64,6 +92,18 @@ impl KeyInfo for Key {
 
             #[cfg(feature = "p384")]
             Self::P384(k) => k.is_supported(algo),
+
+            #[cfg(feature = "p521")]
+            Self::P521(k) => k.is_supported(algo),
+
+            #[cfg(feature = "k256")]
+            Self::P256K(k) => k.is_supported(algo),
+
+            #[cfg(feature = "ed25519")]
+            Self::Ed25519(k) => k.is_supported(algo),
+
+            #[cfg(feature = "x25519")]
+            Self::X25519(k) => k.is_supported(algo),
         }
     }
 }
@@ -137,6 +177,90 @@ impl From<p384::SecretKey> for Key {
     }
 }
 
+#[cfg(feature = "p521")]
+impl From<super::Kind<p521::PublicKey, p521::SecretKey>> for Key {
+    fn from(value: super::Kind<p521::PublicKey, p521::SecretKey>) -> Self {
+        Self::P521(value)
+    }
+}
+
+#[cfg(feature = "p521")]
+impl From<p521::PublicKey> for Key {
+    fn from(value: p521::PublicKey) -> Self {
+        Self::P521(super::Kind::Public(value))
+    }
+}
+
+#[cfg(feature = "p521")]
+impl From<p521::SecretKey> for Key {
+    fn from(value: p521::SecretKey) -> Self {
+        Self::P521(super::Kind::Secret(value))
+    }
+}
+
+#[cfg(feature = "k256")]
+impl From<super::Kind<k256::PublicKey, k256::SecretKey>> for Key {
+    fn from(value: super::Kind<k256::PublicKey, k256::SecretKey>) -> Self {
+        Self::P256K(value)
+    }
+}
+
+#[cfg(feature = "k256")]
+impl From<k256::PublicKey> for Key {
+    fn from(value: k256::PublicKey) -> Self {
+        Self::P256K(super::Kind::Public(value))
+    }
+}
+
+#[cfg(feature = "k256")]
+impl From<k256::SecretKey> for Key {
+    fn from(value: k256::SecretKey) -> Self {
+        Self::P256K(super::Kind::Secret(value))
+    }
+}
+
+#[cfg(feature = "ed25519")]
+impl From<super::Kind<ed25519_dalek::VerifyingKey, ed25519_dalek::SigningKey>> for Key {
+    fn from(value: super::Kind<ed25519_dalek::VerifyingKey, ed25519_dalek::SigningKey>) -> Self {
+        Self::Ed25519(value)
+    }
+}
+
+#[cfg(feature = "ed25519")]
+impl From<ed25519_dalek::VerifyingKey> for Key {
+    fn from(value: ed25519_dalek::VerifyingKey) -> Self {
+        Self::Ed25519(super::Kind::Public(value))
+    }
+}
+
+#[cfg(feature = "ed25519")]
+impl From<ed25519_dalek::SigningKey> for Key {
+    fn from(value: ed25519_dalek::SigningKey) -> Self {
+        Self::Ed25519(super::Kind::Secret(value))
+    }
+}
+
+#[cfg(feature = "x25519")]
+impl From<super::Kind<x25519_dalek::PublicKey, x25519_dalek::StaticSecret>> for Key {
+    fn from(value: super::Kind<x25519_dalek::PublicKey, x25519_dalek::StaticSecret>) -> Self {
+        Self::X25519(value)
+    }
+}
+
+#[cfg(feature = "x25519")]
+impl From<x25519_dalek::PublicKey> for Key {
+    fn from(value: x25519_dalek::PublicKey) -> Self {
+        Self::X25519(super::Kind::Public(value))
+    }
+}
+
+#[cfg(feature = "x25519")]
+impl From<x25519_dalek::StaticSecret> for Key {
+    fn from(value: x25519_dalek::StaticSecret) -> Self {
+        Self::X25519(super::Kind::Secret(value))
+    }
+}
+
 impl From<&crate::Oct> for Key {
     fn from(value: &crate::Oct) -> Self {
         Self::Oct(value.k.to_vec().into_boxed_slice().into())
@@ -152,7 +276,7 @@ impl TryFrom<&crate::Rsa> for Key {
     }
 }
 
-#[cfg(any(feature = "p256", feature = "p384"))]
+#[cfg(any(feature = "k256", feature = "p256", feature = "p384", feature = "p521"))]
 impl TryFrom<&crate::Ec> for Key {
     type Error = super::Error;
 
@@ -164,6 +288,30 @@ impl TryFrom<&crate::Ec> for Key {
             #[cfg(feature = "p384")]
             crate::EcCurves::P384 => Ok(Self::P384(value.try_into()?)),
 
+            #[cfg(feature = "p521")]
+            crate::EcCurves::P521 => Ok(Self::P521(value.try_into()?)),
+
+            #[cfg(feature = "k256")]
+            crate::EcCurves::P256K => Ok(Self::P256K(value.try_into()?)),
+
+            #[allow(unreachable_patterns)]
+            _ => Err(super::Error::Unsupported),
+        }
+    }
+}
+
+#[cfg(any(feature = "ed25519", feature = "x25519"))]
+impl TryFrom<&crate::Okp> for Key {
+    type Error = super::Error;
+
+    fn try_from(value: &crate::Okp) -> Result<Self, Self::Error> {
+        match value.crv {
+            #[cfg(feature = "ed25519")]
+            crate::OkpCurves::Ed25519 => Ok(Self::Ed25519(value.try_into()?)),
+
+            #[cfg(feature = "x25519")]
+            crate::OkpCurves::X25519 => Ok(Self::X25519(value.try_into()?)),
+
             _ => Err(super::Error::Unsupported),
         }
     }
@@ -179,9 +327,13 @@ impl TryFrom<&crate::Key> for Key {
             #[cfg(feature = "rsa")]
             crate::Key::Rsa(rsa) => rsa.try_into(),
 
-            #[cfg(any(feature = "p256", feature = "p384"))]
+            #[cfg(any(feature = "k256", feature = "p256", feature = "p384", feature = "p521"))]
             crate::Key::Ec(ec) => ec.try_into(),
 
+            #[cfg(any(feature = "ed25519", feature = "x25519"))]
+            crate::Key::Okp(okp) => okp.try_into(),
+
+            #[allow(unreachable_patterns)]
             _ => Err(super::Error::Unsupported),
         }
     }
@@ -211,6 +363,30 @@ impl From<&Key> for crate::Key {
                 super::Kind::Public(public) => Self::Ec(public.into()),
                 super::Kind::Secret(secret) => Self::Ec(secret.into()),
             },
+
+            #[cfg(feature = "p521")]
+            Key::P521(kind) => match kind {
+                super::Kind::Public(public) => Self::Ec(public.into()),
+                super::Kind::Secret(secret) => Self::Ec(secret.into()),
+            },
+
+            #[cfg(feature = "k256")]
+            Key::P256K(kind) => match kind {
+                super::Kind::Public(public) => Self::Ec(public.into()),
+                super::Kind::Secret(secret) => Self::Ec(secret.into()),
+            },
+
+            #[cfg(feature = "ed25519")]
+            Key::Ed25519(kind) => match kind {
+                super::Kind::Public(public) => Self::Okp(public.into()),
+                super::Kind::Secret(secret) => Self::Okp(secret.into()),
+            },
+
+            #[cfg(feature = "x25519")]
+            Key::X25519(kind) => match kind {
+                super::Kind::Public(public) => Self::Okp(public.into()),
+                super::Kind::Secret(secret) => Self::Okp(secret.into()),
+            },
         }
     }
 }
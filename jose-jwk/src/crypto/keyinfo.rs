@@ -4,6 +4,10 @@
 use core::ops::Deref;
 
 use alloc::{boxed::Box, vec::Vec};
+use jose_jwa::KeyManagement::{
+    A128Kw, A192Kw, A256Kw, Direct, EcdhEs, EcdhEsA128Kw, EcdhEsA192Kw, EcdhEsA256Kw, Rsa1_5,
+    RsaOaep, RsaOaep256,
+};
 use jose_jwa::{Algorithm, Algorithm::Signing, Signing::*};
 
 use crate::{Ec, EcCurves, Jwk, Key, Oct, Okp, OkpCurves, Rsa};
@@ -72,6 +76,10 @@ impl KeyInfo for [u8] {
             (Signing(Hs256), 16..) => true,
             (Signing(Hs384), 24..) => true,
             (Signing(Hs512), 32..) => true,
+            (Algorithm::KeyManagement(Direct), 1..) => true,
+            (Algorithm::KeyManagement(A128Kw), 16) => true,
+            (Algorithm::KeyManagement(A192Kw), 24) => true,
+            (Algorithm::KeyManagement(A256Kw), 32) => true,
             _ => false,
         }
     }
@@ -124,6 +132,10 @@ impl KeyInfo for Ec {
             (EcCurves::P256K, Signing(Es256K)) => true,
             (EcCurves::P384, Signing(Es384)) => true,
             (EcCurves::P521, Signing(Es512)) => true,
+            (
+                EcCurves::P256 | EcCurves::P384 | EcCurves::P521,
+                Algorithm::KeyManagement(EcdhEs | EcdhEsA128Kw | EcdhEsA192Kw | EcdhEsA256Kw),
+            ) => true,
             _ => false,
         }
     }
@@ -140,6 +152,10 @@ impl KeyInfo for Oct {
             (Signing(Hs256), 16..) => true,
             (Signing(Hs384), 24..) => true,
             (Signing(Hs512), 32..) => true,
+            (Algorithm::KeyManagement(Direct), 1..) => true,
+            (Algorithm::KeyManagement(A128Kw), 16) => true,
+            (Algorithm::KeyManagement(A192Kw), 24) => true,
+            (Algorithm::KeyManagement(A256Kw), 32) => true,
             _ => false,
         }
     }
@@ -156,7 +172,16 @@ impl KeyInfo for Okp {
     }
 
     fn is_supported(&self, algo: &Algorithm) -> bool {
-        matches!(algo, Signing(EdDsa))
+        match self.crv {
+            OkpCurves::Ed25519 => matches!(algo, Signing(EdDsa)),
+            OkpCurves::X25519 => matches!(
+                algo,
+                Algorithm::KeyManagement(EcdhEs | EcdhEsA128Kw | EcdhEsA192Kw | EcdhEsA256Kw)
+            ),
+
+            // There is no Ed448 or X448 implementation to use the key with.
+            OkpCurves::Ed448 | OkpCurves::X448 => false,
+        }
     }
 }
 
@@ -174,6 +199,9 @@ impl KeyInfo for Rsa {
             (Signing(Ps256), 16..) => true,
             (Signing(Ps384), 24..) => true,
             (Signing(Ps512), 32..) => true,
+            (Algorithm::KeyManagement(RsaOaep), 16..) => true,
+            (Algorithm::KeyManagement(RsaOaep256), 16..) => true,
+            (Algorithm::KeyManagement(Rsa1_5), 16..) => true,
             _ => false,
         }
     }
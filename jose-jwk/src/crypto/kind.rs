@@ -98,3 +98,95 @@ impl TryFrom<&crate::Ec> for Kind<p384::PublicKey, p384::SecretKey> {
         }
     }
 }
+
+#[cfg(feature = "p521")]
+impl From<&Kind<p521::PublicKey, p521::SecretKey>> for crate::Ec {
+    fn from(value: &Kind<p521::PublicKey, p521::SecretKey>) -> Self {
+        match value {
+            Kind::Public(key) => key.into(),
+            Kind::Secret(key) => key.into(),
+        }
+    }
+}
+
+#[cfg(feature = "p521")]
+impl TryFrom<&crate::Ec> for Kind<p521::PublicKey, p521::SecretKey> {
+    type Error = super::Error;
+
+    fn try_from(value: &crate::Ec) -> Result<Self, Self::Error> {
+        if value.d.is_none() {
+            Ok(Kind::Public(value.try_into()?))
+        } else {
+            Ok(Kind::Secret(value.try_into()?))
+        }
+    }
+}
+
+#[cfg(feature = "k256")]
+impl From<&Kind<k256::PublicKey, k256::SecretKey>> for crate::Ec {
+    fn from(value: &Kind<k256::PublicKey, k256::SecretKey>) -> Self {
+        match value {
+            Kind::Public(key) => key.into(),
+            Kind::Secret(key) => key.into(),
+        }
+    }
+}
+
+#[cfg(feature = "k256")]
+impl TryFrom<&crate::Ec> for Kind<k256::PublicKey, k256::SecretKey> {
+    type Error = super::Error;
+
+    fn try_from(value: &crate::Ec) -> Result<Self, Self::Error> {
+        if value.d.is_none() {
+            Ok(Kind::Public(value.try_into()?))
+        } else {
+            Ok(Kind::Secret(value.try_into()?))
+        }
+    }
+}
+
+#[cfg(feature = "ed25519")]
+impl From<&Kind<ed25519_dalek::VerifyingKey, ed25519_dalek::SigningKey>> for crate::Okp {
+    fn from(value: &Kind<ed25519_dalek::VerifyingKey, ed25519_dalek::SigningKey>) -> Self {
+        match value {
+            Kind::Public(key) => key.into(),
+            Kind::Secret(key) => key.into(),
+        }
+    }
+}
+
+#[cfg(feature = "ed25519")]
+impl TryFrom<&crate::Okp> for Kind<ed25519_dalek::VerifyingKey, ed25519_dalek::SigningKey> {
+    type Error = super::Error;
+
+    fn try_from(value: &crate::Okp) -> Result<Self, Self::Error> {
+        if value.d.is_none() {
+            Ok(Kind::Public(value.try_into()?))
+        } else {
+            Ok(Kind::Secret(value.try_into()?))
+        }
+    }
+}
+
+#[cfg(feature = "x25519")]
+impl From<&Kind<x25519_dalek::PublicKey, x25519_dalek::StaticSecret>> for crate::Okp {
+    fn from(value: &Kind<x25519_dalek::PublicKey, x25519_dalek::StaticSecret>) -> Self {
+        match value {
+            Kind::Public(key) => key.into(),
+            Kind::Secret(key) => key.into(),
+        }
+    }
+}
+
+#[cfg(feature = "x25519")]
+impl TryFrom<&crate::Okp> for Kind<x25519_dalek::PublicKey, x25519_dalek::StaticSecret> {
+    type Error = super::Error;
+
+    fn try_from(value: &crate::Okp) -> Result<Self, Self::Error> {
+        if value.d.is_none() {
+            Ok(Kind::Public(value.try_into()?))
+        } else {
+            Ok(Kind::Secret(value.try_into()?))
+        }
+    }
+}
@@ -3,12 +3,16 @@
 
 //! Cryptographic primitives for JWK
 
+mod ed25519;
+mod k256;
 mod key;
 mod keyinfo;
 mod kind;
 mod p256;
 mod p384;
+mod p521;
 mod rsa;
+mod x25519;
 
 pub use key::Key;
 pub use keyinfo::KeyInfo;
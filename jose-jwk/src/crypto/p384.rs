@@ -6,7 +6,8 @@
 use p384::elliptic_curve::sec1::{FromEncodedPoint, ToEncodedPoint};
 use p384::{EncodedPoint, FieldBytes, PublicKey, SecretKey};
 
-use jose_jwa::{Algorithm, Algorithm::Signing, Signing::*};
+use jose_jwa::KeyManagement::{EcdhEs, EcdhEsA128Kw, EcdhEsA192Kw, EcdhEsA256Kw};
+use jose_jwa::{Algorithm, Algorithm::KeyManagement, Algorithm::Signing, Signing::*};
 
 use super::Error;
 use super::KeyInfo;
@@ -18,7 +19,10 @@ impl KeyInfo for PublicKey {
     }
 
     fn is_supported(&self, algo: &Algorithm) -> bool {
-        matches!(algo, Signing(Es384))
+        matches!(
+            algo,
+            Signing(Es384) | KeyManagement(EcdhEs | EcdhEsA128Kw | EcdhEsA192Kw | EcdhEsA256Kw)
+        )
     }
 }
 
@@ -28,7 +32,10 @@ impl KeyInfo for SecretKey {
     }
 
     fn is_supported(&self, algo: &Algorithm) -> bool {
-        matches!(algo, Signing(Es384))
+        matches!(
+            algo,
+            Signing(Es384) | KeyManagement(EcdhEs | EcdhEsA128Kw | EcdhEsA192Kw | EcdhEsA256Kw)
+        )
     }
 }
 
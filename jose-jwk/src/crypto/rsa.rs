@@ -8,6 +8,7 @@ use rsa::{
     BigUint, RsaPrivateKey, RsaPublicKey,
 };
 
+use jose_jwa::KeyManagement::{Rsa1_5, RsaOaep, RsaOaep256};
 use jose_jwa::{Algorithm, Algorithm::Signing, Signing::*};
 
 use super::Error;
@@ -37,6 +38,9 @@ impl KeyInfo for RsaPublicKey {
             Signing(Ps256) => true,
             Signing(Ps384) => true,
             Signing(Ps512) => true,
+            Algorithm::KeyManagement(RsaOaep) => true,
+            Algorithm::KeyManagement(RsaOaep256) => true,
+            Algorithm::KeyManagement(Rsa1_5) => true,
             _ => false,
         }
     }
@@ -56,6 +60,9 @@ impl KeyInfo for RsaPrivateKey {
             (Signing(Ps256), 16..) => true,
             (Signing(Ps384), 24..) => true,
             (Signing(Ps512), 32..) => true,
+            (Algorithm::KeyManagement(RsaOaep), 16..) => true,
+            (Algorithm::KeyManagement(RsaOaep256), 16..) => true,
+            (Algorithm::KeyManagement(Rsa1_5), 16..) => true,
             _ => false,
         }
     }
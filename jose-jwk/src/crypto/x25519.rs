@@ -0,0 +1,111 @@
+// SPDX-FileCopyrightText: 2022 Profian Inc. <opensource@profian.com>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+#![cfg(feature = "x25519")]
+
+use x25519_dalek::{PublicKey, StaticSecret};
+
+use jose_jwa::{Algorithm, KeyManagement::*};
+
+use super::Error;
+use super::KeyInfo;
+use crate::{Okp, OkpCurves};
+
+impl KeyInfo for PublicKey {
+    fn strength(&self) -> usize {
+        16
+    }
+
+    fn is_supported(&self, algo: &Algorithm) -> bool {
+        matches!(
+            algo,
+            Algorithm::KeyManagement(EcdhEs | EcdhEsA128Kw | EcdhEsA192Kw | EcdhEsA256Kw)
+        )
+    }
+}
+
+impl KeyInfo for StaticSecret {
+    fn strength(&self) -> usize {
+        16
+    }
+
+    fn is_supported(&self, algo: &Algorithm) -> bool {
+        PublicKey::from(self).is_supported(algo)
+    }
+}
+
+impl From<&PublicKey> for Okp {
+    fn from(pk: &PublicKey) -> Self {
+        Self {
+            crv: OkpCurves::X25519,
+            x: pk.as_bytes().to_vec().into(),
+            d: None,
+        }
+    }
+}
+
+impl From<PublicKey> for Okp {
+    fn from(pk: PublicKey) -> Self {
+        (&pk).into()
+    }
+}
+
+impl TryFrom<&Okp> for PublicKey {
+    type Error = Error;
+
+    fn try_from(value: &Okp) -> Result<Self, Self::Error> {
+        if value.crv != OkpCurves::X25519 {
+            return Err(Error::AlgMismatch);
+        }
+
+        let x: [u8; 32] = value.x.as_ref().try_into().map_err(|_| Error::Invalid)?;
+        Ok(Self::from(x))
+    }
+}
+
+impl TryFrom<Okp> for PublicKey {
+    type Error = Error;
+
+    fn try_from(value: Okp) -> Result<Self, Self::Error> {
+        (&value).try_into()
+    }
+}
+
+impl From<&StaticSecret> for Okp {
+    fn from(sk: &StaticSecret) -> Self {
+        let mut key: Self = PublicKey::from(sk).into();
+        key.d = Some(sk.to_bytes().to_vec().into());
+        key
+    }
+}
+
+impl From<StaticSecret> for Okp {
+    fn from(sk: StaticSecret) -> Self {
+        (&sk).into()
+    }
+}
+
+impl TryFrom<&Okp> for StaticSecret {
+    type Error = Error;
+
+    fn try_from(value: &Okp) -> Result<Self, Self::Error> {
+        if value.crv != OkpCurves::X25519 {
+            return Err(Error::AlgMismatch);
+        }
+
+        if let Some(d) = value.d.as_ref() {
+            let d: [u8; 32] = d.as_ref().try_into().map_err(|_| Error::Invalid)?;
+            return Ok(Self::from(d));
+        }
+
+        Err(Error::NotPrivate)
+    }
+}
+
+impl TryFrom<Okp> for StaticSecret {
+    type Error = Error;
+
+    fn try_from(value: Okp) -> Result<Self, Self::Error> {
+        (&value).try_into()
+    }
+}
@@ -45,6 +45,23 @@ pub struct JwkSet {
     pub keys: alloc::vec::Vec<Jwk>,
 }
 
+impl JwkSet {
+    /// Selects the keys that may be used for an operation with an algorithm.
+    ///
+    /// If `kid` is specified, only the keys with that key identifier are
+    /// selected. Otherwise, every compatible key is selected.
+    pub fn select<'a: 'b, 'b>(
+        &'a self,
+        kid: Option<&'b str>,
+        alg: &'b jose_jwa::Algorithm,
+        op: Operations,
+    ) -> impl Iterator<Item = &'a Jwk> + 'b {
+        self.keys.iter().filter(move |jwk| {
+            kid.map_or(true, |kid| jwk.prm.kid.as_deref() == Some(kid)) && jwk.allows(alg, op)
+        })
+    }
+}
+
 /// A JSON Web Key.
 ///
 /// This type is defined in [RFC7517 Section 4].
@@ -60,3 +77,23 @@ pub struct Jwk {
     #[serde(flatten)]
     pub prm: Parameters,
 }
+
+impl Jwk {
+    /// Returns whether the key may be used for an operation with an algorithm.
+    ///
+    /// This checks that the key material supports the algorithm and that the
+    /// `alg`, `use` and `key_ops` parameters, when present, permit it.
+    pub fn allows(&self, alg: &jose_jwa::Algorithm, op: Operations) -> bool {
+        use crypto::KeyInfo;
+
+        let cls = match op {
+            Operations::Sign | Operations::Verify => Class::Signing,
+            _ => Class::Encryption,
+        };
+
+        self.key.is_supported(alg)
+            && self.prm.alg.as_ref().map_or(true, |a| a == alg)
+            && self.prm.cls.map_or(true, |c| c == cls)
+            && self.prm.ops.as_ref().map_or(true, |o| o.contains(&op))
+    }
+}
@@ -507,7 +507,15 @@ mod rfc8037 {
         };
 
         assert_eq!(jwk, serde_json::from_value(val.clone()).unwrap());
-        assert_eq!(val, serde_json::to_value(jwk).unwrap());
+        assert_eq!(val, serde_json::to_value(&jwk).unwrap());
+
+        #[cfg(feature = "x25519")]
+        if let Key::Okp(key) = &jwk.key {
+            let pk = x25519_dalek::PublicKey::try_from(key).unwrap();
+            assert_eq!(key, &pk.into());
+        } else {
+            unreachable!()
+        }
     }
 
     #[test]
@@ -538,6 +546,61 @@ mod rfc8037 {
         };
 
         assert_eq!(jwk, serde_json::from_value(val.clone()).unwrap());
-        assert_eq!(val, serde_json::to_value(jwk).unwrap());
+        assert_eq!(val, serde_json::to_value(&jwk).unwrap());
+
+        // There is no X448 implementation to agree on a key with.
+        let alg = jose_jwa::Algorithm::KeyManagement(jose_jwa::KeyManagement::EcdhEs);
+        assert!(!crypto::KeyInfo::is_supported(&jwk.key, &alg));
+    }
+}
+
+#[cfg(test)]
+mod set {
+    use jose_jwa::{Algorithm, Signing};
+    use jose_jwk::*;
+
+    #[test]
+    fn select() {
+        let set: JwkSet = serde_json::from_value(serde_json::json!({
+            "keys": [
+                {
+                    "kty": "OKP",
+                    "crv": "Ed25519",
+                    "kid": "a",
+                    "use": "sig",
+                    "x": "11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo"
+                },
+                {
+                    "kty": "OKP",
+                    "crv": "Ed25519",
+                    "kid": "b",
+                    "key_ops": ["sign"],
+                    "x": "11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo"
+                },
+                {
+                    "kty": "OKP",
+                    "crv": "X25519",
+                    "kid": "c",
+                    "x": "3p7bfXt9wbTTW2HC7OQ1Nz-DQ8hbeGdNrfx-FG-IK08"
+                }
+            ]
+        }))
+        .unwrap();
+
+        let kids = |kid, alg: &Algorithm, op| -> Vec<String> {
+            set.select(kid, alg, op)
+                .map(|jwk| jwk.prm.kid.clone().unwrap())
+                .collect()
+        };
+
+        let eddsa = Algorithm::Signing(Signing::EdDsa);
+        assert_eq!(kids(None, &eddsa, Operations::Verify), ["a"]);
+        assert_eq!(kids(None, &eddsa, Operations::Sign), ["a", "b"]);
+        assert_eq!(kids(Some("b"), &eddsa, Operations::Sign), ["b"]);
+        assert!(kids(Some("b"), &eddsa, Operations::Verify).is_empty());
+        assert!(kids(Some("c"), &eddsa, Operations::Verify).is_empty());
+
+        let es256 = Algorithm::Signing(Signing::Es256);
+        assert!(kids(None, &es256, Operations::Verify).is_empty());
     }
 }
@@ -1,15 +1,20 @@
 // SPDX-FileCopyrightText: 2022 Profian Inc. <opensource@profian.com>
 // SPDX-License-Identifier: Apache-2.0 OR MIT
 
+use alloc::string::{String, ToString};
 use core::fmt::Display;
 use core::{convert::Infallible, str::FromStr};
 
 use jose_b64::base64ct::{Base64UrlUnpadded, Encoding};
+use jose_b64::serde::Bytes;
 use jose_b64::stream::Error;
 
 use crate::{Flattened, General, Jws, Signature};
 
-impl FromStr for Jws {
+impl<P: TryFrom<Bytes>> FromStr for Jws<P>
+where
+    P::Error: Display,
+{
     type Err = Error<serde_json::Error>;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
@@ -17,7 +22,10 @@ impl FromStr for Jws {
     }
 }
 
-impl FromStr for General {
+impl<P: TryFrom<Bytes>> FromStr for General<P>
+where
+    P::Error: Display,
+{
     type Err = Error<serde_json::Error>;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
@@ -25,7 +33,10 @@ impl FromStr for General {
     }
 }
 
-impl FromStr for Flattened {
+impl<P: TryFrom<Bytes>> FromStr for Flattened<P>
+where
+    P::Error: Display,
+{
     type Err = Error<serde_json::Error>;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
@@ -38,35 +49,145 @@ impl FromStr for Flattened {
             return Err(Error::Length);
         }
 
-        let payload = match payl {
+        let signature = Signature {
+            protected: Some(prot.parse()?),
+            header: None,
+            signature: sign.parse().map_err(|e: Error<Infallible>| e.cast())?,
+        };
+
+        // An unencoded payload appears as is (RFC 7797 Section 5.2).
+        let payload: Option<Bytes> = match payl {
             "" => None,
+            _ if !signature.b64() => Some(payl.as_bytes().to_vec().into()),
             _ => Some(payl.parse().map_err(|e: Error<Infallible>| e.cast())?),
         };
 
-        Ok(Self {
-            payload,
-            signature: Signature {
-                protected: Some(prot.parse()?),
-                header: None,
-                signature: sign.parse().map_err(|e: Error<Infallible>| e.cast())?,
-            },
-        })
+        let payload = payload.map(P::try_from).transpose();
+        let payload = payload.map_err(|e| Error::Inner(serde::de::Error::custom(e)))?;
+        Ok(Self { payload, signature })
     }
 }
 
-impl Display for Flattened {
+/// The compact serialization of a signature over a payload.
+struct Compact<'a>(Option<&'a [u8]>, &'a Signature);
+
+impl Display for Compact<'_> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        let mut prot = alloc::string::String::new();
-        if let Some(x) = self.signature.protected.as_ref() {
+        let Self(payload, signature) = self;
+
+        let mut prot = String::new();
+        if let Some(x) = signature.protected.as_ref() {
             prot = Base64UrlUnpadded::encode_string(x.as_ref());
         }
 
-        let mut payl = alloc::string::String::new();
-        if let Some(x) = self.payload.as_ref() {
+        let mut payl = String::new();
+        if let Some(x) = payload.filter(|_| signature.b64()) {
             payl = Base64UrlUnpadded::encode_string(x);
         }
 
-        let sign = Base64UrlUnpadded::encode_string(&self.signature.signature);
+        let sign = Base64UrlUnpadded::encode_string(&signature.signature);
         write!(f, "{prot}.{payl}.{sign}")
     }
 }
+
+/// Formats the compact serialization.
+///
+/// If the payload is unencoded (RFC 7797), the detached form is produced: the
+/// payload is left out of the output and must be conveyed separately. Use
+/// [`Flattened::to_compact`] to have this fail instead.
+impl<P: AsRef<[u8]>> Display for Flattened<P> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let payload = self.payload.as_ref().map(AsRef::as_ref);
+        Compact(payload, &self.signature).fmt(f)
+    }
+}
+
+/// An error producing the compact serialization.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CompactError {
+    /// There is not exactly one signature.
+    Signatures(usize),
+
+    /// The signature has an unprotected header, which cannot be represented.
+    Unprotected,
+
+    /// The signature has no protected header.
+    Protected,
+
+    /// The payload is unencoded (RFC 7797) and would be left out.
+    Unencoded,
+}
+
+impl Display for CompactError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Signatures(n) => write!(f, "expected exactly one signature, found {n}"),
+            Self::Unprotected => f.write_str("the unprotected header cannot be represented"),
+            Self::Protected => f.write_str("the protected header is missing"),
+            Self::Unencoded => f.write_str("the unencoded payload would be detached"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CompactError {}
+
+impl Signature {
+    fn compact(&self, payload: Option<&[u8]>) -> Result<(), CompactError> {
+        if self.header.is_some() {
+            return Err(CompactError::Unprotected);
+        }
+
+        if self.protected.is_none() {
+            return Err(CompactError::Protected);
+        }
+
+        if payload.is_some() && !self.b64() {
+            return Err(CompactError::Unencoded);
+        }
+
+        Ok(())
+    }
+}
+
+impl<P: AsRef<[u8]>> Flattened<P> {
+    /// Returns the compact serialization.
+    ///
+    /// Unlike [`Display`], this fails if the signature cannot be represented
+    /// without loss, including when an unencoded payload would be detached.
+    pub fn to_compact(&self) -> Result<String, CompactError> {
+        let payload = self.payload.as_ref().map(AsRef::as_ref);
+        self.signature.compact(payload)?;
+        Ok(self.to_string())
+    }
+}
+
+impl<P: AsRef<[u8]>> General<P> {
+    /// Returns the compact serialization.
+    ///
+    /// This fails unless there is exactly one signature that can be
+    /// represented without loss.
+    pub fn to_compact(&self) -> Result<String, CompactError> {
+        let signature = match &self.signatures[..] {
+            [signature] => signature,
+            all => return Err(CompactError::Signatures(all.len())),
+        };
+
+        let payload = self.payload.as_ref().map(AsRef::as_ref);
+        signature.compact(payload)?;
+        Ok(Compact(payload, signature).to_string())
+    }
+}
+
+impl<P: AsRef<[u8]>> Jws<P> {
+    /// Returns the compact serialization.
+    ///
+    /// See [`General::to_compact`].
+    pub fn to_compact(&self) -> Result<String, CompactError> {
+        match self {
+            Self::General(general) => general.to_compact(),
+            Self::Flattened(flattened) => flattened.to_compact(),
+        }
+    }
+}
@@ -0,0 +1,426 @@
+// SPDX-FileCopyrightText: 2022 Profian Inc. <opensource@profian.com>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Signing and verification of ACME requests (RFC 8555 Section 6.2).
+//!
+//! An ACME request is a flattened JWS with only a protected header. The
+//! header carries the request `url`, a `nonce` issued by the server and
+//! exactly one of `jwk`, the account key itself, or `kid`, the account URL.
+//! Unsecured and MAC-based algorithms are not allowed.
+//!
+//! The signing functions take the private account key as a [`Jwk`] and sign
+//! with the algorithm of its `alg` parameter. Without one, the algorithm is
+//! inferred from the key: ES256, ES384 or ES512 for the NIST curves, ES256K
+//! for secp256k1, EdDSA for Ed25519, RS256 for RSA and HS256 for the MAC key
+//! of an external account binding. Only the public part of a key is ever
+//! embedded in a request.
+
+#![cfg(all(
+    feature = "url",
+    any(
+        feature = "ed25519",
+        feature = "hmac",
+        feature = "k256",
+        feature = "p256",
+        feature = "p384",
+        feature = "p521",
+        feature = "rsa"
+    )
+))]
+
+use alloc::string::ToString;
+
+use jose_b64::serde::Bytes;
+use jose_b64::stream::Update;
+use jose_jwa::{Algorithm, Signing};
+use jose_jwk::{crypto::Key, Ec, EcCurves, Jwk, Okp, OkpCurves, Rsa};
+use rand_core::CryptoRngCore;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use super::quorum::same;
+use super::{Error, Policy, Signer, SigningKey, Verified};
+use crate::{Flattened, Protected, Signature, Unprotected};
+
+/// The algorithms allowed for signing requests.
+const ALGORITHMS: [Signing; 11] = [
+    Signing::EdDsa,
+    Signing::Es256,
+    Signing::Es256K,
+    Signing::Es384,
+    Signing::Es512,
+    Signing::Ps256,
+    Signing::Ps384,
+    Signing::Ps512,
+    Signing::Rs256,
+    Signing::Rs384,
+    Signing::Rs512,
+];
+
+/// The algorithms allowed for external account bindings.
+const MACS: [Signing; 3] = [Signing::Hs256, Signing::Hs384, Signing::Hs512];
+
+/// A rule of RFC 8555 that a request breaks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Violation {
+    /// The header does not contain exactly one of `jwk` and `kid`, or not the
+    /// one the request requires.
+    Identity,
+
+    /// The `jwk` header parameter contains private key material.
+    Private,
+
+    /// The `nonce` header parameter is missing, or present where it is not
+    /// allowed.
+    Nonce,
+
+    /// The `url` header parameter is missing or does not match the request.
+    Url,
+
+    /// The JWS has an unprotected header.
+    Unprotected,
+
+    /// The payload is unencoded (RFC 7797), which ACME forbids.
+    Unencoded,
+
+    /// The payload is missing, malformed or does not match the request.
+    Payload,
+}
+
+impl From<Violation> for Error {
+    fn from(value: Violation) -> Self {
+        Self::Acme(value)
+    }
+}
+
+/// The account key of a request, as identified by its header.
+#[derive(Clone, Copy, Debug)]
+pub enum Account<'a> {
+    /// The account key, embedded as the `jwk` header parameter.
+    Jwk(&'a Jwk),
+
+    /// The account URL, as the `kid` header parameter.
+    Kid(&'a str),
+}
+
+/// The payload of the inner JWS of a key change (RFC 8555 Section 7.3.5).
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct KeyChange {
+    account: Url,
+    old_key: Jwk,
+}
+
+/// Returns the public part of the key, without its parameters.
+fn public(jwk: &Jwk) -> Result<Jwk, Error> {
+    let key = match &jwk.key {
+        jose_jwk::Key::Ec(ec) => Ec {
+            d: None,
+            ..ec.clone()
+        }
+        .into(),
+
+        jose_jwk::Key::Rsa(rsa) => Rsa {
+            prv: None,
+            ..rsa.clone()
+        }
+        .into(),
+
+        jose_jwk::Key::Okp(okp) => Okp {
+            d: None,
+            ..okp.clone()
+        }
+        .into(),
+
+        _ => return Err(jose_jwk::crypto::Error::Unsupported.into()),
+    };
+
+    Ok(Jwk {
+        key,
+        prm: Default::default(),
+    })
+}
+
+/// Returns whether the key contains only public key material.
+fn is_public(jwk: &Jwk) -> bool {
+    match &jwk.key {
+        jose_jwk::Key::Ec(ec) => ec.d.is_none(),
+        jose_jwk::Key::Rsa(rsa) => rsa.prv.is_none(),
+        jose_jwk::Key::Okp(okp) => okp.d.is_none(),
+        _ => false,
+    }
+}
+
+/// Returns the algorithm of the key if it is one of the allowed algorithms.
+/// Without an `alg` parameter, it is inferred from the type of the key.
+fn algorithm(jwk: &Jwk, algs: &[Signing]) -> Result<Signing, Error> {
+    let alg = match (&jwk.prm.alg, &jwk.key) {
+        (Some(Algorithm::Signing(alg)), _) => *alg,
+        (Some(..), _) => return Err(Error::Disallowed),
+
+        (None, jose_jwk::Key::Ec(ec)) => match ec.crv {
+            EcCurves::P256 => Signing::Es256,
+            EcCurves::P256K => Signing::Es256K,
+            EcCurves::P384 => Signing::Es384,
+            EcCurves::P521 => Signing::Es512,
+            #[allow(unreachable_patterns)]
+            _ => return Err(jose_jwk::crypto::Error::Unsupported.into()),
+        },
+
+        (None, jose_jwk::Key::Okp(okp)) => match okp.crv {
+            OkpCurves::Ed25519 => Signing::EdDsa,
+            _ => return Err(jose_jwk::crypto::Error::Unsupported.into()),
+        },
+
+        (None, jose_jwk::Key::Rsa(..)) => Signing::Rs256,
+        (None, jose_jwk::Key::Oct(..)) => Signing::Hs256,
+
+        #[allow(unreachable_patterns)]
+        (None, _) => return Err(jose_jwk::crypto::Error::Unsupported.into()),
+    };
+
+    match algs.contains(&alg) {
+        true => Ok(alg),
+        false => Err(Error::Disallowed),
+    }
+}
+
+/// Signs the payload with the key.
+fn sign_with(
+    jwk: &Jwk,
+    prot: Protected,
+    payload: &[u8],
+    rng: &mut impl CryptoRngCore,
+) -> Result<Flattened, Error> {
+    let key = Key::try_from(&jwk.key)?;
+    let mut signer = key.sign(Some(prot), None)?;
+    signer.update(payload)?;
+
+    Ok(Flattened {
+        payload: Some(payload.to_vec().into()),
+        signature: signer.finish(rng)?,
+    })
+}
+
+/// Signs an ACME request.
+///
+/// If specified, `kid` is the account URL. Otherwise, the public part of the
+/// account key is embedded as the `jwk` header parameter, as required for
+/// newAccount requests and for revocations signed with the certificate key.
+pub fn sign(
+    jwk: &Jwk,
+    kid: Option<&Url>,
+    url: Url,
+    nonce: Bytes,
+    payload: &[u8],
+    rng: &mut impl CryptoRngCore,
+) -> Result<Flattened, Error> {
+    let prot = Protected {
+        url: Some(url),
+        nonce: Some(nonce),
+        oth: Unprotected {
+            alg: Some(algorithm(jwk, &ALGORITHMS)?),
+            jwk: kid.is_none().then(|| public(jwk)).transpose()?,
+            kid: kid.map(ToString::to_string),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    sign_with(jwk, prot, payload, rng)
+}
+
+/// Signs an ACME POST-as-GET request (RFC 8555 Section 6.3).
+///
+/// The payload of the request is empty.
+pub fn post_as_get(
+    jwk: &Jwk,
+    kid: &Url,
+    url: Url,
+    nonce: Bytes,
+    rng: &mut impl CryptoRngCore,
+) -> Result<Flattened, Error> {
+    sign(jwk, Some(kid), url, nonce, b"", rng)
+}
+
+/// Creates an external account binding (RFC 8555 Section 7.3.4).
+///
+/// The binding signs the public part of the account key with the MAC key
+/// provided by the CA, whose `kid` parameter must be the key identifier
+/// provided by the CA. The `url` is the newAccount URL. The binding is sent
+/// as the `externalAccountBinding` member of the newAccount payload.
+pub fn bind(
+    mac: &Jwk,
+    jwk: &Jwk,
+    url: Url,
+    rng: &mut impl CryptoRngCore,
+) -> Result<Flattened, Error> {
+    let prot = Protected {
+        url: Some(url),
+        oth: Unprotected {
+            alg: Some(algorithm(mac, &MACS)?),
+            kid: Some(mac.prm.kid.clone().ok_or(Violation::Identity)?),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let payload = serde_json::to_vec(&public(jwk)?)?;
+    sign_with(mac, prot, &payload, rng)
+}
+
+/// Creates a key change request (RFC 8555 Section 7.3.5).
+///
+/// The inner JWS is signed with the new key and becomes the payload of the
+/// outer JWS, which is signed with the old key for the account `kid`. The
+/// `url` is the keyChange URL.
+pub fn key_change(
+    old: &Jwk,
+    new: &Jwk,
+    kid: &Url,
+    url: Url,
+    nonce: Bytes,
+    rng: &mut impl CryptoRngCore,
+) -> Result<Flattened, Error> {
+    let prot = Protected {
+        url: Some(url.clone()),
+        oth: Unprotected {
+            alg: Some(algorithm(new, &ALGORITHMS)?),
+            jwk: Some(public(new)?),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let payload = serde_json::to_vec(&KeyChange {
+        account: kid.clone(),
+        old_key: public(old)?,
+    })?;
+
+    let inner = sign_with(new, prot, &payload, rng)?;
+    let inner = serde_json::to_vec(&inner)?;
+    sign(old, Some(kid), url, nonce, &inner, rng)
+}
+
+/// Checks the header of a signature of a request received at `url`.
+fn check<'a>(sig: &'a Signature, url: &Url, nonce: bool) -> Result<&'a Protected, Violation> {
+    if sig.header.is_some() {
+        return Err(Violation::Unprotected);
+    }
+
+    let prot = sig.protected.as_deref().ok_or(Violation::Url)?;
+
+    if prot.nonce.is_some() != nonce {
+        return Err(Violation::Nonce);
+    }
+
+    if prot.url.as_ref() != Some(url) {
+        return Err(Violation::Url);
+    }
+
+    // The unencoded payload option MUST NOT be used (RFC 8555 Section 6.2).
+    if !prot.b64 {
+        return Err(Violation::Unencoded);
+    }
+
+    Ok(prot)
+}
+
+/// Identifies the account key from the header.
+fn identify(prot: &Protected) -> Result<Account<'_>, Violation> {
+    match (&prot.oth.jwk, &prot.oth.kid) {
+        (Some(jwk), None) if is_public(jwk) => Ok(Account::Jwk(jwk)),
+        (Some(..), None) => Err(Violation::Private),
+        (None, Some(kid)) => Ok(Account::Kid(kid)),
+        _ => Err(Violation::Identity),
+    }
+}
+
+/// Checks the header of an ACME request received at `url` and identifies its
+/// account key.
+///
+/// The signature is not verified. The server must look up the account key by
+/// the account URL, or use the embedded key, and pass it to [`verify`].
+pub fn account<'a, P>(jws: &'a Flattened<P>, url: &Url) -> Result<Account<'a>, Error> {
+    let prot = check(&jws.signature, url, true)?;
+    Ok(identify(prot)?)
+}
+
+/// Verifies an ACME request received at `url` with the account key.
+///
+/// The header must pass the checks of [`account`] and the signature must use
+/// an asymmetric algorithm. Checking the nonce against those issued is left
+/// to the server; it is part of the returned header.
+pub fn verify<'a, P: AsRef<[u8]>>(
+    jws: &'a Flattened<P>,
+    key: &Jwk,
+    url: &Url,
+) -> Result<Verified<'a, P>, Error> {
+    account(jws, url)?;
+
+    if jws.payload.is_none() {
+        return Err(Violation::Payload.into());
+    }
+
+    jws.verify_with(key, &Policy::new(ALGORITHMS))
+}
+
+/// Verifies an external account binding (RFC 8555 Section 7.3.4).
+///
+/// The `jwk` is the account key of the newAccount request received at `url`.
+/// The server looks up the MAC key by the `kid` header parameter of the
+/// binding.
+pub fn verify_binding<P: AsRef<[u8]>>(
+    binding: &Flattened<P>,
+    mac: &Jwk,
+    jwk: &Jwk,
+    url: &Url,
+) -> Result<(), Error> {
+    let prot = check(&binding.signature, url, false)?;
+    if !matches!(identify(prot), Ok(Account::Kid(..))) {
+        return Err(Violation::Identity.into());
+    }
+
+    let payload = binding.payload.as_ref().ok_or(Violation::Payload)?;
+    let bound: Jwk = serde_json::from_slice(payload.as_ref()).map_err(|_| Violation::Payload)?;
+    if !same(&bound.key, &jwk.key) {
+        return Err(Violation::Payload.into());
+    }
+
+    binding.verify_with(mac, &Policy::new(MACS))?;
+    Ok(())
+}
+
+/// Verifies a key change request (RFC 8555 Section 7.3.5).
+///
+/// The `old` key is the current key of the account and `url` is the keyChange
+/// URL. On success, the new key is returned. The server must still check that
+/// no other account uses it.
+pub fn verify_key_change<P: AsRef<[u8]>>(
+    jws: &Flattened<P>,
+    old: &Jwk,
+    url: &Url,
+) -> Result<Jwk, Error> {
+    let Account::Kid(kid) = account(jws, url)? else {
+        return Err(Violation::Identity.into());
+    };
+
+    let outer = verify(jws, old, url)?;
+    let inner: Flattened =
+        serde_json::from_slice(outer.payload()).map_err(|_| Violation::Payload)?;
+
+    let prot = check(&inner.signature, url, false)?;
+    let Account::Jwk(new) = identify(prot)? else {
+        return Err(Violation::Identity.into());
+    };
+
+    let payload = inner.payload.as_deref().ok_or(Violation::Payload)?;
+    let change: KeyChange = serde_json::from_slice(payload).map_err(|_| Violation::Payload)?;
+    if change.account.as_str() != kid || !same(&change.old_key.key, &old.key) {
+        return Err(Violation::Payload.into());
+    }
+
+    inner.verify_with(new, &Policy::new(ALGORITHMS))?;
+    Ok(new.clone())
+}
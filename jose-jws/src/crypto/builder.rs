@@ -0,0 +1,237 @@
+// SPDX-FileCopyrightText: 2022 Profian Inc. <opensource@profian.com>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+#![cfg(any(
+    feature = "ed25519",
+    feature = "hmac",
+    feature = "k256",
+    feature = "p256",
+    feature = "p384",
+    feature = "p521",
+    feature = "rsa"
+))]
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use jose_b64::serde::Bytes;
+use jose_b64::stream::Update;
+use jose_jwa::Algorithm;
+use jose_jwk::{crypto::Key, Jwk, Operations};
+use rand_core::CryptoRngCore;
+
+use super::{Error, Signer, SigningKey};
+use crate::{Flattened, General, Jws, Protected, Signature, Unprotected};
+
+#[allow(clippy::large_enum_variant)]
+enum Source<'a> {
+    Key(&'a Key),
+    Jwk(Key, &'a Jwk),
+}
+
+struct Entry<'a> {
+    key: Source<'a>,
+    prot: Option<Protected>,
+    head: Option<Unprotected>,
+}
+
+impl Entry<'_> {
+    fn sign(self, payload: &[u8], rng: &mut impl CryptoRngCore) -> Result<Signature, Error> {
+        let (key, jwk) = match &self.key {
+            Source::Key(key) => (*key, None),
+            Source::Jwk(key, jwk) => (key, Some(*jwk)),
+        };
+
+        let mut prot = self.prot;
+        let head = self.head;
+
+        if let Some(jwk) = jwk {
+            let prm = &jwk.prm;
+            let alg = prot.as_ref().and_then(|p| p.oth.alg);
+            let alg = alg.or_else(|| head.as_ref().and_then(|h| h.alg));
+            let alg = match (alg, prm.alg.as_ref()) {
+                (Some(alg), Some(pinned)) if Algorithm::Signing(alg) != *pinned => {
+                    return Err(Error::AlgMismatch);
+                }
+
+                (None, Some(Algorithm::Signing(alg))) => {
+                    prot.get_or_insert_with(Default::default).oth.alg = Some(*alg);
+                    Some(*alg)
+                }
+
+                (alg, _) => alg,
+            };
+
+            if let Some(alg) = alg {
+                if !jwk.allows(&alg.into(), Operations::Sign) {
+                    return Err(Error::NoKey);
+                }
+            }
+
+            let kid = prot.as_ref().and_then(|p| p.oth.kid.as_ref());
+            let kid = kid.or_else(|| head.as_ref().and_then(|h| h.kid.as_ref()));
+            if kid.is_none() {
+                if let Some(kid) = prm.kid.as_ref() {
+                    prot.get_or_insert_with(Default::default).oth.kid = Some(kid.to_string());
+                }
+            }
+        }
+
+        let mut signer = key.sign(prot, head)?;
+        signer.update(payload)?;
+        signer.finish(rng)
+    }
+}
+
+/// A builder for signing a payload with one or more keys.
+///
+/// If a signer is added from a [`Jwk`], the `alg` and `kid` parameters of the
+/// JWK are copied into the protected header unless either header already
+/// specifies them. A header `alg` that differs from the `alg` of the JWK is
+/// rejected, as is a key whose `use` or `key_ops` parameters do not permit
+/// signing.
+///
+/// ```
+/// # use jose_jwk::Jwk;
+/// # use jose_jws::crypto::Builder;
+/// # use rand_core::OsRng;
+/// let jwk: Jwk = serde_json::from_value(serde_json::json!({
+///     "kty": "oct",
+///     "alg": "HS256",
+///     "kid": "secret",
+///     "k": "AyM1SysPpbyDfgZld3umj1qzKObwVMkoqQ-EstJQLr_T-1qS0gZH75aKtMN3Yj0iPS4hcgUuTwjAzZr1Z9CAow",
+/// }))
+/// .unwrap();
+///
+/// let compact = Builder::new(b"Hello world!")
+///     .jwk(&jwk, None, None)
+///     .unwrap()
+///     .compact(&mut OsRng)
+///     .unwrap();
+///
+/// assert!(compact.starts_with("eyJhbGciOiJIUzI1NiIsImtpZCI6InNlY3JldCJ9."));
+/// ```
+pub struct Builder<'a> {
+    payload: &'a [u8],
+    entries: Vec<Entry<'a>>,
+    detached: bool,
+}
+
+impl<'a> Builder<'a> {
+    /// Creates a new builder for the specified payload.
+    pub fn new(payload: &'a [u8]) -> Self {
+        Self {
+            payload,
+            entries: Vec::new(),
+            detached: false,
+        }
+    }
+
+    /// Omits the payload from the output (RFC 7515 Appendix F).
+    ///
+    /// The payload is still signed, but must be conveyed separately. In the
+    /// compact serialization, this produces `header..signature`.
+    pub fn detach(mut self) -> Self {
+        self.detached = true;
+        self
+    }
+
+    fn payload(&self) -> Option<Bytes> {
+        match self.detached {
+            true => None,
+            false => Some(self.payload.to_vec().into()),
+        }
+    }
+
+    /// Adds a signature using the specified key and headers.
+    pub fn key(mut self, key: &'a Key, prot: Option<Protected>, head: Option<Unprotected>) -> Self {
+        self.entries.push(Entry {
+            key: Source::Key(key),
+            prot,
+            head,
+        });
+
+        self
+    }
+
+    /// Adds a signature using the specified JWK and headers.
+    pub fn jwk(
+        mut self,
+        jwk: &'a Jwk,
+        prot: Option<Protected>,
+        head: Option<Unprotected>,
+    ) -> Result<Self, Error> {
+        self.entries.push(Entry {
+            key: Source::Jwk(Key::try_from(&jwk.key)?, jwk),
+            prot,
+            head,
+        });
+
+        Ok(self)
+    }
+
+    fn signatures(self, rng: &mut impl CryptoRngCore) -> Result<Vec<Signature>, Error> {
+        let payload = self.payload;
+
+        self.entries
+            .into_iter()
+            .map(|entry| entry.sign(payload, rng))
+            .collect()
+    }
+
+    /// Signs the payload, producing the general serialization.
+    ///
+    /// This fails if the signers disagree on the `b64` header parameter.
+    pub fn general(self, rng: &mut impl CryptoRngCore) -> Result<General, Error> {
+        if self.entries.is_empty() {
+            return Err(Error::Serialization);
+        }
+
+        let b64 = |e: &Entry<'_>| e.prot.as_ref().map_or(true, |p| p.b64);
+        if self.entries.iter().any(|e| b64(e) != b64(&self.entries[0])) {
+            return Err(Error::MixedB64);
+        }
+
+        let payload = self.payload();
+        let signatures = self.signatures(rng)?;
+        Ok(General {
+            payload,
+            signatures,
+        })
+    }
+
+    /// Signs the payload, producing the flattened serialization.
+    ///
+    /// This fails unless exactly one signature has been added.
+    pub fn flattened(self, rng: &mut impl CryptoRngCore) -> Result<Flattened, Error> {
+        if self.entries.len() != 1 {
+            return Err(Error::Serialization);
+        }
+
+        let payload = self.payload();
+        let signature = self.signatures(rng)?.remove(0);
+        Ok(Flattened { payload, signature })
+    }
+
+    /// Signs the payload, producing the compact serialization.
+    ///
+    /// This fails unless exactly one signature has been added without an
+    /// unprotected header, since the compact serialization cannot carry one.
+    /// If the payload is unencoded, the detached form is produced.
+    pub fn compact(self, rng: &mut impl CryptoRngCore) -> Result<String, Error> {
+        if self.entries.iter().any(|e| e.head.is_some()) {
+            return Err(Error::Serialization);
+        }
+
+        Ok(self.flattened(rng)?.to_string())
+    }
+
+    /// Signs the payload, producing the flattened serialization for a single
+    /// signature and the general serialization otherwise.
+    pub fn jws(self, rng: &mut impl CryptoRngCore) -> Result<Jws, Error> {
+        match self.entries.len() {
+            1 => self.flattened(rng).map(Jws::Flattened),
+            _ => self.general(rng).map(Jws::General),
+        }
+    }
+}
@@ -5,12 +5,12 @@ use alloc::vec::Vec;
 
 use jose_b64::stream::Update;
 use jose_jwa::Signing;
-use rand_core::RngCore;
+use rand_core::CryptoRngCore;
 
 pub trait CoreSigner: Update {
     type FinishError: From<Self::Error>;
 
-    fn finish(self, rng: impl 'static + RngCore) -> Result<Vec<u8>, Self::FinishError>;
+    fn finish(self, rng: impl CryptoRngCore) -> Result<Vec<u8>, Self::FinishError>;
 }
 
 pub trait CoreSigningKey<'a> {
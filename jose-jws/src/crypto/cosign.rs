@@ -0,0 +1,115 @@
+// SPDX-FileCopyrightText: 2022 Profian Inc. <opensource@profian.com>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use jose_b64::stream::Update;
+use rand_core::CryptoRngCore;
+
+use super::{attached, Error, Signer, SigningKey};
+use crate::{Flattened, General, Protected, Unprotected};
+
+/// A signature creation state for a detached payload.
+///
+/// The payload is supplied in chunks with [`Update::update`] and is left out
+/// of the result (RFC 7515 Appendix F). EdDSA keys buffer the payload while
+/// signing. This is obtained from
+/// [`Flattened::sign_detached`].
+pub struct DetachedSigner<S> {
+    signer: S,
+}
+
+impl<S: Update> Update for DetachedSigner<S>
+where
+    Error: From<S::Error>,
+{
+    type Error = Error;
+
+    fn update(&mut self, chunk: impl AsRef<[u8]>) -> Result<(), Self::Error> {
+        Ok(self.signer.update(chunk)?)
+    }
+}
+
+impl<S: Signer<FinishError = Error>> DetachedSigner<S> {
+    /// Finishes signing, producing a JWS without its payload.
+    pub fn finish(self, rng: impl CryptoRngCore) -> Result<Flattened, Error> {
+        Ok(Flattened {
+            payload: None,
+            signature: self.signer.finish(rng)?,
+        })
+    }
+}
+
+impl Flattened {
+    /// Begins signing a detached payload, which is then supplied in chunks.
+    ///
+    /// The result is verified with [`Flattened::verify_detached`] or
+    /// [`Flattened::detached`].
+    pub fn sign_detached<'a, K>(
+        key: &'a K,
+        prot: Option<Protected>,
+        head: Option<Unprotected>,
+    ) -> Result<DetachedSigner<K::Signer>, Error>
+    where
+        K: SigningKey<'a, StartError = Error>,
+    {
+        Ok(DetachedSigner {
+            signer: key.sign(prot, head)?,
+        })
+    }
+}
+
+impl<P: AsRef<[u8]>> General<P> {
+    /// Adds a signature over the existing payload.
+    ///
+    /// The payload is signed as it is, so a second party can countersign
+    /// without re-serializing it. The new signature must agree with the
+    /// existing signatures on the `b64` header parameter (RFC 7797 Section 3).
+    /// A detached payload cannot be countersigned this way and fails with
+    /// [`Error::Detached`].
+    pub fn cosign<'a, K>(
+        &mut self,
+        key: &'a K,
+        prot: Option<Protected>,
+        head: Option<Unprotected>,
+        rng: &mut impl CryptoRngCore,
+    ) -> Result<(), Error>
+    where
+        K: SigningKey<'a, StartError = Error>,
+        K::Signer: Signer<FinishError = Error>,
+        Error: From<<K::Signer as Update>::Error>,
+    {
+        let b64 = prot.as_ref().map_or(true, |p| p.b64);
+        if !self.signatures.is_empty() && self.b64() != Some(b64) {
+            return Err(Error::MixedB64);
+        }
+
+        let payload = attached(self.payload.as_ref())?;
+        let mut signer = key.sign(prot, head)?;
+        signer.update(payload.as_ref())?;
+        self.signatures.push(signer.finish(rng)?);
+        Ok(())
+    }
+}
+
+impl<P: AsRef<[u8]> + Clone> Flattened<P> {
+    /// Adds a signature over the existing payload, producing the general
+    /// serialization.
+    ///
+    /// The JWS is left as it is, so that it is still available if this
+    /// fails. See [`General::cosign`].
+    pub fn cosign<'a, K>(
+        &self,
+        key: &'a K,
+        prot: Option<Protected>,
+        head: Option<Unprotected>,
+        rng: &mut impl CryptoRngCore,
+    ) -> Result<General<P>, Error>
+    where
+        K: SigningKey<'a, StartError = Error>,
+        K::Signer: Signer<FinishError = Error>,
+        Error: From<<K::Signer as Update>::Error>,
+    {
+        let mut general = General::from(self.clone());
+        general.cosign(key, prot, head, rng)?;
+        Ok(general)
+    }
+}
@@ -0,0 +1,115 @@
+// SPDX-FileCopyrightText: 2022 Profian Inc. <opensource@profian.com>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+#![cfg(feature = "ed25519")]
+
+use alloc::vec::Vec;
+use core::convert::Infallible;
+
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+use jose_b64::stream::Update;
+use jose_jwa::{Signing, Signing::EdDsa};
+use jose_jwk::crypto;
+use rand_core::CryptoRngCore;
+
+use super::core::{CoreSigner, CoreSigningKey, CoreVerifier, CoreVerifyingKey};
+use super::Error;
+
+/// An Ed25519 (RFC 8037 Section 3.1) signing state.
+///
+/// Ed25519 hashes the message twice, so the signing input is buffered.
+pub struct Ed25519Signer {
+    key: SigningKey,
+    msg: Vec<u8>,
+}
+
+impl Update for Ed25519Signer {
+    type Error = Infallible;
+
+    fn update(&mut self, chunk: impl AsRef<[u8]>) -> Result<(), Self::Error> {
+        self.msg.extend_from_slice(chunk.as_ref());
+        Ok(())
+    }
+}
+
+impl CoreSigner for Ed25519Signer {
+    type FinishError = Error;
+
+    fn finish(self, _: impl CryptoRngCore) -> Result<Vec<u8>, Self::FinishError> {
+        Ok(Signer::sign(&self.key, &self.msg).to_bytes().to_vec())
+    }
+}
+
+/// An Ed25519 (RFC 8037 Section 3.1) verification state.
+pub struct Ed25519Verifier {
+    key: VerifyingKey,
+    msg: Vec<u8>,
+}
+
+impl Update for Ed25519Verifier {
+    type Error = Infallible;
+
+    fn update(&mut self, chunk: impl AsRef<[u8]>) -> Result<(), Self::Error> {
+        self.msg.extend_from_slice(chunk.as_ref());
+        Ok(())
+    }
+}
+
+impl Ed25519Verifier {
+    fn new(key: VerifyingKey, alg: Signing) -> Result<Self, Error> {
+        match alg {
+            EdDsa => Ok(Self {
+                key,
+                msg: Vec::new(),
+            }),
+
+            _ => Err(crypto::Error::AlgMismatch.into()),
+        }
+    }
+}
+
+impl CoreVerifier for Ed25519Verifier {
+    type FinishError = Error;
+
+    fn finish(self, signature: &[u8]) -> Result<(), Self::FinishError> {
+        let signature = Signature::from_slice(signature).map_err(|_| Error::Invalid)?;
+
+        self.key
+            .verify_strict(&self.msg, &signature)
+            .map_err(|_| Error::Invalid)
+    }
+}
+
+impl<'a> CoreSigningKey<'a> for SigningKey {
+    type StartError = Error;
+    type Finish = Ed25519Signer;
+
+    fn sign(&'a self, alg: Signing) -> Result<Self::Finish, Self::StartError> {
+        match alg {
+            EdDsa => Ok(Ed25519Signer {
+                key: self.clone(),
+                msg: Vec::new(),
+            }),
+
+            _ => Err(crypto::Error::AlgMismatch.into()),
+        }
+    }
+}
+
+impl<'a> CoreVerifyingKey<'a> for SigningKey {
+    type StartError = Error;
+    type Finish = Ed25519Verifier;
+
+    fn verify(&'a self, alg: Signing) -> Result<Self::Finish, Self::StartError> {
+        Ed25519Verifier::new(self.verifying_key(), alg)
+    }
+}
+
+impl<'a> CoreVerifyingKey<'a> for VerifyingKey {
+    type StartError = Error;
+    type Finish = Ed25519Verifier;
+
+    fn verify(&'a self, alg: Signing) -> Result<Self::Finish, Self::StartError> {
+        Ed25519Verifier::new(*self, alg)
+    }
+}
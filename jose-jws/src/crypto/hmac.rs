@@ -0,0 +1,95 @@
+// SPDX-FileCopyrightText: 2022 Profian Inc. <opensource@profian.com>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+#![cfg(feature = "hmac")]
+
+use alloc::vec::Vec;
+use core::convert::Infallible;
+
+use hmac::{Hmac as Inner, Mac};
+use jose_b64::stream::Update;
+use jose_jwa::{Signing, Signing::*};
+use jose_jwk::{crypto, Oct};
+use rand_core::CryptoRngCore;
+use sha2::{Sha256, Sha384, Sha512};
+
+use super::core::{CoreSigner, CoreSigningKey, CoreVerifier, CoreVerifyingKey};
+use super::Error;
+
+/// An HMAC (RFC 7518 Section 3.2) signing and verification state.
+pub enum Hmac {
+    Hs256(Inner<Sha256>),
+    Hs384(Inner<Sha384>),
+    Hs512(Inner<Sha512>),
+}
+
+impl Hmac {
+    pub fn new(key: &[u8], alg: Signing) -> Result<Self, Error> {
+        let inv = |_| crypto::Error::Invalid;
+
+        Ok(match alg {
+            Hs256 => Self::Hs256(Inner::new_from_slice(key).map_err(inv)?),
+            Hs384 => Self::Hs384(Inner::new_from_slice(key).map_err(inv)?),
+            Hs512 => Self::Hs512(Inner::new_from_slice(key).map_err(inv)?),
+            _ => return Err(crypto::Error::AlgMismatch.into()),
+        })
+    }
+}
+
+impl Update for Hmac {
+    type Error = Infallible;
+
+    fn update(&mut self, chunk: impl AsRef<[u8]>) -> Result<(), Self::Error> {
+        match self {
+            Self::Hs256(mac) => Mac::update(mac, chunk.as_ref()),
+            Self::Hs384(mac) => Mac::update(mac, chunk.as_ref()),
+            Self::Hs512(mac) => Mac::update(mac, chunk.as_ref()),
+        }
+
+        Ok(())
+    }
+}
+
+impl CoreSigner for Hmac {
+    type FinishError = Error;
+
+    fn finish(self, _: impl CryptoRngCore) -> Result<Vec<u8>, Self::FinishError> {
+        Ok(match self {
+            Self::Hs256(mac) => mac.finalize().into_bytes().to_vec(),
+            Self::Hs384(mac) => mac.finalize().into_bytes().to_vec(),
+            Self::Hs512(mac) => mac.finalize().into_bytes().to_vec(),
+        })
+    }
+}
+
+impl CoreVerifier for Hmac {
+    type FinishError = Error;
+
+    fn finish(self, signature: &[u8]) -> Result<(), Self::FinishError> {
+        // The tag comparison is constant time.
+        match self {
+            Self::Hs256(mac) => mac.verify_slice(signature),
+            Self::Hs384(mac) => mac.verify_slice(signature),
+            Self::Hs512(mac) => mac.verify_slice(signature),
+        }
+        .map_err(|_| Error::Invalid)
+    }
+}
+
+impl<'a> CoreSigningKey<'a> for Oct {
+    type StartError = Error;
+    type Finish = Hmac;
+
+    fn sign(&'a self, alg: Signing) -> Result<Self::Finish, Self::StartError> {
+        Hmac::new(&self.k, alg)
+    }
+}
+
+impl<'a> CoreVerifyingKey<'a> for Oct {
+    type StartError = Error;
+    type Finish = Hmac;
+
+    fn verify(&'a self, alg: Signing) -> Result<Self::Finish, Self::StartError> {
+        Hmac::new(&self.k, alg)
+    }
+}
@@ -0,0 +1,215 @@
+// SPDX-FileCopyrightText: 2022 Profian Inc. <opensource@profian.com>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Streaming signing and verification over [`std::io`].
+//!
+//! The payload is read from a [`Read`] in fixed-size chunks, so it is never
+//! held in memory as a whole. The writers emit the base64url-encoded payload
+//! as it is signed.
+//!
+//! EdDSA is the exception: Ed25519 hashes the signing input twice (RFC 8032
+//! Section 5.1.6), so the Ed25519 keys buffer the whole payload while
+//! signing or verifying. The payload is still read in chunks, but its size
+//! is then bounded by the available memory.
+
+#![cfg(feature = "std")]
+
+use std::io::{ErrorKind, Read, Write};
+
+use jose_b64::base64ct::{Base64UrlUnpadded, Encoding};
+use jose_b64::stream::{Encoder, Update};
+use rand_core::CryptoRngCore;
+
+use super::verify::start;
+use super::{Error, Policy, Signer, SigningKey, SigningState, Verifier, VerifyingKey};
+use crate::{Protected, Signature, Unprotected};
+
+const CHUNK: usize = 8192;
+
+/// Adapts a writer to receive the encoded payload.
+struct Output<W>(W);
+
+impl<W: Write> Update for Output<W> {
+    type Error = std::io::Error;
+
+    fn update(&mut self, chunk: impl AsRef<[u8]>) -> Result<(), Self::Error> {
+        self.0.write_all(chunk.as_ref())
+    }
+}
+
+/// Reads the payload to its end, passing each chunk to the callback.
+fn copy(
+    mut payload: impl Read,
+    mut f: impl FnMut(&[u8]) -> Result<(), Error>,
+) -> Result<(), Error> {
+    let mut buf = [0u8; CHUNK];
+
+    loop {
+        match payload.read(&mut buf) {
+            Ok(0) => return Ok(()),
+            Ok(n) => f(&buf[..n])?,
+            Err(e) if e.kind() == ErrorKind::Interrupted => (),
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+/// Signs the payload read from the reader.
+///
+/// The payload is not included in the returned signature.
+///
+/// With an EdDSA key, the payload is buffered in memory; see the
+/// [module documentation](self).
+pub fn sign<'a, K>(
+    key: &'a K,
+    prot: Option<Protected>,
+    head: Option<Unprotected>,
+    payload: impl Read,
+    rng: &mut impl CryptoRngCore,
+) -> Result<Signature, Error>
+where
+    K: SigningKey<'a, StartError = Error>,
+    K::Signer: Signer<FinishError = Error>,
+    Error: From<<K::Signer as Update>::Error>,
+{
+    let mut signer = key.sign(prot, head)?;
+    copy(payload, |chunk| Ok(signer.update(chunk)?))?;
+    signer.finish(rng)
+}
+
+/// Verifies the signature over the payload read from the reader.
+///
+/// On success, the header of the verified signature is returned.
+///
+/// With an EdDSA key, the payload is buffered in memory; see the
+/// [module documentation](self).
+pub fn verify<'a, K>(key: &'a K, sig: &'a Signature, payload: impl Read) -> Result<Protected, Error>
+where
+    K: VerifyingKey<'a, &'a Signature, StartError = Error>,
+    K::Verifier: Verifier<'a, FinishError = Error>,
+    Error: From<<K::Verifier as Update>::Error>,
+{
+    let mut det = start(key, None, sig)?;
+    copy(payload, |chunk| det.update(chunk))?;
+    det.finish()
+}
+
+/// Verifies the signature over the payload read from the reader with the
+/// specified policy.
+///
+/// With an EdDSA key, the payload is buffered in memory; see the
+/// [module documentation](self).
+pub fn verify_with<'a, K>(
+    key: &'a K,
+    policy: &Policy,
+    sig: &'a Signature,
+    payload: impl Read,
+) -> Result<Protected, Error>
+where
+    K: VerifyingKey<'a, &'a Signature, StartError = Error>,
+    K::Verifier: Verifier<'a, FinishError = Error>,
+    Error: From<<K::Verifier as Update>::Error>,
+{
+    let mut det = start(key, Some(policy), sig)?;
+    copy(payload, |chunk| det.update(chunk))?;
+    det.finish()
+}
+
+/// Signs the payload read from the reader, writing the compact serialization
+/// to the writer.
+///
+/// If the payload is unencoded (RFC 7797), the detached form is written.
+/// The protected header is written exactly as the key signs it, so only the
+/// built-in [`SigningState`] is accepted.
+///
+/// With an EdDSA key, the payload is buffered in memory; see the
+/// [module documentation](self).
+pub fn write_compact<'a, K, S>(
+    key: &'a K,
+    prot: Protected,
+    payload: impl Read,
+    mut out: impl Write,
+    rng: &mut impl CryptoRngCore,
+) -> Result<(), Error>
+where
+    K: SigningKey<'a, StartError = Error, Signer = SigningState<S>>,
+    SigningState<S>: Signer<FinishError = Error>,
+    Error: From<<SigningState<S> as Update>::Error>,
+{
+    let mut signer = key.sign(Some(prot), None)?;
+    let prot = signer.protected().ok_or(Error::Serialization)?;
+    let b64 = prot.b64;
+
+    out.write_all(Base64UrlUnpadded::encode_string(prot.as_ref()).as_bytes())?;
+    out.write_all(b".")?;
+
+    let mut enc: Encoder<_> = Output(&mut out).into();
+    copy(payload, |chunk| {
+        signer.update(chunk)?;
+        if b64 {
+            enc.update(chunk)?;
+        }
+
+        Ok(())
+    })?;
+    enc.finish()?;
+
+    let sig = signer.finish(rng)?;
+    out.write_all(b".")?;
+    out.write_all(Base64UrlUnpadded::encode_string(&sig.signature).as_bytes())?;
+    Ok(())
+}
+
+/// Signs the payload read from the reader, writing the flattened JSON
+/// serialization to the writer.
+///
+/// Unencoded payloads (RFC 7797) are not supported, since they would have to
+/// be escaped as a JSON string. As with [`write_compact`], the headers are
+/// written exactly as the key signs them.
+///
+/// With an EdDSA key, the payload is buffered in memory; see the
+/// [module documentation](self).
+pub fn write_flattened<'a, K, S>(
+    key: &'a K,
+    prot: Option<Protected>,
+    head: Option<Unprotected>,
+    payload: impl Read,
+    mut out: impl Write,
+    rng: &mut impl CryptoRngCore,
+) -> Result<(), Error>
+where
+    K: SigningKey<'a, StartError = Error, Signer = SigningState<S>>,
+    SigningState<S>: Signer<FinishError = Error>,
+    Error: From<<SigningState<S> as Update>::Error>,
+{
+    if prot.as_ref().map_or(false, |p| !p.b64) {
+        return Err(Error::Serialization);
+    }
+
+    let mut signer = key.sign(prot, head)?;
+    let unprotected = signer.header().map(serde_json::to_vec).transpose()?;
+
+    out.write_all(b"{")?;
+    if let Some(prot) = signer.protected() {
+        let header = Base64UrlUnpadded::encode_string(prot.as_ref());
+        write!(out, "\"protected\":\"{header}\",")?;
+    }
+
+    if let Some(unprotected) = unprotected {
+        out.write_all(b"\"header\":")?;
+        out.write_all(&unprotected)?;
+        out.write_all(b",")?;
+    }
+
+    out.write_all(b"\"payload\":\"")?;
+    let mut enc: Encoder<_> = Output(&mut out).into();
+    copy(payload, |chunk| {
+        signer.update(chunk)?;
+        Ok(enc.update(chunk)?)
+    })?;
+    enc.finish()?;
+
+    let sig = Base64UrlUnpadded::encode_string(&signer.finish(rng)?.signature);
+    write!(out, "\",\"signature\":\"{sig}\"}}")?;
+    Ok(())
+}
@@ -0,0 +1,118 @@
+// SPDX-FileCopyrightText: 2022 Profian Inc. <opensource@profian.com>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+#![cfg(feature = "k256")]
+
+use alloc::vec::Vec;
+use core::convert::Infallible;
+
+use jose_b64::stream::Update;
+use jose_jwa::{Signing, Signing::Es256K};
+use jose_jwk::crypto;
+use k256::ecdsa::signature::{DigestSigner, DigestVerifier};
+use k256::ecdsa::{Signature, SigningKey, VerifyingKey};
+use k256::{PublicKey, SecretKey};
+use rand_core::CryptoRngCore;
+use sha2::{Digest, Sha256};
+
+use super::core::{CoreSigner, CoreSigningKey, CoreVerifier, CoreVerifyingKey};
+use super::Error;
+
+/// An ES256K (RFC 8812 Section 3.2) signing state.
+pub struct Es256KSigner {
+    key: SigningKey,
+    sha: Sha256,
+}
+
+impl Update for Es256KSigner {
+    type Error = Infallible;
+
+    fn update(&mut self, chunk: impl AsRef<[u8]>) -> Result<(), Self::Error> {
+        Digest::update(&mut self.sha, chunk);
+        Ok(())
+    }
+}
+
+impl CoreSigner for Es256KSigner {
+    type FinishError = Error;
+
+    fn finish(self, _: impl CryptoRngCore) -> Result<Vec<u8>, Self::FinishError> {
+        let signature: Signature = self.key.sign_digest(self.sha);
+        Ok(signature.to_bytes().to_vec())
+    }
+}
+
+/// An ES256K (RFC 8812 Section 3.2) verification state.
+pub struct Es256KVerifier {
+    key: VerifyingKey,
+    sha: Sha256,
+}
+
+impl Update for Es256KVerifier {
+    type Error = Infallible;
+
+    fn update(&mut self, chunk: impl AsRef<[u8]>) -> Result<(), Self::Error> {
+        Digest::update(&mut self.sha, chunk);
+        Ok(())
+    }
+}
+
+impl Es256KVerifier {
+    fn new(key: &PublicKey, alg: Signing) -> Result<Self, Error> {
+        match alg {
+            Es256K => Ok(Self {
+                key: key.into(),
+                sha: Sha256::new(),
+            }),
+
+            _ => Err(crypto::Error::AlgMismatch.into()),
+        }
+    }
+}
+
+impl CoreVerifier for Es256KVerifier {
+    type FinishError = Error;
+
+    fn finish(self, signature: &[u8]) -> Result<(), Self::FinishError> {
+        // The signature must be the fixed-width R || S encoding, not DER.
+        let signature = Signature::from_slice(signature).map_err(|_| Error::Invalid)?;
+
+        self.key
+            .verify_digest(self.sha, &signature)
+            .map_err(|_| Error::Invalid)
+    }
+}
+
+impl<'a> CoreSigningKey<'a> for SecretKey {
+    type StartError = Error;
+    type Finish = Es256KSigner;
+
+    fn sign(&'a self, alg: Signing) -> Result<Self::Finish, Self::StartError> {
+        match alg {
+            Es256K => Ok(Es256KSigner {
+                key: self.into(),
+                sha: Sha256::new(),
+            }),
+
+            _ => Err(crypto::Error::AlgMismatch.into()),
+        }
+    }
+}
+
+impl<'a> CoreVerifyingKey<'a> for SecretKey {
+    type StartError = Error;
+    type Finish = Es256KVerifier;
+
+    fn verify(&'a self, alg: Signing) -> Result<Self::Finish, Self::StartError> {
+        Es256KVerifier::new(&self.public_key(), alg)
+    }
+}
+
+impl<'a> CoreVerifyingKey<'a> for PublicKey {
+    type StartError = Error;
+    type Finish = Es256KVerifier;
+
+    fn verify(&'a self, alg: Signing) -> Result<Self::Finish, Self::StartError> {
+        Es256KVerifier::new(self, alg)
+    }
+}
@@ -0,0 +1,341 @@
+// SPDX-FileCopyrightText: 2022 Profian Inc. <opensource@profian.com>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+#![cfg(any(
+    feature = "ed25519",
+    feature = "hmac",
+    feature = "k256",
+    feature = "p256",
+    feature = "p384",
+    feature = "p521",
+    feature = "rsa"
+))]
+
+use alloc::vec::Vec;
+use core::convert::Infallible;
+
+use jose_b64::stream::Update;
+use jose_jwa::Signing;
+use jose_jwk::crypto::{self, Key, Kind};
+use rand_core::CryptoRngCore;
+
+use super::core::{CoreSigner, CoreSigningKey, CoreVerifier, CoreVerifyingKey};
+use super::Error;
+
+/// The signing state of a [`Key`].
+#[cfg_attr(not(feature = "rsa"), allow(clippy::large_enum_variant))]
+pub enum KeySigner<'a> {
+    #[cfg(feature = "hmac")]
+    Hmac(super::hmac::Hmac),
+
+    #[cfg(feature = "p256")]
+    Es256(super::p256::Es256Signer),
+
+    #[cfg(feature = "p384")]
+    Es384(super::p384::Es384Signer),
+
+    #[cfg(feature = "p521")]
+    Es512(super::p521::Es512Signer),
+
+    #[cfg(feature = "k256")]
+    Es256K(super::k256::Es256KSigner),
+
+    #[cfg(feature = "rsa")]
+    Rsa(super::rsa::RsaSigner<'a>),
+
+    #[cfg(feature = "ed25519")]
+    Ed25519(super::ed25519::Ed25519Signer),
+
+    /// Only the RSA state borrows the key.
+    #[cfg(not(feature = "rsa"))]
+    #[doc(hidden)]
+    Unused(Infallible, core::marker::PhantomData<&'a ()>),
+}
+
+impl Update for KeySigner<'_> {
+    type Error = Infallible;
+
+    fn update(&mut self, chunk: impl AsRef<[u8]>) -> Result<(), Self::Error> {
+        match self {
+            #[cfg(feature = "hmac")]
+            Self::Hmac(x) => x.update(chunk),
+
+            #[cfg(feature = "p256")]
+            Self::Es256(x) => x.update(chunk),
+
+            #[cfg(feature = "p384")]
+            Self::Es384(x) => x.update(chunk),
+
+            #[cfg(feature = "p521")]
+            Self::Es512(x) => x.update(chunk),
+
+            #[cfg(feature = "k256")]
+            Self::Es256K(x) => x.update(chunk),
+
+            #[cfg(feature = "rsa")]
+            Self::Rsa(x) => x.update(chunk),
+
+            #[cfg(feature = "ed25519")]
+            Self::Ed25519(x) => x.update(chunk),
+
+            #[cfg(not(feature = "rsa"))]
+            Self::Unused(x, ..) => match *x {},
+        }
+    }
+}
+
+impl CoreSigner for KeySigner<'_> {
+    type FinishError = Error;
+
+    fn finish(self, rng: impl CryptoRngCore) -> Result<Vec<u8>, Self::FinishError> {
+        match self {
+            #[cfg(feature = "hmac")]
+            Self::Hmac(x) => CoreSigner::finish(x, rng),
+
+            #[cfg(feature = "p256")]
+            Self::Es256(x) => x.finish(rng),
+
+            #[cfg(feature = "p384")]
+            Self::Es384(x) => x.finish(rng),
+
+            #[cfg(feature = "p521")]
+            Self::Es512(x) => x.finish(rng),
+
+            #[cfg(feature = "k256")]
+            Self::Es256K(x) => x.finish(rng),
+
+            #[cfg(feature = "rsa")]
+            Self::Rsa(x) => x.finish(rng),
+
+            #[cfg(feature = "ed25519")]
+            Self::Ed25519(x) => x.finish(rng),
+
+            #[cfg(not(feature = "rsa"))]
+            Self::Unused(x, ..) => match x {},
+        }
+    }
+}
+
+/// The verification state of a [`Key`].
+#[cfg_attr(not(feature = "rsa"), allow(clippy::large_enum_variant))]
+pub enum KeyVerifier<'a> {
+    #[cfg(feature = "hmac")]
+    Hmac(super::hmac::Hmac),
+
+    #[cfg(feature = "p256")]
+    Es256(super::p256::Es256Verifier),
+
+    #[cfg(feature = "p384")]
+    Es384(super::p384::Es384Verifier),
+
+    #[cfg(feature = "p521")]
+    Es512(super::p521::Es512Verifier),
+
+    #[cfg(feature = "k256")]
+    Es256K(super::k256::Es256KVerifier),
+
+    #[cfg(feature = "rsa")]
+    Rsa(super::rsa::RsaVerifier<'a>),
+
+    #[cfg(feature = "ed25519")]
+    Ed25519(super::ed25519::Ed25519Verifier),
+
+    /// Only the RSA state borrows the key.
+    #[cfg(not(feature = "rsa"))]
+    #[doc(hidden)]
+    Unused(Infallible, core::marker::PhantomData<&'a ()>),
+}
+
+impl KeyVerifier<'_> {
+    /// Detaches the state from the key it was started with.
+    pub(super) fn into_owned(self) -> KeyVerifier<'static> {
+        match self {
+            #[cfg(feature = "hmac")]
+            Self::Hmac(x) => KeyVerifier::Hmac(x),
+
+            #[cfg(feature = "p256")]
+            Self::Es256(x) => KeyVerifier::Es256(x),
+
+            #[cfg(feature = "p384")]
+            Self::Es384(x) => KeyVerifier::Es384(x),
+
+            #[cfg(feature = "p521")]
+            Self::Es512(x) => KeyVerifier::Es512(x),
+
+            #[cfg(feature = "k256")]
+            Self::Es256K(x) => KeyVerifier::Es256K(x),
+
+            #[cfg(feature = "rsa")]
+            Self::Rsa(x) => KeyVerifier::Rsa(x.into_owned()),
+
+            #[cfg(feature = "ed25519")]
+            Self::Ed25519(x) => KeyVerifier::Ed25519(x),
+
+            #[cfg(not(feature = "rsa"))]
+            Self::Unused(x, ..) => match x {},
+        }
+    }
+}
+
+impl Update for KeyVerifier<'_> {
+    type Error = Infallible;
+
+    fn update(&mut self, chunk: impl AsRef<[u8]>) -> Result<(), Self::Error> {
+        match self {
+            #[cfg(feature = "hmac")]
+            Self::Hmac(x) => x.update(chunk),
+
+            #[cfg(feature = "p256")]
+            Self::Es256(x) => x.update(chunk),
+
+            #[cfg(feature = "p384")]
+            Self::Es384(x) => x.update(chunk),
+
+            #[cfg(feature = "p521")]
+            Self::Es512(x) => x.update(chunk),
+
+            #[cfg(feature = "k256")]
+            Self::Es256K(x) => x.update(chunk),
+
+            #[cfg(feature = "rsa")]
+            Self::Rsa(x) => x.update(chunk),
+
+            #[cfg(feature = "ed25519")]
+            Self::Ed25519(x) => x.update(chunk),
+
+            #[cfg(not(feature = "rsa"))]
+            Self::Unused(x, ..) => match *x {},
+        }
+    }
+}
+
+impl CoreVerifier for KeyVerifier<'_> {
+    type FinishError = Error;
+
+    fn finish(self, signature: &[u8]) -> Result<(), Self::FinishError> {
+        match self {
+            #[cfg(feature = "hmac")]
+            Self::Hmac(x) => CoreVerifier::finish(x, signature),
+
+            #[cfg(feature = "p256")]
+            Self::Es256(x) => x.finish(signature),
+
+            #[cfg(feature = "p384")]
+            Self::Es384(x) => x.finish(signature),
+
+            #[cfg(feature = "p521")]
+            Self::Es512(x) => x.finish(signature),
+
+            #[cfg(feature = "k256")]
+            Self::Es256K(x) => x.finish(signature),
+
+            #[cfg(feature = "rsa")]
+            Self::Rsa(x) => x.finish(signature),
+
+            #[cfg(feature = "ed25519")]
+            Self::Ed25519(x) => x.finish(signature),
+
+            #[cfg(not(feature = "rsa"))]
+            Self::Unused(x, ..) => match x {},
+        }
+    }
+}
+
+impl<'a> CoreSigningKey<'a> for Key {
+    type StartError = Error;
+    type Finish = KeySigner<'a>;
+
+    fn sign(&'a self, alg: Signing) -> Result<Self::Finish, Self::StartError> {
+        match self {
+            #[cfg(feature = "hmac")]
+            Key::Oct(k) => Ok(KeySigner::Hmac(super::hmac::Hmac::new(k, alg)?)),
+
+            #[cfg(feature = "p256")]
+            Key::P256(k) => Ok(KeySigner::Es256(k.sign(alg)?)),
+
+            #[cfg(feature = "p384")]
+            Key::P384(k) => Ok(KeySigner::Es384(k.sign(alg)?)),
+
+            #[cfg(feature = "p521")]
+            Key::P521(k) => Ok(KeySigner::Es512(k.sign(alg)?)),
+
+            #[cfg(feature = "k256")]
+            Key::P256K(k) => Ok(KeySigner::Es256K(k.sign(alg)?)),
+
+            #[cfg(feature = "rsa")]
+            Key::Rsa(k) => Ok(KeySigner::Rsa(k.sign(alg)?)),
+
+            #[cfg(feature = "ed25519")]
+            Key::Ed25519(k) => Ok(KeySigner::Ed25519(k.sign(alg)?)),
+
+            #[allow(unreachable_patterns)]
+            _ => Err(crypto::Error::Unsupported.into()),
+        }
+    }
+}
+
+impl<'a> CoreVerifyingKey<'a> for Key {
+    type StartError = Error;
+    type Finish = KeyVerifier<'a>;
+
+    fn verify(&'a self, alg: Signing) -> Result<Self::Finish, Self::StartError> {
+        match self {
+            #[cfg(feature = "hmac")]
+            Key::Oct(k) => Ok(KeyVerifier::Hmac(super::hmac::Hmac::new(k, alg)?)),
+
+            #[cfg(feature = "p256")]
+            Key::P256(k) => Ok(KeyVerifier::Es256(k.verify(alg)?)),
+
+            #[cfg(feature = "p384")]
+            Key::P384(k) => Ok(KeyVerifier::Es384(k.verify(alg)?)),
+
+            #[cfg(feature = "p521")]
+            Key::P521(k) => Ok(KeyVerifier::Es512(k.verify(alg)?)),
+
+            #[cfg(feature = "k256")]
+            Key::P256K(k) => Ok(KeyVerifier::Es256K(k.verify(alg)?)),
+
+            #[cfg(feature = "rsa")]
+            Key::Rsa(k) => Ok(KeyVerifier::Rsa(k.verify(alg)?)),
+
+            #[cfg(feature = "ed25519")]
+            Key::Ed25519(k) => Ok(KeyVerifier::Ed25519(k.verify(alg)?)),
+
+            #[allow(unreachable_patterns)]
+            _ => Err(crypto::Error::Unsupported.into()),
+        }
+    }
+}
+
+impl<'a, P, S> CoreSigningKey<'a> for Kind<P, S>
+where
+    S: CoreSigningKey<'a, StartError = Error>,
+    Error: From<<S::Finish as Update>::Error>,
+{
+    type StartError = Error;
+    type Finish = S::Finish;
+
+    fn sign(&'a self, alg: Signing) -> Result<Self::Finish, Self::StartError> {
+        match self {
+            Kind::Public(..) => Err(crypto::Error::NotPrivate.into()),
+            Kind::Secret(k) => k.sign(alg),
+        }
+    }
+}
+
+impl<'a, P, S> CoreVerifyingKey<'a> for Kind<P, S>
+where
+    P: CoreVerifyingKey<'a, StartError = Error>,
+    S: CoreVerifyingKey<'a, StartError = Error, Finish = P::Finish>,
+    Error: From<<P::Finish as Update>::Error>,
+{
+    type StartError = Error;
+    type Finish = P::Finish;
+
+    fn verify(&'a self, alg: Signing) -> Result<Self::Finish, Self::StartError> {
+        match self {
+            Kind::Public(k) => k.verify(alg),
+            Kind::Secret(k) => k.verify(alg),
+        }
+    }
+}
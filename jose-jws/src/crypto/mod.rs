@@ -3,14 +3,204 @@
 
 //! JWS Cryptographic Implementation
 
+pub mod acme;
+mod builder;
 mod core;
+mod cosign;
+mod ed25519;
+mod hmac;
+pub mod io;
+mod k256;
+mod key;
+mod p256;
+mod p384;
+mod p521;
+mod policy;
+mod quorum;
+mod rsa;
+mod set;
+mod verify;
 
+use ::core::convert::Infallible;
 use alloc::{vec, vec::Vec};
 
-use jose_b64::stream::Update;
-use rand_core::RngCore;
+use jose_b64::serde::Json;
+use jose_b64::stream::{Encoder, Optional, Update};
+use jose_jwa::{Algorithm, Signing};
+use jose_jwk::crypto::KeyInfo;
+use rand_core::CryptoRngCore;
 
-use crate::{Flattened, General, Jws, Protected, Signature, Unprotected};
+#[cfg(any(
+    feature = "ed25519",
+    feature = "hmac",
+    feature = "k256",
+    feature = "p256",
+    feature = "p384",
+    feature = "p521",
+    feature = "rsa"
+))]
+pub use builder::Builder;
+#[cfg(any(
+    feature = "ed25519",
+    feature = "hmac",
+    feature = "k256",
+    feature = "p256",
+    feature = "p384",
+    feature = "p521",
+    feature = "rsa"
+))]
+pub use quorum::{Quorum, Report};
+
+pub use cosign::DetachedSigner;
+pub use policy::Policy;
+pub use verify::{Detached, Unsecured, Verified};
+
+use self::core::{CoreSigner, CoreSigningKey, CoreVerifier, CoreVerifyingKey};
+use crate::{Flattened, General, HeaderError, Jws, Protected, Signature, Unprotected};
+
+/// A JWS cryptographic error.
+#[derive(Debug, Default)]
+#[non_exhaustive]
+pub enum Error {
+    /// The signature is invalid.
+    #[default]
+    Invalid,
+
+    /// The signature could not be created.
+    Signing,
+
+    /// The header does not specify an algorithm.
+    MissingAlg,
+
+    /// The key cannot be used with the algorithm.
+    Key(jose_jwk::crypto::Error),
+
+    /// The protected header could not be serialized.
+    Json(serde_json::Error),
+
+    /// The signatures cannot be represented in the requested serialization.
+    Serialization,
+
+    /// No key is suitable for creating or verifying the signature.
+    NoKey,
+
+    /// The algorithm is not allowed by the verification policy.
+    Disallowed,
+
+    /// The signature is unsecured, and either the verification policy
+    /// rejects it or it was presented for verification with a key.
+    Unsecured,
+
+    /// The algorithm does not match the `alg` parameter of the key.
+    AlgMismatch,
+
+    /// The `crit` header parameter is malformed or lists an extension that
+    /// is not understood.
+    Critical,
+
+    /// The signatures disagree on the `b64` header parameter.
+    MixedB64,
+
+    /// The JWS does not carry its payload, which must instead be supplied
+    /// separately (RFC 7515 Appendix F).
+    Detached,
+
+    /// The JWS carries its payload, which must not also be supplied
+    /// separately.
+    Attached,
+
+    /// The signatures do not satisfy the required quorum.
+    Quorum,
+
+    /// The headers are malformed or not disjoint.
+    Header(HeaderError),
+
+    /// The request breaks a rule of ACME (RFC 8555).
+    #[cfg(all(
+        feature = "url",
+        any(
+            feature = "ed25519",
+            feature = "hmac",
+            feature = "k256",
+            feature = "p256",
+            feature = "p384",
+            feature = "p521",
+            feature = "rsa"
+        )
+    ))]
+    Acme(acme::Violation),
+
+    /// The payload could not be read or the output could not be written.
+    #[cfg(feature = "std")]
+    Io(std::io::Error),
+}
+
+impl From<Infallible> for Error {
+    fn from(value: Infallible) -> Self {
+        match value {}
+    }
+}
+
+impl From<jose_jwk::crypto::Error> for Error {
+    fn from(value: jose_jwk::crypto::Error) -> Self {
+        Self::Key(value)
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<std::io::Error> for Error {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl From<HeaderError> for Error {
+    fn from(value: HeaderError) -> Self {
+        Self::Header(value)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(value: serde_json::Error) -> Self {
+        Self::Json(value)
+    }
+}
+
+/// Returns the algorithm from the protected header or, failing that, from
+/// the unprotected header.
+fn algorithm(prot: Option<&Protected>, head: Option<&Unprotected>) -> Result<Signing, Error> {
+    prot.and_then(|p| p.oth.alg)
+        .or_else(|| head.and_then(|h| h.alg))
+        .ok_or(Error::MissingAlg)
+}
+
+/// Returns the payload carried by a JWS.
+///
+/// A detached payload is an error rather than an empty payload, so that the
+/// caller is directed to the methods that take the payload separately.
+fn attached<P>(payload: Option<&P>) -> Result<&P, Error> {
+    payload.ok_or(Error::Detached)
+}
+
+/// Checks that a JWS does not carry its payload.
+///
+/// The methods that take the payload separately would otherwise ignore the
+/// embedded one, so that it would appear to be verified.
+fn unattached<P>(payload: Option<&P>) -> Result<(), Error> {
+    match payload {
+        Some(..) => Err(Error::Attached),
+        None => Ok(()),
+    }
+}
+
+/// Feeds `BASE64URL(protected) || '.'` into the core state and prepares it
+/// to receive the payload.
+fn prepare<T: Update>(core: T, prot: Option<&[u8]>, b64: bool) -> Result<Optional<T>, T::Error> {
+    let mut enc: Encoder<T> = Encoder::from(core);
+    enc.update(prot.unwrap_or_default())?;
+    let core = enc.finish()?.chain(b".")?;
+    Ok(Optional::new(core, b64))
+}
 
 /// Signature creation state
 pub trait Signer: Update {
@@ -18,7 +208,54 @@ pub trait Signer: Update {
     type FinishError: From<Self::Error>;
 
     /// Finish processing payload and create the signature.
-    fn finish(self, rng: impl 'static + RngCore) -> Result<Signature, Self::FinishError>;
+    fn finish(self, rng: impl CryptoRngCore) -> Result<Signature, Self::FinishError>;
+}
+
+/// The signature creation state of the built-in keys.
+///
+/// Payload bytes passed to [`Update::update`] are base64-encoded on the fly
+/// unless the protected header sets `b64` to `false`.
+pub struct SigningState<T> {
+    prot: Option<Json<Protected>>,
+    head: Option<Unprotected>,
+    core: Optional<T>,
+}
+
+impl<T> SigningState<T> {
+    /// Returns the protected header exactly as it is signed.
+    pub fn protected(&self) -> Option<&Json<Protected>> {
+        self.prot.as_ref()
+    }
+
+    /// Returns the unprotected header.
+    pub fn header(&self) -> Option<&Unprotected> {
+        self.head.as_ref()
+    }
+}
+
+impl<T: CoreSigner> Update for SigningState<T> {
+    type Error = T::Error;
+
+    fn update(&mut self, chunk: impl AsRef<[u8]>) -> Result<(), Self::Error> {
+        self.core.update(chunk)
+    }
+}
+
+impl<T: CoreSigner<FinishError = Error>> Signer for SigningState<T>
+where
+    Error: From<T::Error>,
+{
+    type FinishError = Error;
+
+    fn finish(self, rng: impl CryptoRngCore) -> Result<Signature, Self::FinishError> {
+        let signature = self.core.finish()?.finish(rng)?;
+
+        Ok(Signature {
+            header: self.head,
+            protected: self.prot,
+            signature: signature.into(),
+        })
+    }
 }
 
 /// A signature creation key
@@ -37,6 +274,41 @@ pub trait SigningKey<'a> {
     ) -> Result<Self::Signer, Self::StartError>;
 }
 
+impl<'a, K> SigningKey<'a> for K
+where
+    K: CoreSigningKey<'a, StartError = Error> + KeyInfo,
+    K::Finish: CoreSigner<FinishError = Error>,
+    Error: From<<K::Finish as Update>::Error>,
+{
+    type StartError = Error;
+    type Signer = SigningState<K::Finish>;
+
+    fn sign(
+        &'a self,
+        prot: Option<Protected>,
+        head: Option<Unprotected>,
+    ) -> Result<Self::Signer, Self::StartError> {
+        let alg = algorithm(prot.as_ref(), head.as_ref())?;
+        if !self.is_supported(&Algorithm::Signing(alg)) {
+            return Err(jose_jwk::crypto::Error::Unsupported.into());
+        }
+
+        let b64 = prot.as_ref().map_or(true, |p| p.b64);
+        let crit = prot.as_ref().and_then(|p| p.crit.as_ref());
+        if !b64 && !crit.map_or(false, |c| c.iter().any(|c| c == "b64")) {
+            return Err(Error::Critical);
+        }
+
+        let prot = prot.map(Json::new).transpose()?;
+        crate::head::disjoint(prot.as_ref().map(|p| p.as_ref()), head.as_ref())?;
+
+        let core = CoreSigningKey::sign(self, alg)?;
+        let core = prepare(core, prot.as_ref().map(|p| p.as_ref()), b64)?;
+
+        Ok(SigningState { prot, head, core })
+    }
+}
+
 /// Signature verification state
 pub trait Verifier<'a>: Update {
     #[allow(missing_docs)]
@@ -66,6 +338,34 @@ where
     }
 }
 
+/// The signature verification state of the built-in keys.
+///
+/// Payload bytes passed to [`Update::update`] are base64-encoded on the fly
+/// unless the protected header sets `b64` to `false`.
+pub struct VerifyingState<'a, T> {
+    signature: &'a [u8],
+    core: Optional<T>,
+}
+
+impl<'a, T: CoreVerifier> Update for VerifyingState<'a, T> {
+    type Error = T::Error;
+
+    fn update(&mut self, chunk: impl AsRef<[u8]>) -> Result<(), Self::Error> {
+        self.core.update(chunk)
+    }
+}
+
+impl<'a, T: CoreVerifier<FinishError = Error>> Verifier<'a> for VerifyingState<'a, T>
+where
+    Error: From<T::Error>,
+{
+    type FinishError = Error;
+
+    fn finish(self) -> Result<(), Self::FinishError> {
+        self.core.finish()?.finish(self.signature)
+    }
+}
+
 /// A signature verification key
 pub trait VerifyingKey<'a, T> {
     #[allow(missing_docs)]
@@ -78,6 +378,51 @@ pub trait VerifyingKey<'a, T> {
     fn verify(&'a self, val: T) -> Result<Self::Verifier, Self::StartError>;
 }
 
+impl<'a, K> VerifyingKey<'a, &'a Signature> for K
+where
+    K: CoreVerifyingKey<'a, StartError = Error> + KeyInfo,
+    K::Finish: CoreVerifier<FinishError = Error>,
+    Error: From<<K::Finish as Update>::Error>,
+{
+    type StartError = Error;
+    type Verifier = VerifyingState<'a, K::Finish>;
+
+    fn verify(&'a self, sig: &'a Signature) -> Result<Self::Verifier, Self::StartError> {
+        start(self, sig, |core| core)
+    }
+}
+
+/// Begins verifying a signature.
+///
+/// The core state is passed through `detach` before it receives any input,
+/// which lets the caller release its borrow of a key that does not outlive
+/// the signature.
+fn start<'a, 'k, K, T>(
+    key: &'k K,
+    sig: &'a Signature,
+    detach: impl FnOnce(K::Finish) -> T,
+) -> Result<VerifyingState<'a, T>, Error>
+where
+    K: CoreVerifyingKey<'k, StartError = Error> + KeyInfo,
+    T: Update,
+    Error: From<T::Error>,
+{
+    let prot = sig.protected.as_ref();
+    let alg = algorithm(prot.map(|p| &**p), sig.header.as_ref())?;
+    if !key.is_supported(&Algorithm::Signing(alg)) {
+        return Err(jose_jwk::crypto::Error::Unsupported.into());
+    }
+
+    let b64 = prot.map_or(true, |p| p.b64);
+    let core = detach(CoreVerifyingKey::verify(key, alg)?);
+    let core = prepare(core, prot.map(|p| p.as_ref()), b64)?;
+
+    Ok(VerifyingState {
+        signature: &sig.signature,
+        core,
+    })
+}
+
 impl<'a, A, T, V> VerifyingKey<'a, A> for [T]
 where
     T: VerifyingKey<'a, A, Verifier = Vec<V>>,
@@ -100,26 +445,26 @@ where
     }
 }
 
-impl<'a, T: VerifyingKey<'a, &'a Signature>> VerifyingKey<'a, &'a Flattened> for T
+impl<'a, P, T: VerifyingKey<'a, &'a Signature>> VerifyingKey<'a, &'a Flattened<P>> for T
 where
     <T::Verifier as Verifier<'a>>::FinishError: Default,
 {
     type StartError = T::StartError;
     type Verifier = Vec<T::Verifier>;
 
-    fn verify(&'a self, flattened: &'a Flattened) -> Result<Self::Verifier, Self::StartError> {
+    fn verify(&'a self, flattened: &'a Flattened<P>) -> Result<Self::Verifier, Self::StartError> {
         Ok(vec![self.verify(&flattened.signature)?])
     }
 }
 
-impl<'a, T: VerifyingKey<'a, &'a Signature>> VerifyingKey<'a, &'a General> for T
+impl<'a, P, T: VerifyingKey<'a, &'a Signature>> VerifyingKey<'a, &'a General<P>> for T
 where
     <T::Verifier as Verifier<'a>>::FinishError: Default,
 {
     type StartError = T::StartError;
     type Verifier = Vec<T::Verifier>;
 
-    fn verify(&'a self, general: &'a General) -> Result<Self::Verifier, Self::StartError> {
+    fn verify(&'a self, general: &'a General<P>) -> Result<Self::Verifier, Self::StartError> {
         general
             .signatures
             .iter()
@@ -128,17 +473,17 @@ where
     }
 }
 
-impl<'a, T, V, E> VerifyingKey<'a, &'a Jws> for T
+impl<'a, P, T, V, E> VerifyingKey<'a, &'a Jws<P>> for T
 where
-    T: VerifyingKey<'a, &'a Flattened, Verifier = V, StartError = E>,
-    T: VerifyingKey<'a, &'a General, Verifier = V, StartError = E>,
+    T: VerifyingKey<'a, &'a Flattened<P>, Verifier = V, StartError = E>,
+    T: VerifyingKey<'a, &'a General<P>, Verifier = V, StartError = E>,
     E: From<V::Error>,
     V: Verifier<'a>,
 {
     type StartError = E;
     type Verifier = V;
 
-    fn verify(&'a self, jws: &'a Jws) -> Result<Self::Verifier, Self::StartError> {
+    fn verify(&'a self, jws: &'a Jws<P>) -> Result<Self::Verifier, Self::StartError> {
         match jws {
             Jws::General(general) => self.verify(general),
             Jws::Flattened(flattened) => self.verify(flattened),
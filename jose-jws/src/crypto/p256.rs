@@ -0,0 +1,118 @@
+// SPDX-FileCopyrightText: 2022 Profian Inc. <opensource@profian.com>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+#![cfg(feature = "p256")]
+
+use alloc::vec::Vec;
+use core::convert::Infallible;
+
+use jose_b64::stream::Update;
+use jose_jwa::{Signing, Signing::Es256};
+use jose_jwk::crypto;
+use p256::ecdsa::signature::{DigestSigner, DigestVerifier};
+use p256::ecdsa::{Signature, SigningKey, VerifyingKey};
+use p256::{PublicKey, SecretKey};
+use rand_core::CryptoRngCore;
+use sha2::{Digest, Sha256};
+
+use super::core::{CoreSigner, CoreSigningKey, CoreVerifier, CoreVerifyingKey};
+use super::Error;
+
+/// An ES256 (RFC 7518 Section 3.4) signing state.
+pub struct Es256Signer {
+    key: SigningKey,
+    sha: Sha256,
+}
+
+impl Update for Es256Signer {
+    type Error = Infallible;
+
+    fn update(&mut self, chunk: impl AsRef<[u8]>) -> Result<(), Self::Error> {
+        Digest::update(&mut self.sha, chunk);
+        Ok(())
+    }
+}
+
+impl CoreSigner for Es256Signer {
+    type FinishError = Error;
+
+    fn finish(self, _: impl CryptoRngCore) -> Result<Vec<u8>, Self::FinishError> {
+        let signature: Signature = self.key.sign_digest(self.sha);
+        Ok(signature.to_bytes().to_vec())
+    }
+}
+
+/// An ES256 (RFC 7518 Section 3.4) verification state.
+pub struct Es256Verifier {
+    key: VerifyingKey,
+    sha: Sha256,
+}
+
+impl Update for Es256Verifier {
+    type Error = Infallible;
+
+    fn update(&mut self, chunk: impl AsRef<[u8]>) -> Result<(), Self::Error> {
+        Digest::update(&mut self.sha, chunk);
+        Ok(())
+    }
+}
+
+impl Es256Verifier {
+    fn new(key: &PublicKey, alg: Signing) -> Result<Self, Error> {
+        match alg {
+            Es256 => Ok(Self {
+                key: key.into(),
+                sha: Sha256::new(),
+            }),
+
+            _ => Err(crypto::Error::AlgMismatch.into()),
+        }
+    }
+}
+
+impl CoreVerifier for Es256Verifier {
+    type FinishError = Error;
+
+    fn finish(self, signature: &[u8]) -> Result<(), Self::FinishError> {
+        // The signature must be the fixed-width R || S encoding, not DER.
+        let signature = Signature::from_slice(signature).map_err(|_| Error::Invalid)?;
+
+        self.key
+            .verify_digest(self.sha, &signature)
+            .map_err(|_| Error::Invalid)
+    }
+}
+
+impl<'a> CoreSigningKey<'a> for SecretKey {
+    type StartError = Error;
+    type Finish = Es256Signer;
+
+    fn sign(&'a self, alg: Signing) -> Result<Self::Finish, Self::StartError> {
+        match alg {
+            Es256 => Ok(Es256Signer {
+                key: self.into(),
+                sha: Sha256::new(),
+            }),
+
+            _ => Err(crypto::Error::AlgMismatch.into()),
+        }
+    }
+}
+
+impl<'a> CoreVerifyingKey<'a> for SecretKey {
+    type StartError = Error;
+    type Finish = Es256Verifier;
+
+    fn verify(&'a self, alg: Signing) -> Result<Self::Finish, Self::StartError> {
+        Es256Verifier::new(&self.public_key(), alg)
+    }
+}
+
+impl<'a> CoreVerifyingKey<'a> for PublicKey {
+    type StartError = Error;
+    type Finish = Es256Verifier;
+
+    fn verify(&'a self, alg: Signing) -> Result<Self::Finish, Self::StartError> {
+        Es256Verifier::new(self, alg)
+    }
+}
@@ -0,0 +1,118 @@
+// SPDX-FileCopyrightText: 2022 Profian Inc. <opensource@profian.com>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+#![cfg(feature = "p384")]
+
+use alloc::vec::Vec;
+use core::convert::Infallible;
+
+use jose_b64::stream::Update;
+use jose_jwa::{Signing, Signing::Es384};
+use jose_jwk::crypto;
+use p384::ecdsa::signature::{DigestSigner, DigestVerifier};
+use p384::ecdsa::{Signature, SigningKey, VerifyingKey};
+use p384::{PublicKey, SecretKey};
+use rand_core::CryptoRngCore;
+use sha2::{Digest, Sha384};
+
+use super::core::{CoreSigner, CoreSigningKey, CoreVerifier, CoreVerifyingKey};
+use super::Error;
+
+/// An ES384 (RFC 7518 Section 3.4) signing state.
+pub struct Es384Signer {
+    key: SigningKey,
+    sha: Sha384,
+}
+
+impl Update for Es384Signer {
+    type Error = Infallible;
+
+    fn update(&mut self, chunk: impl AsRef<[u8]>) -> Result<(), Self::Error> {
+        Digest::update(&mut self.sha, chunk);
+        Ok(())
+    }
+}
+
+impl CoreSigner for Es384Signer {
+    type FinishError = Error;
+
+    fn finish(self, _: impl CryptoRngCore) -> Result<Vec<u8>, Self::FinishError> {
+        let signature: Signature = self.key.sign_digest(self.sha);
+        Ok(signature.to_bytes().to_vec())
+    }
+}
+
+/// An ES384 (RFC 7518 Section 3.4) verification state.
+pub struct Es384Verifier {
+    key: VerifyingKey,
+    sha: Sha384,
+}
+
+impl Update for Es384Verifier {
+    type Error = Infallible;
+
+    fn update(&mut self, chunk: impl AsRef<[u8]>) -> Result<(), Self::Error> {
+        Digest::update(&mut self.sha, chunk);
+        Ok(())
+    }
+}
+
+impl Es384Verifier {
+    fn new(key: &PublicKey, alg: Signing) -> Result<Self, Error> {
+        match alg {
+            Es384 => Ok(Self {
+                key: key.into(),
+                sha: Sha384::new(),
+            }),
+
+            _ => Err(crypto::Error::AlgMismatch.into()),
+        }
+    }
+}
+
+impl CoreVerifier for Es384Verifier {
+    type FinishError = Error;
+
+    fn finish(self, signature: &[u8]) -> Result<(), Self::FinishError> {
+        // The signature must be the fixed-width R || S encoding, not DER.
+        let signature = Signature::from_slice(signature).map_err(|_| Error::Invalid)?;
+
+        self.key
+            .verify_digest(self.sha, &signature)
+            .map_err(|_| Error::Invalid)
+    }
+}
+
+impl<'a> CoreSigningKey<'a> for SecretKey {
+    type StartError = Error;
+    type Finish = Es384Signer;
+
+    fn sign(&'a self, alg: Signing) -> Result<Self::Finish, Self::StartError> {
+        match alg {
+            Es384 => Ok(Es384Signer {
+                key: self.into(),
+                sha: Sha384::new(),
+            }),
+
+            _ => Err(crypto::Error::AlgMismatch.into()),
+        }
+    }
+}
+
+impl<'a> CoreVerifyingKey<'a> for SecretKey {
+    type StartError = Error;
+    type Finish = Es384Verifier;
+
+    fn verify(&'a self, alg: Signing) -> Result<Self::Finish, Self::StartError> {
+        Es384Verifier::new(&self.public_key(), alg)
+    }
+}
+
+impl<'a> CoreVerifyingKey<'a> for PublicKey {
+    type StartError = Error;
+    type Finish = Es384Verifier;
+
+    fn verify(&'a self, alg: Signing) -> Result<Self::Finish, Self::StartError> {
+        Es384Verifier::new(self, alg)
+    }
+}
@@ -0,0 +1,123 @@
+// SPDX-FileCopyrightText: 2022 Profian Inc. <opensource@profian.com>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+#![cfg(feature = "p521")]
+
+use alloc::vec::Vec;
+use core::convert::Infallible;
+
+use jose_b64::stream::Update;
+use jose_jwa::{Signing, Signing::Es512};
+use jose_jwk::crypto;
+use p521::ecdsa::signature::hazmat::{PrehashVerifier, RandomizedPrehashSigner};
+use p521::ecdsa::{Signature, SigningKey, VerifyingKey};
+use p521::{PublicKey, SecretKey};
+use rand_core::CryptoRngCore;
+use sha2::{Digest, Sha512};
+
+use super::core::{CoreSigner, CoreSigningKey, CoreVerifier, CoreVerifyingKey};
+use super::Error;
+
+/// An ES512 (RFC 7518 Section 3.4) signing state.
+pub struct Es512Signer {
+    key: SigningKey,
+    sha: Sha512,
+}
+
+impl Update for Es512Signer {
+    type Error = Infallible;
+
+    fn update(&mut self, chunk: impl AsRef<[u8]>) -> Result<(), Self::Error> {
+        Digest::update(&mut self.sha, chunk);
+        Ok(())
+    }
+}
+
+impl CoreSigner for Es512Signer {
+    type FinishError = Error;
+
+    fn finish(self, mut rng: impl CryptoRngCore) -> Result<Vec<u8>, Self::FinishError> {
+        // The p521 crate does not implement RFC 6979, so signing is randomized.
+        let signature: Signature = self
+            .key
+            .sign_prehash_with_rng(&mut rng, &self.sha.finalize())
+            .map_err(|_| Error::Signing)?;
+
+        Ok(signature.to_bytes().to_vec())
+    }
+}
+
+/// An ES512 (RFC 7518 Section 3.4) verification state.
+pub struct Es512Verifier {
+    key: VerifyingKey,
+    sha: Sha512,
+}
+
+impl Update for Es512Verifier {
+    type Error = Infallible;
+
+    fn update(&mut self, chunk: impl AsRef<[u8]>) -> Result<(), Self::Error> {
+        Digest::update(&mut self.sha, chunk);
+        Ok(())
+    }
+}
+
+impl Es512Verifier {
+    fn new(key: &PublicKey, alg: Signing) -> Result<Self, Error> {
+        match alg {
+            Es512 => Ok(Self {
+                key: VerifyingKey::from_affine(*key.as_affine()).map_err(|_| Error::Invalid)?,
+                sha: Sha512::new(),
+            }),
+
+            _ => Err(crypto::Error::AlgMismatch.into()),
+        }
+    }
+}
+
+impl CoreVerifier for Es512Verifier {
+    type FinishError = Error;
+
+    fn finish(self, signature: &[u8]) -> Result<(), Self::FinishError> {
+        // The signature must be the fixed-width R || S encoding, not DER.
+        let signature = Signature::from_slice(signature).map_err(|_| Error::Invalid)?;
+
+        self.key
+            .verify_prehash(&self.sha.finalize(), &signature)
+            .map_err(|_| Error::Invalid)
+    }
+}
+
+impl<'a> CoreSigningKey<'a> for SecretKey {
+    type StartError = Error;
+    type Finish = Es512Signer;
+
+    fn sign(&'a self, alg: Signing) -> Result<Self::Finish, Self::StartError> {
+        match alg {
+            Es512 => Ok(Es512Signer {
+                key: SigningKey::from_bytes(&self.to_bytes()).map_err(|_| Error::Invalid)?,
+                sha: Sha512::new(),
+            }),
+
+            _ => Err(crypto::Error::AlgMismatch.into()),
+        }
+    }
+}
+
+impl<'a> CoreVerifyingKey<'a> for SecretKey {
+    type StartError = Error;
+    type Finish = Es512Verifier;
+
+    fn verify(&'a self, alg: Signing) -> Result<Self::Finish, Self::StartError> {
+        Es512Verifier::new(&self.public_key(), alg)
+    }
+}
+
+impl<'a> CoreVerifyingKey<'a> for PublicKey {
+    type StartError = Error;
+    type Finish = Es512Verifier;
+
+    fn verify(&'a self, alg: Signing) -> Result<Self::Finish, Self::StartError> {
+        Es512Verifier::new(self, alg)
+    }
+}
@@ -0,0 +1,137 @@
+// SPDX-FileCopyrightText: 2022 Profian Inc. <opensource@profian.com>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use jose_jwa::Signing;
+use serde_json::{Map, Value};
+
+use super::{algorithm, Error};
+use crate::Signature;
+
+/// A verification policy that pins the accepted algorithms.
+///
+/// Pinning the algorithms prevents an attacker from choosing the algorithm
+/// with which a key is used, for example by having an RSA public key used as
+/// an HMAC secret. Unsecured signatures (`"alg": "none"`) are rejected unless
+/// explicitly allowed with [`Policy::unsecured`], and even then are only
+/// accepted by the `unsecured` methods, such as [`Flattened::unsecured`].
+///
+/// [`Flattened::unsecured`]: crate::Flattened::unsecured
+///
+/// ```
+/// # use jose_jwa::Signing;
+/// # use jose_jws::crypto::Policy;
+/// let policy = Policy::new([Signing::Es256, Signing::EdDsa]);
+/// assert!(policy.allows(Signing::Es256));
+/// assert!(!policy.allows(Signing::Hs256));
+/// assert!(!policy.allows(Signing::Null));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Policy {
+    algs: Vec<Signing>,
+    null: bool,
+    crit: Vec<String>,
+}
+
+impl Policy {
+    /// Creates a policy accepting only the specified algorithms.
+    pub fn new(algs: impl IntoIterator<Item = Signing>) -> Self {
+        Self {
+            algs: algs.into_iter().filter(|a| *a != Signing::Null).collect(),
+            null: false,
+            crit: Vec::new(),
+        }
+    }
+
+    /// Sets whether unsecured signatures are accepted.
+    ///
+    /// An unsecured signature is accepted only if its signature is empty, and
+    /// only by the `unsecured` methods, which return an
+    /// [`Unsecured`](super::Unsecured) rather than a
+    /// [`Verified`](super::Verified) payload.
+    pub fn unsecured(mut self, allow: bool) -> Self {
+        self.null = allow;
+        self
+    }
+
+    /// Declares that the application understands a critical extension.
+    ///
+    /// Signatures listing an extension in `crit` are only accepted if the
+    /// extension has been declared. The `b64` extension (RFC 7797) is
+    /// implemented by this crate and is always understood.
+    pub fn critical(mut self, name: impl Into<String>) -> Self {
+        self.crit.push(name.into());
+        self
+    }
+
+    /// Returns whether the policy accepts the algorithm.
+    pub fn allows(&self, alg: Signing) -> bool {
+        match alg {
+            Signing::Null => self.null,
+            alg => self.algs.contains(&alg),
+        }
+    }
+
+    /// Checks the algorithm and critical extensions of a signature against
+    /// the policy.
+    pub fn check(&self, sig: &Signature) -> Result<Signing, Error> {
+        let alg = algorithm(sig.protected.as_deref(), sig.header.as_ref())?;
+
+        match alg {
+            Signing::Null if !self.null => return Err(Error::Unsecured),
+            alg if !self.allows(alg) => return Err(Error::Disallowed),
+            _ => (),
+        }
+
+        critical(sig, &self.crit)?;
+        Ok(alg)
+    }
+}
+
+/// The header parameters registered by RFC 7515 Section 4.1.
+const REGISTERED: &[&str] = &[
+    "alg", "jku", "jwk", "kid", "x5u", "x5c", "x5t", "x5t#S256", "typ", "cty", "crit",
+];
+
+/// The extensions implemented by this crate.
+const UNDERSTOOD: &[&str] = &["b64"];
+
+/// Checks the `crit` header parameter (RFC 7515 Section 4.1.11).
+///
+/// This includes the requirement that `b64` be critical when present (RFC
+/// 7797 Section 6).
+pub(crate) fn critical(sig: &Signature, understood: &[String]) -> Result<(), Error> {
+    let prot = match sig.protected.as_ref() {
+        Some(prot) => prot,
+        None => return Ok(()),
+    };
+
+    let crit = match prot.crit.as_deref() {
+        Some([]) => return Err(Error::Critical),
+        Some(crit) => crit,
+        None => &[],
+    };
+
+    let all: Map<String, Value> = serde_json::from_slice(prot.as_ref())?;
+    if all.contains_key("b64") && !crit.iter().any(|c| c == "b64") {
+        return Err(Error::Critical);
+    }
+
+    for name in crit {
+        if REGISTERED.contains(&name.as_str()) {
+            return Err(Error::Critical);
+        }
+
+        if !UNDERSTOOD.contains(&name.as_str()) && !understood.contains(name) {
+            return Err(Error::Critical);
+        }
+
+        if !all.contains_key(name) {
+            return Err(Error::Critical);
+        }
+    }
+
+    Ok(())
+}
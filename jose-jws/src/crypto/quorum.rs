@@ -0,0 +1,206 @@
+// SPDX-FileCopyrightText: 2022 Profian Inc. <opensource@profian.com>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+#![cfg(any(
+    feature = "ed25519",
+    feature = "hmac",
+    feature = "k256",
+    feature = "p256",
+    feature = "p384",
+    feature = "p521",
+    feature = "rsa"
+))]
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use jose_jwa::{Algorithm, Signing};
+use jose_jwk::{Jwk, JwkSet, Operations};
+
+use super::verify::verify;
+use super::{algorithm, set, Error, Policy};
+use crate::{General, Signature};
+
+/// A requirement on the signatures of a [`General`] JWS.
+///
+/// Every requirement implies that at least one signature verified.
+/// Unsecured signatures never count towards a requirement.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum Quorum {
+    /// At least one signature must verify.
+    Any,
+
+    /// Every signature must verify.
+    All,
+
+    /// Signatures from at least this many keys with distinct key material
+    /// must verify.
+    Threshold(usize),
+
+    /// For each key identifier, a signature must verify with a key that has
+    /// that identifier. The keys of different identifiers must have distinct
+    /// key material.
+    Kids(Vec<String>),
+}
+
+/// The outcome of verifying each signature of a [`General`] JWS.
+///
+/// This is obtained from [`General::report`].
+#[derive(Debug)]
+pub struct Report<'a> {
+    outcomes: Vec<Result<&'a Jwk, Error>>,
+}
+
+impl<'a> Report<'a> {
+    /// The outcome for each signature, in order.
+    ///
+    /// A signature that verified yields the key that verified it.
+    pub fn outcomes(&self) -> &[Result<&'a Jwk, Error>] {
+        &self.outcomes
+    }
+
+    /// The indices of the signatures that verified.
+    pub fn passed(&self) -> impl Iterator<Item = usize> + '_ {
+        self.outcomes
+            .iter()
+            .enumerate()
+            .filter_map(|(i, o)| o.as_ref().ok().map(|_| i))
+    }
+
+    /// The indices of the signatures that failed, with the reason.
+    pub fn failed(&self) -> impl Iterator<Item = (usize, &Error)> + '_ {
+        self.outcomes
+            .iter()
+            .enumerate()
+            .filter_map(|(i, o)| o.as_ref().err().map(|e| (i, e)))
+    }
+
+    /// Returns whether the signatures satisfy the requirement.
+    ///
+    /// Keys are told apart by their public key material, so a key listed in
+    /// the set under several key identifiers counts once, even if only some
+    /// of its entries include the private key.
+    pub fn satisfies(&self, quorum: &Quorum) -> bool {
+        let verified: Vec<&Jwk> = self.outcomes.iter().flatten().copied().collect();
+
+        let mut keys: Vec<&Jwk> = Vec::new();
+        for key in &verified {
+            if !keys.iter().any(|k| same(&k.key, &key.key)) {
+                keys.push(key);
+            }
+        }
+
+        if keys.is_empty() {
+            return false;
+        }
+
+        match quorum {
+            Quorum::Any => true,
+            Quorum::All => self.outcomes.iter().all(Result::is_ok),
+            Quorum::Threshold(n) => keys.len() >= *n,
+            Quorum::Kids(kids) => {
+                // Each key identifier needs its own key material, so that one
+                // signer cannot stand in for several.
+                let mut used: Vec<&Jwk> = Vec::new();
+                kids.iter().all(|kid| {
+                    let key = match verified.iter().find(|k| k.prm.kid.as_ref() == Some(kid)) {
+                        Some(key) => key,
+                        None => return false,
+                    };
+
+                    match used.iter().find(|u| same(&u.key, &key.key)) {
+                        Some(u) => u.prm.kid == key.prm.kid,
+                        None => {
+                            used.push(key);
+                            true
+                        }
+                    }
+                })
+            }
+        }
+    }
+
+    /// Checks that the signatures satisfy the requirement.
+    pub fn check(&self, quorum: &Quorum) -> Result<(), Error> {
+        match self.satisfies(quorum) {
+            true => Ok(()),
+            false => Err(Error::Quorum),
+        }
+    }
+}
+
+/// Returns whether the keys have the same key material.
+///
+/// Only the public parts of asymmetric keys are compared, so a private key
+/// matches its public key.
+pub(super) fn same(a: &jose_jwk::Key, b: &jose_jwk::Key) -> bool {
+    use jose_jwk::Key::*;
+
+    match (a, b) {
+        (Ec(a), Ec(b)) => a.crv == b.crv && a.x == b.x && a.y == b.y,
+        (Rsa(a), Rsa(b)) => a.n == b.n && a.e == b.e,
+        (Okp(a), Okp(b)) => a.crv == b.crv && a.x == b.x,
+        (Oct(a), Oct(b)) => a.k == b.k,
+        _ => false,
+    }
+}
+
+/// Verifies the signature with the first candidate key of the set that
+/// verifies it.
+///
+/// Candidates are selected as for the [`VerifyingKey`](super::VerifyingKey)
+/// implementation of [`JwkSet`].
+fn outcome<'a>(
+    set: &'a JwkSet,
+    policy: Option<&Policy>,
+    payload: &[u8],
+    sig: &'a Signature,
+) -> Result<&'a Jwk, Error> {
+    let alg = match algorithm(sig.protected.as_deref(), sig.header.as_ref())? {
+        Signing::Null => return Err(Error::Unsecured),
+        alg => Algorithm::Signing(alg),
+    };
+
+    let kid = set::kid(sig);
+    let mut err = match set::pinned(set, kid, &alg) {
+        true => Error::AlgMismatch,
+        false => Error::NoKey,
+    };
+
+    for jwk in set.select(kid, &alg, Operations::Verify) {
+        match verify(jwk, policy, payload, sig) {
+            Ok(..) => return Ok(jwk),
+            Err(Error::Invalid) => err = Error::Invalid,
+            Err(Error::Key(..) | Error::NoKey | Error::AlgMismatch) => (),
+            Err(e) => return Err(e),
+        }
+    }
+
+    Err(err)
+}
+
+impl<P: AsRef<[u8]>> General<P> {
+    /// Verifies each signature with the keys of the set, reporting which
+    /// signatures verified and with which key.
+    ///
+    /// If specified, the policy applies to every signature. The report can
+    /// then be checked against a [`Quorum`]. Every signature fails with
+    /// [`Error::Detached`] if the JWS does not carry its payload.
+    pub fn report<'a>(&'a self, set: &'a JwkSet, policy: Option<&Policy>) -> Report<'a> {
+        let payload = self.payload.as_ref();
+        let mixed = self.b64().is_none();
+
+        let outcomes = self
+            .signatures
+            .iter()
+            .map(|sig| match (mixed, payload) {
+                (true, _) => Err(Error::MixedB64),
+                (false, None) => Err(Error::Detached),
+                (false, Some(payload)) => outcome(set, policy, payload.as_ref(), sig),
+            })
+            .collect();
+
+        Report { outcomes }
+    }
+}
@@ -0,0 +1,172 @@
+// SPDX-FileCopyrightText: 2022 Profian Inc. <opensource@profian.com>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+#![cfg(feature = "rsa")]
+
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+use core::convert::Infallible;
+
+use jose_b64::stream::Update;
+use jose_jwa::{Signing, Signing::*};
+use jose_jwk::crypto;
+use rand_core::CryptoRngCore;
+use rsa::{Pkcs1v15Sign, Pss, RsaPrivateKey, RsaPublicKey};
+use sha2::{Digest, Sha256, Sha384, Sha512};
+
+use super::core::{CoreSigner, CoreSigningKey, CoreVerifier, CoreVerifyingKey};
+use super::Error;
+
+/// The running digest of an RSA (RFC 7518 Sections 3.3 and 3.5) signature.
+enum Hash {
+    Rs256(Sha256),
+    Rs384(Sha384),
+    Rs512(Sha512),
+    Ps256(Sha256),
+    Ps384(Sha384),
+    Ps512(Sha512),
+}
+
+impl Hash {
+    fn new(alg: Signing) -> Result<Self, Error> {
+        Ok(match alg {
+            Rs256 => Self::Rs256(Sha256::new()),
+            Rs384 => Self::Rs384(Sha384::new()),
+            Rs512 => Self::Rs512(Sha512::new()),
+            Ps256 => Self::Ps256(Sha256::new()),
+            Ps384 => Self::Ps384(Sha384::new()),
+            Ps512 => Self::Ps512(Sha512::new()),
+            _ => return Err(crypto::Error::AlgMismatch.into()),
+        })
+    }
+
+    fn update(&mut self, chunk: &[u8]) {
+        match self {
+            Self::Rs256(sha) | Self::Ps256(sha) => sha.update(chunk),
+            Self::Rs384(sha) | Self::Ps384(sha) => sha.update(chunk),
+            Self::Rs512(sha) | Self::Ps512(sha) => sha.update(chunk),
+        }
+    }
+}
+
+/// An RSA signing state.
+pub struct RsaSigner<'a> {
+    key: &'a RsaPrivateKey,
+    hash: Hash,
+}
+
+impl Update for RsaSigner<'_> {
+    type Error = Infallible;
+
+    fn update(&mut self, chunk: impl AsRef<[u8]>) -> Result<(), Self::Error> {
+        self.hash.update(chunk.as_ref());
+        Ok(())
+    }
+}
+
+impl CoreSigner for RsaSigner<'_> {
+    type FinishError = Error;
+
+    fn finish(self, mut rng: impl CryptoRngCore) -> Result<Vec<u8>, Self::FinishError> {
+        let rng = &mut rng;
+        let key = self.key;
+
+        // The rng blinds the private key operation and, for PSS, provides
+        // the salt (whose length equals the digest size).
+        match self.hash {
+            Hash::Rs256(sha) => {
+                key.sign_with_rng(rng, Pkcs1v15Sign::new::<Sha256>(), &sha.finalize())
+            }
+            Hash::Rs384(sha) => {
+                key.sign_with_rng(rng, Pkcs1v15Sign::new::<Sha384>(), &sha.finalize())
+            }
+            Hash::Rs512(sha) => {
+                key.sign_with_rng(rng, Pkcs1v15Sign::new::<Sha512>(), &sha.finalize())
+            }
+            Hash::Ps256(sha) => key.sign_with_rng(rng, Pss::new::<Sha256>(), &sha.finalize()),
+            Hash::Ps384(sha) => key.sign_with_rng(rng, Pss::new::<Sha384>(), &sha.finalize()),
+            Hash::Ps512(sha) => key.sign_with_rng(rng, Pss::new::<Sha512>(), &sha.finalize()),
+        }
+        .map_err(|_| Error::Signing)
+    }
+}
+
+/// An RSA verification state.
+pub struct RsaVerifier<'a> {
+    key: Cow<'a, RsaPublicKey>,
+    hash: Hash,
+}
+
+impl RsaVerifier<'_> {
+    /// Detaches the state from the key it was started with.
+    pub(super) fn into_owned(self) -> RsaVerifier<'static> {
+        RsaVerifier {
+            key: Cow::Owned(self.key.into_owned()),
+            hash: self.hash,
+        }
+    }
+}
+
+impl Update for RsaVerifier<'_> {
+    type Error = Infallible;
+
+    fn update(&mut self, chunk: impl AsRef<[u8]>) -> Result<(), Self::Error> {
+        self.hash.update(chunk.as_ref());
+        Ok(())
+    }
+}
+
+impl CoreVerifier for RsaVerifier<'_> {
+    type FinishError = Error;
+
+    fn finish(self, signature: &[u8]) -> Result<(), Self::FinishError> {
+        let key = &*self.key;
+        let sig = signature;
+
+        match self.hash {
+            Hash::Rs256(sha) => key.verify(Pkcs1v15Sign::new::<Sha256>(), &sha.finalize(), sig),
+            Hash::Rs384(sha) => key.verify(Pkcs1v15Sign::new::<Sha384>(), &sha.finalize(), sig),
+            Hash::Rs512(sha) => key.verify(Pkcs1v15Sign::new::<Sha512>(), &sha.finalize(), sig),
+            Hash::Ps256(sha) => key.verify(Pss::new::<Sha256>(), &sha.finalize(), sig),
+            Hash::Ps384(sha) => key.verify(Pss::new::<Sha384>(), &sha.finalize(), sig),
+            Hash::Ps512(sha) => key.verify(Pss::new::<Sha512>(), &sha.finalize(), sig),
+        }
+        .map_err(|_| Error::Invalid)
+    }
+}
+
+impl<'a> CoreSigningKey<'a> for RsaPrivateKey {
+    type StartError = Error;
+    type Finish = RsaSigner<'a>;
+
+    fn sign(&'a self, alg: Signing) -> Result<Self::Finish, Self::StartError> {
+        Ok(RsaSigner {
+            hash: Hash::new(alg)?,
+            key: self,
+        })
+    }
+}
+
+impl<'a> CoreVerifyingKey<'a> for RsaPrivateKey {
+    type StartError = Error;
+    type Finish = RsaVerifier<'a>;
+
+    fn verify(&'a self, alg: Signing) -> Result<Self::Finish, Self::StartError> {
+        Ok(RsaVerifier {
+            hash: Hash::new(alg)?,
+            key: Cow::Borrowed(self.as_ref()),
+        })
+    }
+}
+
+impl<'a> CoreVerifyingKey<'a> for RsaPublicKey {
+    type StartError = Error;
+    type Finish = RsaVerifier<'a>;
+
+    fn verify(&'a self, alg: Signing) -> Result<Self::Finish, Self::StartError> {
+        Ok(RsaVerifier {
+            hash: Hash::new(alg)?,
+            key: Cow::Borrowed(self),
+        })
+    }
+}
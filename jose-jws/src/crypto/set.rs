@@ -0,0 +1,90 @@
+// SPDX-FileCopyrightText: 2022 Profian Inc. <opensource@profian.com>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+#![cfg(any(
+    feature = "ed25519",
+    feature = "hmac",
+    feature = "k256",
+    feature = "p256",
+    feature = "p384",
+    feature = "p521",
+    feature = "rsa"
+))]
+
+use alloc::vec::Vec;
+
+use jose_jwa::Algorithm;
+use jose_jwk::{crypto::Key, Jwk, JwkSet, Operations};
+
+use super::key::KeyVerifier;
+use super::{algorithm, start, Error, VerifyingKey, VerifyingState};
+use crate::Signature;
+
+/// Verifies with the key if its parameters permit it.
+///
+/// If the key has an `alg` parameter, it must match the signature's `alg`.
+/// The `use` and `key_ops` parameters, if present, must permit verification.
+impl<'a> VerifyingKey<'a, &'a Signature> for Jwk {
+    type StartError = Error;
+    type Verifier = VerifyingState<'a, KeyVerifier<'static>>;
+
+    fn verify(&'a self, sig: &'a Signature) -> Result<Self::Verifier, Self::StartError> {
+        let alg = algorithm(sig.protected.as_deref(), sig.header.as_ref())?;
+        let alg = Algorithm::Signing(alg);
+
+        if self.prm.alg.as_ref().map_or(false, |a| *a != alg) {
+            return Err(Error::AlgMismatch);
+        }
+
+        if !self.allows(&alg, Operations::Verify) {
+            return Err(Error::NoKey);
+        }
+
+        start(&Key::try_from(&self.key)?, sig, KeyVerifier::into_owned)
+    }
+}
+
+/// Verifies with the keys of the set that match the signature.
+///
+/// The candidates are the keys selected by [`JwkSet::select`] for verifying
+/// with the algorithm of the signature: if the signature names a `kid`, only
+/// the keys with that key identifier are candidates. The signature is valid
+/// if any of them verifies it.
+impl<'a> VerifyingKey<'a, &'a Signature> for JwkSet {
+    type StartError = Error;
+    type Verifier = Vec<VerifyingState<'a, KeyVerifier<'static>>>;
+
+    fn verify(&'a self, sig: &'a Signature) -> Result<Self::Verifier, Self::StartError> {
+        let alg = algorithm(sig.protected.as_deref(), sig.header.as_ref())?;
+        let alg = Algorithm::Signing(alg);
+        let kid = kid(sig);
+
+        let all: Vec<_> = self
+            .select(kid, &alg, Operations::Verify)
+            .filter_map(|jwk| jwk.verify(sig).ok())
+            .collect();
+
+        match (all.is_empty(), pinned(self, kid, &alg)) {
+            (false, _) => Ok(all),
+            (true, true) => Err(Error::AlgMismatch),
+            (true, false) => Err(Error::NoKey),
+        }
+    }
+}
+
+/// Returns the key identifier of the signature, preferring the protected
+/// header.
+pub(super) fn kid(sig: &Signature) -> Option<&str> {
+    let kid = sig.protected.as_ref().and_then(|p| p.oth.kid.as_deref());
+    kid.or_else(|| sig.header.as_ref().and_then(|h| h.kid.as_deref()))
+}
+
+/// Returns whether a key that would otherwise be a candidate is pinned to an
+/// algorithm other than `alg`, which is worth reporting when no key matches.
+pub(super) fn pinned(set: &JwkSet, kid: Option<&str>, alg: &Algorithm) -> bool {
+    let pins = set.keys.iter().filter_map(|jwk| jwk.prm.alg.as_ref());
+    pins.filter(|pin| *pin != alg).any(|pin| {
+        set.select(kid, pin, Operations::Verify)
+            .any(|jwk| jwk.prm.alg.as_ref() == Some(pin))
+    })
+}
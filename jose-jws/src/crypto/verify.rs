@@ -0,0 +1,545 @@
+// SPDX-FileCopyrightText: 2022 Profian Inc. <opensource@profian.com>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use jose_b64::serde::Bytes;
+use jose_b64::stream::Update;
+use jose_jwa::Signing;
+
+use super::policy::critical;
+use super::{attached, unattached, Error, Policy, Verifier, VerifyingKey};
+use crate::{Flattened, General, Jws, Protected, Signature};
+
+/// A payload whose signature has been verified.
+///
+/// This can only be obtained from a successful verification, so its contents
+/// are known to be authentic. For a typed payload, such as
+/// [`Json`](jose_b64::serde::Json), the value is the one parsed from the
+/// verified bytes, borrowed from the JWS.
+#[derive(Clone, Debug)]
+pub struct Verified<'a, P = Bytes> {
+    payload: &'a P,
+    header: Protected,
+}
+
+impl<'a, P: AsRef<[u8]>> Verified<'a, P> {
+    /// The verified payload bytes.
+    pub fn payload(&self) -> &'a [u8] {
+        self.payload.as_ref()
+    }
+
+    /// Consumes the value, returning a copy of the verified payload bytes.
+    pub fn into_payload(self) -> Vec<u8> {
+        self.payload.as_ref().to_vec()
+    }
+}
+
+impl<'a, P> Verified<'a, P> {
+    /// The verified payload.
+    pub fn value(&self) -> &'a P {
+        self.payload
+    }
+
+    /// The header of the verified signature.
+    ///
+    /// This is the protected header merged with the unprotected header. Only
+    /// the parameters that came from the protected header are covered by the
+    /// signature.
+    pub fn header(&self) -> &Protected {
+        &self.header
+    }
+}
+
+/// A payload accepted without a signature.
+///
+/// This is obtained from [`Flattened::unsecured`] for an unsecured JWS
+/// (`"alg": "none"`) allowed by the policy. Unlike [`Verified`], nothing
+/// vouches for its contents.
+#[derive(Clone, Debug)]
+pub struct Unsecured<'a, P = Bytes> {
+    payload: Option<&'a P>,
+    header: Protected,
+}
+
+impl<'a, P: AsRef<[u8]>> Unsecured<'a, P> {
+    /// The unsecured payload bytes.
+    pub fn payload(&self) -> &'a [u8] {
+        self.payload.map_or(&[], AsRef::as_ref)
+    }
+}
+
+impl<'a, P> Unsecured<'a, P> {
+    /// The unsecured payload, if the JWS carried one.
+    pub fn value(&self) -> Option<&'a P> {
+        self.payload
+    }
+
+    /// The header of the unsecured signature.
+    ///
+    /// This is the protected header merged with the unprotected header.
+    pub fn header(&self) -> &Protected {
+        &self.header
+    }
+}
+
+/// A verification state for a detached payload.
+///
+/// The payload is supplied in chunks with [`Update::update`], without being
+/// copied, except by EdDSA keys, which must buffer it. This is obtained from [`Flattened::detached`], or from
+/// [`General::detached`], in which case every signature that the key can
+/// check is verified at once.
+pub struct Detached<V> {
+    states: Vec<(V, Protected)>,
+}
+
+impl<V: Update> Update for Detached<V>
+where
+    Error: From<V::Error>,
+{
+    type Error = Error;
+
+    fn update(&mut self, chunk: impl AsRef<[u8]>) -> Result<(), Self::Error> {
+        for (ver, ..) in &mut self.states {
+            ver.update(chunk.as_ref())?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a, V: Verifier<'a, FinishError = Error>> Detached<V>
+where
+    Error: From<V::Error>,
+{
+    /// Finishes verification, returning the header of the verified signature.
+    ///
+    /// See [`Verified::header`] for the contents of the header, and
+    /// [`General::verify`] for how multiple signatures are handled.
+    pub fn finish(self) -> Result<Protected, Error> {
+        let mut err = None;
+
+        for (ver, header) in self.states {
+            match ver.finish() {
+                Ok(()) => return Ok(header),
+                Err(Error::Invalid) => err = Some(Error::Invalid),
+                Err(e) => {
+                    err.get_or_insert(e);
+                }
+            }
+        }
+
+        Err(err.unwrap_or_default())
+    }
+}
+
+pub(super) fn start<'a, K>(
+    key: &'a K,
+    policy: Option<&Policy>,
+    sig: &'a Signature,
+) -> Result<Detached<K::Verifier>, Error>
+where
+    K: VerifyingKey<'a, &'a Signature, StartError = Error>,
+{
+    sig.validate()?;
+
+    let alg = match policy {
+        Some(policy) => Some(policy.check(sig)?),
+        None => {
+            critical(sig, &[])?;
+            None
+        }
+    };
+
+    // Even if the policy allows it, an unsecured signature is only accepted
+    // by `unsecured`, so that it is never mistaken for a verified one.
+    if alg == Some(Signing::Null) {
+        return Err(Error::Unsecured);
+    }
+
+    Ok(Detached {
+        states: vec![(key.verify(sig)?, sig.header())],
+    })
+}
+
+/// Accepts an unsecured signature allowed by the policy.
+fn accept(policy: &Policy, sig: &Signature) -> Result<Protected, Error> {
+    sig.validate()?;
+
+    match policy.check(sig)? {
+        // An unsecured signature must be empty (RFC 7518 Section 3.6).
+        Signing::Null if !sig.signature.is_empty() => Err(Error::Invalid),
+        Signing::Null => Ok(sig.header()),
+        _ => Err(Error::NoKey),
+    }
+}
+
+pub(super) fn verify<'a, K>(
+    key: &'a K,
+    policy: Option<&Policy>,
+    payload: &[u8],
+    sig: &'a Signature,
+) -> Result<Protected, Error>
+where
+    K: VerifyingKey<'a, &'a Signature, StartError = Error>,
+    K::Verifier: Verifier<'a, FinishError = Error>,
+    Error: From<<K::Verifier as Update>::Error>,
+{
+    let mut det = start(key, policy, sig)?;
+    det.update(payload)?;
+    det.finish()
+}
+
+impl<P: AsRef<[u8]>> Flattened<P> {
+    /// Verifies the signature with the specified key.
+    ///
+    /// This fails with [`Error::Detached`] if the JWS does not carry its
+    /// payload; use [`Flattened::verify_detached`] for that case.
+    pub fn verify<'s: 'a, 'a, K>(&'s self, key: &'a K) -> Result<Verified<'s, P>, Error>
+    where
+        K: VerifyingKey<'a, &'a Signature, StartError = Error>,
+        K::Verifier: Verifier<'a, FinishError = Error>,
+        Error: From<<K::Verifier as Update>::Error>,
+    {
+        let payload = attached(self.payload.as_ref())?;
+        let header = verify(key, None, payload.as_ref(), &self.signature)?;
+        Ok(Verified { payload, header })
+    }
+
+    /// Verifies the signature with the specified key and policy.
+    pub fn verify_with<'s: 'a, 'a, K>(
+        &'s self,
+        key: &'a K,
+        policy: &Policy,
+    ) -> Result<Verified<'s, P>, Error>
+    where
+        K: VerifyingKey<'a, &'a Signature, StartError = Error>,
+        K::Verifier: Verifier<'a, FinishError = Error>,
+        Error: From<<K::Verifier as Update>::Error>,
+    {
+        let payload = attached(self.payload.as_ref())?;
+        let header = verify(key, Some(policy), payload.as_ref(), &self.signature)?;
+        Ok(Verified { payload, header })
+    }
+
+    /// Accepts an unsecured JWS (`"alg": "none"`).
+    ///
+    /// The policy must allow unsecured signatures with
+    /// [`Policy::unsecured`]. The verification methods never accept them,
+    /// and this method accepts nothing else: a signed JWS fails with
+    /// [`Error::NoKey`].
+    pub fn unsecured(&self, policy: &Policy) -> Result<Unsecured<'_, P>, Error> {
+        let header = accept(policy, &self.signature)?;
+        Ok(Unsecured {
+            payload: self.payload.as_ref(),
+            header,
+        })
+    }
+
+    /// Verifies the signature over a detached payload with the specified key.
+    ///
+    /// The payload is supplied by the caller rather than taken from the
+    /// signature (RFC 7515 Appendix F). On success, the header of the verified
+    /// signature is returned. A JWS that carries its own payload fails with
+    /// [`Error::Attached`].
+    pub fn verify_detached<'a, K>(&'a self, key: &'a K, payload: &[u8]) -> Result<Protected, Error>
+    where
+        K: VerifyingKey<'a, &'a Signature, StartError = Error>,
+        K::Verifier: Verifier<'a, FinishError = Error>,
+        Error: From<<K::Verifier as Update>::Error>,
+    {
+        unattached(self.payload.as_ref())?;
+        verify(key, None, payload, &self.signature)
+    }
+
+    /// Verifies the signature over a detached payload with the specified key
+    /// and policy.
+    pub fn verify_detached_with<'a, K>(
+        &'a self,
+        key: &'a K,
+        policy: &Policy,
+        payload: &[u8],
+    ) -> Result<Protected, Error>
+    where
+        K: VerifyingKey<'a, &'a Signature, StartError = Error>,
+        K::Verifier: Verifier<'a, FinishError = Error>,
+        Error: From<<K::Verifier as Update>::Error>,
+    {
+        unattached(self.payload.as_ref())?;
+        verify(key, Some(policy), payload, &self.signature)
+    }
+
+    /// Begins verifying the signature over a detached payload, which is then
+    /// supplied in chunks.
+    ///
+    /// If specified, the policy is checked before verification begins. To
+    /// sign a detached payload in chunks, use [`Flattened::sign_detached`].
+    /// As in [`Flattened::verify_detached`], a JWS that carries its own
+    /// payload fails with [`Error::Attached`].
+    pub fn detached<'a, K>(
+        &'a self,
+        key: &'a K,
+        policy: Option<&Policy>,
+    ) -> Result<Detached<K::Verifier>, Error>
+    where
+        K: VerifyingKey<'a, &'a Signature, StartError = Error>,
+    {
+        unattached(self.payload.as_ref())?;
+        start(key, policy, &self.signature)
+    }
+}
+
+impl<P: AsRef<[u8]>> General<P> {
+    fn verify_any<'a, K>(
+        &'a self,
+        key: &'a K,
+        policy: Option<&Policy>,
+        payload: &[u8],
+    ) -> Result<Protected, Error>
+    where
+        K: VerifyingKey<'a, &'a Signature, StartError = Error>,
+        K::Verifier: Verifier<'a, FinishError = Error>,
+        Error: From<<K::Verifier as Update>::Error>,
+    {
+        if self.b64().is_none() {
+            return Err(Error::MixedB64);
+        }
+
+        let mut err = None;
+
+        for sig in &self.signatures {
+            match verify(key, policy, payload, sig) {
+                Ok(header) => return Ok(header),
+                Err(Error::Invalid) => err = Some(Error::Invalid),
+                Err(e) => {
+                    err.get_or_insert(e);
+                }
+            }
+        }
+
+        Err(err.unwrap_or_default())
+    }
+
+    /// Verifies the signatures with the specified key.
+    ///
+    /// This succeeds with the first signature that the key verifies. If none
+    /// does, a signature that failed verification is reported in preference
+    /// to the signatures that the key cannot check.
+    ///
+    /// This fails with [`Error::Detached`] if the JWS does not carry its
+    /// payload; use [`General::verify_detached`] for that case.
+    pub fn verify<'s: 'a, 'a, K>(&'s self, key: &'a K) -> Result<Verified<'s, P>, Error>
+    where
+        K: VerifyingKey<'a, &'a Signature, StartError = Error>,
+        K::Verifier: Verifier<'a, FinishError = Error>,
+        Error: From<<K::Verifier as Update>::Error>,
+    {
+        let payload = attached(self.payload.as_ref())?;
+        let header = self.verify_any(key, None, payload.as_ref())?;
+        Ok(Verified { payload, header })
+    }
+
+    /// Verifies the signatures with the specified key and policy.
+    ///
+    /// Signatures rejected by the policy are skipped as in [`General::verify`].
+    pub fn verify_with<'s: 'a, 'a, K>(
+        &'s self,
+        key: &'a K,
+        policy: &Policy,
+    ) -> Result<Verified<'s, P>, Error>
+    where
+        K: VerifyingKey<'a, &'a Signature, StartError = Error>,
+        K::Verifier: Verifier<'a, FinishError = Error>,
+        Error: From<<K::Verifier as Update>::Error>,
+    {
+        let payload = attached(self.payload.as_ref())?;
+        let header = self.verify_any(key, Some(policy), payload.as_ref())?;
+        Ok(Verified { payload, header })
+    }
+
+    /// Accepts the first unsecured signature (`"alg": "none"`).
+    ///
+    /// See [`Flattened::unsecured`].
+    pub fn unsecured(&self, policy: &Policy) -> Result<Unsecured<'_, P>, Error> {
+        let mut err = None;
+
+        for sig in &self.signatures {
+            match accept(policy, sig) {
+                Ok(header) => {
+                    return Ok(Unsecured {
+                        payload: self.payload.as_ref(),
+                        header,
+                    })
+                }
+                Err(e) => {
+                    err.get_or_insert(e);
+                }
+            }
+        }
+
+        Err(err.unwrap_or(Error::NoKey))
+    }
+
+    /// Verifies the signatures over a detached payload with the specified key.
+    ///
+    /// See [`Flattened::verify_detached`] and [`General::verify`].
+    pub fn verify_detached<'a, K>(&'a self, key: &'a K, payload: &[u8]) -> Result<Protected, Error>
+    where
+        K: VerifyingKey<'a, &'a Signature, StartError = Error>,
+        K::Verifier: Verifier<'a, FinishError = Error>,
+        Error: From<<K::Verifier as Update>::Error>,
+    {
+        unattached(self.payload.as_ref())?;
+        self.verify_any(key, None, payload)
+    }
+
+    /// Verifies the signatures over a detached payload with the specified key
+    /// and policy.
+    pub fn verify_detached_with<'a, K>(
+        &'a self,
+        key: &'a K,
+        policy: &Policy,
+        payload: &[u8],
+    ) -> Result<Protected, Error>
+    where
+        K: VerifyingKey<'a, &'a Signature, StartError = Error>,
+        K::Verifier: Verifier<'a, FinishError = Error>,
+        Error: From<<K::Verifier as Update>::Error>,
+    {
+        unattached(self.payload.as_ref())?;
+        self.verify_any(key, Some(policy), payload)
+    }
+
+    /// Begins verifying the signatures over a detached payload, which is then
+    /// supplied in chunks.
+    ///
+    /// Every signature that the key can check is verified over the same
+    /// chunks. See [`Flattened::detached`] and [`General::verify`].
+    pub fn detached<'a, K>(
+        &'a self,
+        key: &'a K,
+        policy: Option<&Policy>,
+    ) -> Result<Detached<K::Verifier>, Error>
+    where
+        K: VerifyingKey<'a, &'a Signature, StartError = Error>,
+    {
+        unattached(self.payload.as_ref())?;
+        if self.b64().is_none() {
+            return Err(Error::MixedB64);
+        }
+
+        let mut states = Vec::new();
+        let mut err = None;
+
+        for sig in &self.signatures {
+            match start(key, policy, sig) {
+                Ok(det) => states.extend(det.states),
+                Err(e) => {
+                    err.get_or_insert(e);
+                }
+            }
+        }
+
+        match states.is_empty() {
+            true => Err(err.unwrap_or_default()),
+            false => Ok(Detached { states }),
+        }
+    }
+}
+
+impl<P: AsRef<[u8]>> Jws<P> {
+    /// Verifies the signatures with the specified key.
+    ///
+    /// See [`General::verify`] for how multiple signatures are handled.
+    pub fn verify<'s: 'a, 'a, K>(&'s self, key: &'a K) -> Result<Verified<'s, P>, Error>
+    where
+        K: VerifyingKey<'a, &'a Signature, StartError = Error>,
+        K::Verifier: Verifier<'a, FinishError = Error>,
+        Error: From<<K::Verifier as Update>::Error>,
+    {
+        match self {
+            Jws::General(general) => general.verify(key),
+            Jws::Flattened(flattened) => flattened.verify(key),
+        }
+    }
+
+    /// Verifies the signatures with the specified key and policy.
+    pub fn verify_with<'s: 'a, 'a, K>(
+        &'s self,
+        key: &'a K,
+        policy: &Policy,
+    ) -> Result<Verified<'s, P>, Error>
+    where
+        K: VerifyingKey<'a, &'a Signature, StartError = Error>,
+        K::Verifier: Verifier<'a, FinishError = Error>,
+        Error: From<<K::Verifier as Update>::Error>,
+    {
+        match self {
+            Jws::General(general) => general.verify_with(key, policy),
+            Jws::Flattened(flattened) => flattened.verify_with(key, policy),
+        }
+    }
+
+    /// Verifies the signatures over a detached payload with the specified key.
+    ///
+    /// See [`Flattened::verify_detached`].
+    pub fn verify_detached<'a, K>(&'a self, key: &'a K, payload: &[u8]) -> Result<Protected, Error>
+    where
+        K: VerifyingKey<'a, &'a Signature, StartError = Error>,
+        K::Verifier: Verifier<'a, FinishError = Error>,
+        Error: From<<K::Verifier as Update>::Error>,
+    {
+        match self {
+            Jws::General(general) => general.verify_detached(key, payload),
+            Jws::Flattened(flattened) => flattened.verify_detached(key, payload),
+        }
+    }
+
+    /// Verifies the signatures over a detached payload with the specified key
+    /// and policy.
+    pub fn verify_detached_with<'a, K>(
+        &'a self,
+        key: &'a K,
+        policy: &Policy,
+        payload: &[u8],
+    ) -> Result<Protected, Error>
+    where
+        K: VerifyingKey<'a, &'a Signature, StartError = Error>,
+        K::Verifier: Verifier<'a, FinishError = Error>,
+        Error: From<<K::Verifier as Update>::Error>,
+    {
+        match self {
+            Jws::General(general) => general.verify_detached_with(key, policy, payload),
+            Jws::Flattened(flattened) => flattened.verify_detached_with(key, policy, payload),
+        }
+    }
+
+    /// Begins verifying the signatures over a detached payload, which is then
+    /// supplied in chunks.
+    ///
+    /// See [`Flattened::detached`] and [`General::detached`].
+    pub fn detached<'a, K>(
+        &'a self,
+        key: &'a K,
+        policy: Option<&Policy>,
+    ) -> Result<Detached<K::Verifier>, Error>
+    where
+        K: VerifyingKey<'a, &'a Signature, StartError = Error>,
+    {
+        match self {
+            Jws::General(general) => general.detached(key, policy),
+            Jws::Flattened(flattened) => flattened.detached(key, policy),
+        }
+    }
+
+    /// Accepts an unsecured signature (`"alg": "none"`).
+    ///
+    /// See [`Flattened::unsecured`].
+    pub fn unsecured(&self, policy: &Policy) -> Result<Unsecured<'_, P>, Error> {
+        match self {
+            Jws::General(general) => general.unsecured(policy),
+            Jws::Flattened(flattened) => flattened.unsecured(policy),
+        }
+    }
+}
@@ -3,12 +3,15 @@
 
 use alloc::vec::Vec;
 use alloc::{boxed::Box, string::String};
+use core::fmt;
 
 use jose_b64::base64ct::Base64;
 use jose_b64::serde::Bytes;
 use jose_jwa::Signing;
 use jose_jwk::{Jwk, Thumbprint};
-use serde::{Deserialize, Serialize};
+use serde::de::{IgnoredAny, MapAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{Map, Value};
 
 #[inline]
 fn b64_default() -> bool {
@@ -17,7 +20,7 @@ fn b64_default() -> bool {
 
 #[inline]
 fn b64_serialize(value: &bool) -> bool {
-    !value
+    *value
 }
 
 /// The JWS Protected Header
@@ -99,4 +102,128 @@ pub struct Unprotected {
     /// RFC 7517 Section 4.1.10
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub cty: Option<String>,
+
+    /// Extension parameters, such as those defined by other specifications.
+    ///
+    /// This collects the members that are not otherwise represented. It
+    /// should not contain the names of the other parameters.
+    #[serde(flatten)]
+    pub ext: Map<String, Value>,
+}
+
+impl Unprotected {
+    /// Fills in the parameters that are unset in `self` from `other`.
+    pub(crate) fn merge(&mut self, other: &Self) {
+        let other = other.clone();
+
+        self.alg = self.alg.or(other.alg);
+        self.jwk = self.jwk.take().or(other.jwk);
+        self.kid = self.kid.take().or(other.kid);
+        self.x5c = self.x5c.take().or(other.x5c);
+        self.x5t.s1 = self.x5t.s1.take().or(other.x5t.s1);
+        self.x5t.s256 = self.x5t.s256.take().or(other.x5t.s256);
+        self.typ = self.typ.take().or(other.typ);
+        self.cty = self.cty.take().or(other.cty);
+
+        for (name, value) in other.ext {
+            self.ext.entry(name).or_insert(value);
+        }
+
+        #[cfg(feature = "url")]
+        {
+            self.jku = self.jku.take().or(other.jku);
+            self.x5u = self.x5u.take().or(other.x5u);
+        }
+    }
+}
+
+/// An error in the JOSE header of a signature.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum HeaderError {
+    /// A parameter appears more than once in the protected header.
+    Duplicate(String),
+
+    /// A parameter appears in both the protected and unprotected headers
+    /// (RFC 7515 Section 7.2.1).
+    Overlap(String),
+
+    /// A header is not a valid JSON object.
+    Json(serde_json::Error),
+}
+
+impl fmt::Display for HeaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Duplicate(name) => write!(f, "duplicate header parameter: {name}"),
+            Self::Overlap(name) => write!(
+                f,
+                "header parameter is both protected and unprotected: {name}"
+            ),
+            Self::Json(e) => write!(f, "invalid header: {e}"),
+        }
+    }
+}
+
+impl From<serde_json::Error> for HeaderError {
+    fn from(value: serde_json::Error) -> Self {
+        Self::Json(value)
+    }
+}
+
+/// The member names of a JSON object and the first duplicate name, if any.
+//
+// This and `disjoint` mirror `jose-jwe/src/head.rs`: fix bugs in both.
+struct Names(Vec<String>, Option<String>);
+
+impl<'de> Deserialize<'de> for Names {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct NamesVisitor;
+
+        impl<'de> Visitor<'de> for NamesVisitor {
+            type Value = Names;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a JSON object")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Names, A::Error> {
+                let mut names = Vec::new();
+                let mut dup = None;
+
+                while let Some(name) = map.next_key::<String>()? {
+                    map.next_value::<IgnoredAny>()?;
+
+                    match names.contains(&name) {
+                        true => dup = dup.or(Some(name)),
+                        false => names.push(name),
+                    }
+                }
+
+                Ok(Names(names, dup))
+            }
+        }
+
+        deserializer.deserialize_map(NamesVisitor)
+    }
+}
+
+/// Checks that the protected header has no duplicate member names and that
+/// its parameters are disjoint from those of the unprotected header.
+pub(crate) fn disjoint(prot: Option<&[u8]>, head: Option<&Unprotected>) -> Result<(), HeaderError> {
+    let prot = match prot.map(serde_json::from_slice).transpose()? {
+        Some(Names(_, Some(dup))) => return Err(HeaderError::Duplicate(dup)),
+        Some(Names(names, None)) => names,
+        None => Vec::new(),
+    };
+
+    let head = match head.map(serde_json::to_value).transpose()? {
+        Some(Value::Object(head)) => head,
+        _ => return Ok(()),
+    };
+
+    match head.keys().find(|name| prot.contains(name)) {
+        Some(name) => Err(HeaderError::Overlap(name.clone())),
+        None => Ok(()),
+    }
 }
@@ -0,0 +1,155 @@
+// SPDX-FileCopyrightText: 2022 Profian Inc. <opensource@profian.com>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Display;
+
+use jose_b64::base64ct::{Base64UrlUnpadded, Encoding};
+use jose_b64::serde::Bytes;
+use serde::{de::Error as _, ser::Error as _};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{Flattened, General, Signature};
+
+const MIXED: &str = "signatures disagree on the b64 header parameter";
+const UTF8: &str = "unencoded payload is not valid UTF-8";
+const BASE64: &str = "payload is not valid base64url";
+
+/// Encodes the payload as it appears in the JSON serializations.
+///
+/// An unencoded payload (RFC 7797 Section 5.3) appears as a JSON string and
+/// must, therefore, be valid UTF-8.
+fn encode(payload: Option<&[u8]>, b64: bool) -> Result<Option<String>, &'static str> {
+    let payload = match payload {
+        Some(payload) => payload,
+        None => return Ok(None),
+    };
+
+    match b64 {
+        true => Ok(Some(Base64UrlUnpadded::encode_string(payload))),
+        false => match core::str::from_utf8(payload) {
+            Ok(payload) => Ok(Some(payload.into())),
+            Err(..) => Err(UTF8),
+        },
+    }
+}
+
+/// Decodes the payload as it appears in the JSON serializations and converts
+/// it to the payload type.
+fn decode<P, E>(payload: Option<String>, b64: bool) -> Result<Option<P>, E>
+where
+    P: TryFrom<Bytes>,
+    P::Error: Display,
+    E: serde::de::Error,
+{
+    let payload = match payload {
+        Some(payload) => payload,
+        None => return Ok(None),
+    };
+
+    let payload: Bytes = match b64 {
+        true => match Base64UrlUnpadded::decode_vec(&payload) {
+            Ok(payload) => payload.into(),
+            Err(..) => return Err(E::custom(BASE64)),
+        },
+        false => payload.into_bytes().into(),
+    };
+
+    P::try_from(payload).map(Some).map_err(E::custom)
+}
+
+#[derive(Serialize)]
+struct GeneralRef<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    payload: Option<String>,
+
+    signatures: &'a [Signature],
+}
+
+#[derive(Deserialize)]
+struct RawGeneral {
+    #[serde(default)]
+    payload: Option<String>,
+
+    signatures: Vec<Signature>,
+}
+
+#[derive(Serialize)]
+struct FlattenedRef<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    payload: Option<String>,
+
+    #[serde(flatten)]
+    signature: &'a Signature,
+}
+
+#[derive(Deserialize)]
+struct RawFlattened {
+    #[serde(default)]
+    payload: Option<String>,
+
+    #[serde(flatten)]
+    signature: Signature,
+}
+
+impl<P: AsRef<[u8]>> Serialize for General<P> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let b64 = self.b64().ok_or_else(|| S::Error::custom(MIXED))?;
+        let payload = self.payload.as_ref().map(AsRef::as_ref);
+
+        GeneralRef {
+            payload: encode(payload, b64).map_err(S::Error::custom)?,
+            signatures: &self.signatures,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, P: TryFrom<Bytes>> Deserialize<'de> for General<P>
+where
+    P::Error: Display,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = RawGeneral::deserialize(deserializer)?;
+
+        let general = Self {
+            payload: None,
+            signatures: raw.signatures,
+        };
+
+        let b64 = general.b64().ok_or_else(|| D::Error::custom(MIXED))?;
+        Ok(Self {
+            payload: decode(raw.payload, b64)?,
+            ..general
+        })
+    }
+}
+
+impl<P: AsRef<[u8]>> Serialize for Flattened<P> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let b64 = self.signature.b64();
+        let payload = self.payload.as_ref().map(AsRef::as_ref);
+
+        FlattenedRef {
+            payload: encode(payload, b64).map_err(S::Error::custom)?,
+            signature: &self.signature,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, P: TryFrom<Bytes>> Deserialize<'de> for Flattened<P>
+where
+    P::Error: Display,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = RawFlattened::deserialize(deserializer)?;
+        let b64 = raw.signature.b64();
+
+        Ok(Self {
+            payload: decode(raw.payload, b64)?,
+            signature: raw.signature,
+        })
+    }
+}
@@ -21,12 +21,19 @@
 
 extern crate alloc;
 
+#[cfg(feature = "std")]
+extern crate std;
+
 pub mod crypto;
 
 mod compact;
 mod head;
+mod json;
+mod parse;
 
-pub use head::{Protected, Unprotected};
+pub use compact::CompactError;
+pub use head::{HeaderError, Protected, Unprotected};
+pub use parse::ParseError;
 
 use alloc::{vec, vec::Vec};
 
@@ -34,26 +41,44 @@ use jose_b64::serde::{Bytes, Json};
 use serde::{Deserialize, Serialize};
 
 /// A JSON Web Signature representation
-#[derive(Clone, Debug, Serialize, Deserialize)]
+///
+/// When deserialized, the general serialization is detected by the presence
+/// of the `signatures` member. See [`Jws::parse`].
+///
+/// The payload is raw bytes by default. Any type that can be converted from
+/// [`Bytes`] and viewed as bytes may be used instead. For example, a
+/// `Jws<Json<T>>` parses the payload as JSON while keeping the original
+/// bytes, which are the ones that are verified.
+#[derive(Clone, Debug, Serialize)]
 #[non_exhaustive]
 #[allow(clippy::large_enum_variant)]
-#[serde(untagged)]
-pub enum Jws {
+#[serde(untagged, bound(serialize = "P: AsRef<[u8]>"))]
+pub enum Jws<P = Bytes> {
     /// General Serialization. This is
-    General(General),
+    General(General<P>),
 
     /// Flattened Serialization
-    Flattened(Flattened),
+    Flattened(Flattened<P>),
+}
+
+impl<P> Jws<P> {
+    /// Converts the payload to another type.
+    pub fn try_convert<Q: TryFrom<P>>(self) -> Result<Jws<Q>, Q::Error> {
+        match self {
+            Self::General(general) => general.try_convert().map(Jws::General),
+            Self::Flattened(flattened) => flattened.try_convert().map(Jws::Flattened),
+        }
+    }
 }
 
-impl From<General> for Jws {
-    fn from(value: General) -> Self {
+impl<P> From<General<P>> for Jws<P> {
+    fn from(value: General<P>) -> Self {
         Jws::General(value)
     }
 }
 
-impl From<Flattened> for Jws {
-    fn from(value: Flattened) -> Self {
+impl<P> From<Flattened<P>> for Jws<P> {
+    fn from(value: Flattened<P>) -> Self {
         Jws::Flattened(value)
     }
 }
@@ -76,17 +101,55 @@ impl From<Flattened> for Jws {
 ///       "signature":"<signature N contents>"}]
 /// }
 /// ```
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct General {
+#[derive(Clone, Debug)]
+pub struct General<P = Bytes> {
     /// The payload of the signature.
-    pub payload: Option<Bytes>,
+    pub payload: Option<P>,
 
     /// The signatures over the payload.
     pub signatures: Vec<Signature>,
 }
 
-impl From<Flattened> for General {
-    fn from(value: Flattened) -> Self {
+impl<P> General<P> {
+    /// Converts the payload to another type.
+    pub fn try_convert<Q: TryFrom<P>>(self) -> Result<General<Q>, Q::Error> {
+        Ok(General {
+            payload: self.payload.map(Q::try_from).transpose()?,
+            signatures: self.signatures,
+        })
+    }
+
+    /// Removes the signature at the index, returning it along with the
+    /// remaining JWS.
+    ///
+    /// The remaining JWS uses the flattened serialization if exactly one
+    /// signature is left. If the index is out of bounds, this fails,
+    /// returning the value unchanged.
+    pub fn remove(mut self, index: usize) -> Result<(Signature, Jws<P>), Self> {
+        if index >= self.signatures.len() {
+            return Err(self);
+        }
+
+        let signature = self.signatures.remove(index);
+        let jws = match Flattened::try_from(self) {
+            Ok(flattened) => Jws::Flattened(flattened),
+            Err(general) => Jws::General(general),
+        };
+
+        Ok((signature, jws))
+    }
+
+    /// Returns whether the payload is base64url-encoded, or `None` if the
+    /// signatures disagree (RFC 7797 Section 3).
+    pub(crate) fn b64(&self) -> Option<bool> {
+        let mut all = self.signatures.iter().map(Signature::b64);
+        let first = all.next().unwrap_or(true);
+        all.all(|b64| b64 == first).then_some(first)
+    }
+}
+
+impl<P> From<Flattened<P>> for General<P> {
+    fn from(value: Flattened<P>) -> Self {
         Self {
             payload: value.payload,
             signatures: vec![value.signature],
@@ -94,6 +157,24 @@ impl From<Flattened> for General {
     }
 }
 
+/// Downgrades to the flattened serialization.
+///
+/// This fails, returning the value unchanged, unless there is exactly one
+/// signature.
+impl<P> TryFrom<General<P>> for Flattened<P> {
+    type Error = General<P>;
+
+    fn try_from(mut value: General<P>) -> Result<Self, Self::Error> {
+        match value.signatures.len() {
+            1 => Ok(Self {
+                payload: value.payload,
+                signature: value.signatures.remove(0),
+            }),
+            _ => Err(value),
+        }
+    }
+}
+
 /// Flattened Serialization
 ///
 /// This is similar to the general serialization but is more compact, only
@@ -107,16 +188,33 @@ impl From<Flattened> for General {
 ///     "signature":"<signature contents>"
 /// }
 /// ```
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct Flattened {
+#[derive(Clone, Debug)]
+pub struct Flattened<P = Bytes> {
     /// The payload of the signature.
-    pub payload: Option<Bytes>,
+    pub payload: Option<P>,
 
     /// The signature over the payload.
-    #[serde(flatten)]
     pub signature: Signature,
 }
 
+impl<P> Flattened<P> {
+    /// Converts the payload to another type.
+    ///
+    /// ```
+    /// # use jose_b64::serde::Json;
+    /// # use jose_jws::Flattened;
+    /// let jws: Flattened = "eyJhbGciOiJub25lIn0.eyJpc3MiOiJqb2UifQ.".parse().unwrap();
+    /// let jws = jws.try_convert::<Json<serde_json::Value>>().unwrap();
+    /// assert_eq!(jws.payload.unwrap()["iss"], "joe");
+    /// ```
+    pub fn try_convert<Q: TryFrom<P>>(self) -> Result<Flattened<Q>, Q::Error> {
+        Ok(Flattened {
+            payload: self.payload.map(Q::try_from).transpose()?,
+            signature: self.signature,
+        })
+    }
+}
+
 /// A Signature
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Signature {
@@ -129,3 +227,34 @@ pub struct Signature {
     /// The Signature Bytes
     pub signature: Bytes,
 }
+
+impl Signature {
+    /// Validates the protected and unprotected headers.
+    ///
+    /// The protected header must not contain duplicate member names, and the
+    /// two headers must not share any parameter (RFC 7515 Section 7.2.1).
+    pub fn validate(&self) -> Result<(), HeaderError> {
+        let prot = self.protected.as_ref().map(|p| p.as_ref());
+        head::disjoint(prot, self.header.as_ref())
+    }
+
+    /// Returns the JOSE header: the protected header merged with the
+    /// unprotected header.
+    ///
+    /// Only the parameters that came from the protected header are covered by
+    /// the signature. If the headers are not disjoint, the protected value
+    /// takes precedence; see [`Signature::validate`].
+    pub fn header(&self) -> Protected {
+        let mut header = self.protected.as_deref().cloned().unwrap_or_default();
+        if let Some(head) = self.header.as_ref() {
+            header.oth.merge(head);
+        }
+
+        header
+    }
+
+    /// Returns whether the payload is base64url-encoded (RFC 7797 Section 3).
+    pub(crate) fn b64(&self) -> bool {
+        self.protected.as_ref().map_or(true, |p| p.b64)
+    }
+}
@@ -0,0 +1,130 @@
+// SPDX-FileCopyrightText: 2022 Profian Inc. <opensource@profian.com>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use alloc::string::String;
+use core::fmt;
+
+use jose_b64::serde::Bytes;
+use serde::{de::Error as _, Deserialize, Deserializer};
+use serde_json::{Map, Value};
+
+use crate::{Flattened, General, Jws};
+
+/// An error parsing a JWS in an unknown serialization.
+///
+/// The variant identifies the serialization that was detected.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ParseError {
+    /// The input looks like JSON but is not a JSON object.
+    Json(serde_json::Error),
+
+    /// The input is a malformed compact serialization.
+    Compact(jose_b64::stream::Error<serde_json::Error>),
+
+    /// The input is a malformed flattened JSON serialization.
+    Flattened(serde_json::Error),
+
+    /// The input is a malformed general JSON serialization.
+    General(serde_json::Error),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Json(e) => write!(f, "invalid JSON: {e}"),
+            Self::Compact(jose_b64::stream::Error::Inner(e)) => {
+                write!(f, "invalid compact serialization: {e}")
+            }
+            Self::Compact(jose_b64::stream::Error::Length) => {
+                f.write_str("invalid compact serialization: expected three parts")
+            }
+            Self::Compact(jose_b64::stream::Error::Value) => {
+                f.write_str("invalid compact serialization: invalid base64url")
+            }
+            Self::Flattened(e) => write!(f, "invalid flattened JSON serialization: {e}"),
+            Self::General(e) => write!(f, "invalid general JSON serialization: {e}"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Json(e) | Self::Flattened(e) | Self::General(e) => Some(e),
+            Self::Compact(jose_b64::stream::Error::Inner(e)) => Some(e),
+            Self::Compact(..) => None,
+        }
+    }
+}
+
+impl Jws {
+    /// Parses a JWS in any of its serializations.
+    ///
+    /// A JSON object is parsed as the general serialization if it has a
+    /// `signatures` member and as the flattened serialization otherwise. Any
+    /// other input is parsed as the compact serialization. To parse into a
+    /// typed payload, use [`Jws::parse_typed`].
+    ///
+    /// ```
+    /// # use jose_jws::Jws;
+    /// let jws = Jws::parse("eyJhbGciOiJub25lIn0.e30.").unwrap();
+    /// assert!(matches!(jws, Jws::Flattened(..)));
+    ///
+    /// let err = Jws::parse(r#"{"signatures": {}}"#).unwrap_err();
+    /// assert!(err.to_string().starts_with("invalid general JSON serialization"));
+    /// ```
+    pub fn parse(s: &str) -> Result<Self, ParseError> {
+        Self::parse_typed(s)
+    }
+}
+
+impl<P: TryFrom<Bytes>> Jws<P>
+where
+    P::Error: fmt::Display,
+{
+    /// Parses a JWS with a typed payload in any of its serializations.
+    ///
+    /// See [`Jws::parse`].
+    ///
+    /// ```
+    /// # use jose_b64::serde::Json;
+    /// # use jose_jws::Jws;
+    /// let jws = Jws::<Json<serde_json::Value>>::parse_typed("eyJhbGciOiJub25lIn0.e30.").unwrap();
+    /// assert!(matches!(jws, Jws::Flattened(..)));
+    /// ```
+    pub fn parse_typed(s: &str) -> Result<Self, ParseError> {
+        if !s.trim_start().starts_with('{') {
+            let flattened = s.parse().map_err(ParseError::Compact)?;
+            return Ok(Self::Flattened(flattened));
+        }
+
+        let map = serde_json::from_str(s).map_err(ParseError::Json)?;
+        Self::from_map(map)
+    }
+
+    /// Parses a JSON serialization, detecting which one it is.
+    fn from_map(map: Map<String, Value>) -> Result<Self, ParseError> {
+        let val = Value::Object(map);
+
+        if val.get("signatures").is_some() {
+            let general: General<P> = serde_json::from_value(val).map_err(ParseError::General)?;
+            Ok(Self::General(general))
+        } else {
+            let flattened: Flattened<P> =
+                serde_json::from_value(val).map_err(ParseError::Flattened)?;
+            Ok(Self::Flattened(flattened))
+        }
+    }
+}
+
+impl<'de, P: TryFrom<Bytes>> Deserialize<'de> for Jws<P>
+where
+    P::Error: fmt::Display,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let map = Map::deserialize(deserializer)?;
+        Self::from_map(map).map_err(D::Error::custom)
+    }
+}
@@ -0,0 +1,2727 @@
+// SPDX-FileCopyrightText: 2022 Profian Inc. <opensource@profian.com>
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+/// The HMAC key of RFC 7515 Appendix A.1.
+#[cfg(feature = "hmac")]
+const HMAC: &str =
+    "AyM1SysPpbyDfgZld3umj1qzKObwVMkoqQ-EstJQLr_T-1qS0gZH75aKtMN3Yj0iPS4hcgUuTwjAzZr1Z9CAow";
+
+/// An HMAC key for the tests that do not need a published one.
+#[cfg(feature = "hmac")]
+const SECRET: [u8; 32] = [7; 32];
+
+#[cfg(feature = "hmac")]
+fn secret() -> jose_jwk::crypto::Key {
+    jose_jwk::crypto::Key::Oct(SECRET.to_vec().into_boxed_slice().into())
+}
+
+/// A protected header selecting HS256.
+#[cfg(feature = "hmac")]
+fn hs256() -> jose_jws::Protected {
+    jose_jws::Protected {
+        oth: jose_jws::Unprotected {
+            alg: Some(jose_jwa::Signing::Hs256),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+/// The Ed25519 key pair of RFC 8037 Appendix A.1.
+#[cfg(feature = "ed25519")]
+const ED25519_D: &str = "nWGxne_9WmC6hEr0kuwsxERJxWl7MmkZcDusAxyuf2A";
+#[cfg(feature = "ed25519")]
+const ED25519_X: &str = "11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo";
+
+/// The P-256 key pair of RFC 7515 Appendix A.3.
+#[cfg(feature = "p256")]
+const P256_D: &str = "jpsQnnGQmL-YBIffH1136cspYG6-0iY7X1fCE9-E9LI";
+#[cfg(feature = "p256")]
+const P256_X: &str = "f83OJ3D2xF1Bg8vub9tLe1gHMzV76e8Tus9uPHvRVEU";
+#[cfg(feature = "p256")]
+const P256_Y: &str = "x_FEzRu9m36HLN_tue659LNpXW6pCyStikYjKIWI5a0";
+
+#[cfg(any(
+    feature = "ed25519",
+    feature = "hmac",
+    feature = "p256",
+    feature = "rsa"
+))]
+fn jwk(val: serde_json::Value) -> jose_jwk::Jwk {
+    serde_json::from_value(val).unwrap()
+}
+
+#[cfg(any(
+    feature = "ed25519",
+    feature = "hmac",
+    feature = "p256",
+    feature = "rsa"
+))]
+fn key(jwk: &jose_jwk::Jwk) -> jose_jwk::crypto::Key {
+    jose_jwk::crypto::Key::try_from(&jwk.key).unwrap()
+}
+
+/// The HS256 key of RFC 7515 Appendix A.1 with the `hmac` key identifier.
+#[cfg(feature = "hmac")]
+fn hmac() -> jose_jwk::Jwk {
+    jwk(serde_json::json!({
+        "kty": "oct",
+        "alg": "HS256",
+        "kid": "hmac",
+        "k": HMAC,
+    }))
+}
+
+/// The EdDSA key of RFC 8037 Appendix A.1 with the `ed25519` key identifier.
+#[cfg(feature = "ed25519")]
+fn ed25519() -> jose_jwk::Jwk {
+    jwk(serde_json::json!({
+        "kty": "OKP",
+        "crv": "Ed25519",
+        "alg": "EdDSA",
+        "kid": "ed25519",
+        "d": ED25519_D,
+        "x": ED25519_X,
+    }))
+}
+
+/// The ES256 key of RFC 7515 Appendix A.3.
+#[cfg(feature = "p256")]
+fn p256() -> jose_jwk::Jwk {
+    jwk(serde_json::json!({
+        "kty": "EC",
+        "crv": "P-256",
+        "alg": "ES256",
+        "x": P256_X,
+        "y": P256_Y,
+        "d": P256_D,
+    }))
+}
+
+/// Removes the private parts of a JWK.
+#[cfg(any(feature = "ed25519", feature = "p256"))]
+fn public(mut jwk: jose_jwk::Jwk) -> jose_jwk::Jwk {
+    match &mut jwk.key {
+        jose_jwk::Key::Ec(ec) => ec.d = None,
+        jose_jwk::Key::Okp(okp) => okp.d = None,
+        _ => unreachable!(),
+    }
+
+    jwk
+}
+
+/// An HS256 key with a key identifier, for tests with several signers.
+#[cfg(feature = "hmac")]
+fn signer(kid: &str, k: &str) -> jose_jwk::Jwk {
+    jwk(serde_json::json!({
+        "kty": "oct",
+        "alg": "HS256",
+        "kid": kid,
+        "k": k,
+    }))
+}
+
+/// A set of independent signers with the key identifiers `a`, `b` and `c`.
+#[cfg(feature = "hmac")]
+fn signers() -> jose_jwk::JwkSet {
+    jose_jwk::JwkSet {
+        keys: vec![
+            signer("a", "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA"),
+            signer("b", "BBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBA"),
+            signer("c", "CCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCA"),
+        ],
+    }
+}
+
+#[cfg(test)]
+#[cfg(any(
+    feature = "hmac",
+    feature = "k256",
+    feature = "p256",
+    feature = "p384",
+    feature = "p521",
+    feature = "rsa"
+))]
+mod rfc7515 {
+    use jose_b64::stream::Update;
+    use jose_jwa::Signing;
+    use jose_jwk::crypto;
+    use jose_jws::crypto::{Error, Signer, SigningKey, Verifier, VerifyingKey};
+    use jose_jws::{Flattened, Unprotected};
+    use rand_core::OsRng;
+
+    fn verify<'a, K>(key: &'a K, jws: &'a Flattened) -> Result<(), Error>
+    where
+        K: VerifyingKey<'a, &'a Flattened, StartError = Error>,
+        K::Verifier: Verifier<'a, FinishError = Error>,
+        Error: From<<K::Verifier as Update>::Error>,
+    {
+        let mut ver = key.verify(jws)?;
+        ver.update(jws.payload.as_ref().unwrap())?;
+        ver.finish()
+    }
+
+    #[test]
+    #[cfg(feature = "hmac")]
+    fn a1() {
+        const JWS: &str = concat!(
+            "eyJ0eXAiOiJKV1QiLA0KICJhbGciOiJIUzI1NiJ9",
+            ".",
+            "eyJpc3MiOiJqb2UiLA0KICJleHAiOjEzMDA4MTkzODAsDQogImh0dHA6Ly9leGFt",
+            "cGxlLmNvbS9pc19yb290Ijp0cnVlfQ",
+            ".",
+            "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk",
+        );
+
+        let key = super::key(&super::hmac());
+        let jws: Flattened = JWS.parse().unwrap();
+        verify(&key, &jws).unwrap();
+        assert_eq!(jws.to_string(), JWS);
+
+        // Tampering with the payload invalidates the signature.
+        let mut bad = jws.clone();
+        bad.payload = Some(b"{\"iss\":\"eve\"}".to_vec().into());
+        assert!(matches!(verify(&key, &bad), Err(Error::Invalid)));
+
+        // Round trip through our own signer for every HMAC algorithm.
+        for alg in [Signing::Hs256, Signing::Hs384, Signing::Hs512] {
+            let prot = jose_jws::Protected {
+                oth: Unprotected {
+                    alg: Some(alg),
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+
+            let mut sig = key.sign(Some(prot), None).unwrap();
+            sig.update(b"Hello world!").unwrap();
+            let jws = Flattened {
+                payload: Some(b"Hello world!".to_vec().into()),
+                signature: sig.finish(OsRng).unwrap(),
+            };
+
+            verify(&key, &jws).unwrap();
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "hmac")]
+    fn a1_short_key() {
+        let key = crypto::Key::Oct(vec![0u8; 16].into_boxed_slice().into());
+
+        for (alg, ok) in [
+            (Signing::Hs256, true),
+            (Signing::Hs384, false),
+            (Signing::Hs512, false),
+            (Signing::Es256, false),
+        ] {
+            let head = Unprotected {
+                alg: Some(alg),
+                ..Default::default()
+            };
+
+            assert_eq!(key.sign(None, Some(head)).is_ok(), ok, "{alg}");
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "p256")]
+    fn a3() {
+        const JWS: &str = concat!(
+            "eyJhbGciOiJFUzI1NiJ9",
+            ".",
+            "eyJpc3MiOiJqb2UiLA0KICJleHAiOjEzMDA4MTkzODAsDQogImh0dHA6Ly9leGFt",
+            "cGxlLmNvbS9pc19yb290Ijp0cnVlfQ",
+            ".",
+            "DtEhU3ljbEg8L38VWAfUAqOyKAM6-Xx-F4GawxaepmXFCgfTjDxw5djxLa8ISlSApmWQ",
+            "xfKTUJqPP3-Kg6NU1Q",
+        );
+
+        let key = super::key(&super::public(super::p256()));
+        let jws: Flattened = JWS.parse().unwrap();
+        verify(&key, &jws).unwrap();
+
+        // A DER encoding of the same signature is rejected.
+        let (r, s) = jws.signature.signature.split_at(32);
+        let mut der = vec![0x30, 0x44, 0x02, 0x20];
+        der.extend_from_slice(r);
+        der.extend_from_slice(&[0x02, 0x20]);
+        der.extend_from_slice(s);
+        let mut bad = jws.clone();
+        bad.signature.signature = der.into();
+        assert!(matches!(verify(&key, &bad), Err(Error::Invalid)));
+
+        // So is a signature of the wrong length.
+        let mut bad = jws.clone();
+        bad.signature.signature = jws.signature.signature[..63].to_vec().into();
+        assert!(matches!(verify(&key, &bad), Err(Error::Invalid)));
+
+        // Only a private key can sign.
+        let secret = p256::SecretKey::random(&mut OsRng);
+        let prv = crypto::Key::from(secret.clone());
+        let key = crypto::Key::from(secret.public_key());
+        let prot = jose_jws::Protected {
+            oth: Unprotected {
+                alg: Some(Signing::Es256),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        assert!(key.sign(Some(prot.clone()), None).is_err());
+        let mut sig = prv.sign(Some(prot), None).unwrap();
+        sig.update(b"Hello world!").unwrap();
+        let jws = Flattened {
+            payload: Some(b"Hello world!".to_vec().into()),
+            signature: sig.finish(OsRng).unwrap(),
+        };
+
+        assert_eq!(jws.signature.signature.len(), 64);
+        verify(&key, &jws).unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "p384")]
+    fn p384() {
+        let secret = p384::SecretKey::random(&mut OsRng);
+        let prv = crypto::Key::from(secret.clone());
+        let key = crypto::Key::from(secret.public_key());
+
+        let head = Unprotected {
+            alg: Some(Signing::Es384),
+            ..Default::default()
+        };
+
+        let mut sig = prv.sign(None, Some(head)).unwrap();
+        sig.update(b"Hello world!").unwrap();
+        let jws = Flattened {
+            payload: Some(b"Hello world!".to_vec().into()),
+            signature: sig.finish(OsRng).unwrap(),
+        };
+
+        assert_eq!(jws.signature.signature.len(), 96);
+        verify(&key, &jws).unwrap();
+
+        let mut bad = jws.clone();
+        bad.signature.header.as_mut().unwrap().alg = Some(Signing::Es256);
+        assert!(verify(&key, &bad).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "p521")]
+    fn p521() {
+        let secret = p521::SecretKey::random(&mut OsRng);
+        let prv = crypto::Key::from(secret.clone());
+
+        // Round trip the public key through its JWK representation.
+        let jwk = jose_jwk::Key::from(&crypto::Key::from(secret.public_key()));
+        let key = crypto::Key::try_from(&jwk).unwrap();
+
+        let head = Unprotected {
+            alg: Some(Signing::Es512),
+            ..Default::default()
+        };
+
+        let mut sig = prv.sign(None, Some(head)).unwrap();
+        sig.update(b"Hello world!").unwrap();
+        let jws = Flattened {
+            payload: Some(b"Hello world!".to_vec().into()),
+            signature: sig.finish(OsRng).unwrap(),
+        };
+
+        assert_eq!(jws.signature.signature.len(), 132);
+        verify(&key, &jws).unwrap();
+
+        let mut bad = jws.clone();
+        bad.signature.header.as_mut().unwrap().alg = Some(Signing::Es384);
+        assert!(verify(&key, &bad).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "k256")]
+    fn k256() {
+        let secret = k256::SecretKey::random(&mut OsRng);
+        let prv = crypto::Key::from(secret.clone());
+
+        // Round trip the public key through its JWK representation.
+        let jwk = jose_jwk::Key::from(&crypto::Key::from(secret.public_key()));
+        let key = crypto::Key::try_from(&jwk).unwrap();
+
+        let head = Unprotected {
+            alg: Some(Signing::Es256K),
+            ..Default::default()
+        };
+
+        let mut sig = prv.sign(None, Some(head)).unwrap();
+        sig.update(b"Hello world!").unwrap();
+        let jws = Flattened {
+            payload: Some(b"Hello world!".to_vec().into()),
+            signature: sig.finish(OsRng).unwrap(),
+        };
+
+        assert_eq!(jws.signature.signature.len(), 64);
+        verify(&key, &jws).unwrap();
+
+        // ES256 uses the same hash and size but a different curve.
+        let mut bad = jws.clone();
+        bad.signature.header.as_mut().unwrap().alg = Some(Signing::Es256);
+        assert!(verify(&key, &bad).is_err());
+    }
+
+    /// A 4096-bit key, which is strong enough for every RSA algorithm.
+    #[cfg(feature = "rsa")]
+    fn rsa4096() -> serde_json::Value {
+        serde_json::json!({
+            "kty": "RSA",
+            "n": concat!(
+                "y8EDU7jfZGPWzOxCQUX_ZowRPwIg7_3T5MfKCSd4fkus8FLTc2f1w6L0vgZMhY8L",
+                "7q4EAzhOtTqPJDjB_scVmFnNunFBtDGr_s0eSQv2iNzcU7SeD072RYCwG6BxYEVt",
+                "Vpt4ShwcSoSAOsK80G1SfgyORb92FGm0WnP9RtLCMCvlSRaO6WYHGc9Nyra3rTdN",
+                "sTnPeRlYrCVrRC_x66qxikGpz6qiLGegq6TQ8UmXvBITwnhqhU96cvLrl-sELXLl",
+                "WMabDhbPD6E3Yh_zae9LCoYKUMopS5BDDrgpUVMBOJWdooGQa8_SW5a8NcrAdcar",
+                "mXXteX0ca96cc7572zTeZXwenzSXhIPh7-4HCTDtKtznS1D67FNYYCGP70wqnPQf",
+                "U9N1n5qNZuRbUB6NzYKUCF_88U3vjDOhjcq864pMp_UztqzlIRXX3rmSn2YirpsQ",
+                "biNpWQ54-0v3rKzwsv0LRXKXYpbUfkYYF2UYMShY2n2K98WblPABF5O_J9_DflNu",
+                "IxlOZbbFmLiS5C5Ssbb7ulMrNmh6L3sSqBIIusWCutvOvbBwvU2LfdmyQkgfh_ec",
+                "LtnAnUlOIkTPYVwAyPHofKffIQMxetczjCcRGW4cQAzK1sO8DnmQw9Sx1r0sVaDn",
+                "muWjrbBqyTpNAGXyd22KbAAA1v7XsLg3qJwEdcHoIKk",
+            ),
+            "e": "AQAB",
+            "d": concat!(
+                "KtTwfttMZKVjPHFG7UNKcPNtVa48H88fScbg9wMr3nrIiD2VEzvNq1pYchDPab0c",
+                "IEjG8alWzFyaxh3DVCxkIwJvz6tqyTcu5ZGXdfoUkYlCLLt5hZTPCuXGP4x_AOKf",
+                "XTcx9nxJyzVm4lNZD0SH9JTtnIZmy1w8LE6ZNdfst-UmXjKmiLRotjmArCY0Xu6S",
+                "_7g8H8QfApMcJTmh9K1NhJeQm6XOUSDYovRy8uIKSmAPdNBM7ii6wCrGMUmXd3lh",
+                "coPPiQrs7KdyhcdbK1ABGZ17UifpPef93LyEfQLCrTMwb76mTi-iFBxQBMvSIQAK",
+                "RyiF6wXMoXVqtJ8cmMRYoqMDz7WWaMVVj5F5n95zrE622BIR3xvsNHF7e-gtzVNm",
+                "z8uyrdM50mHEAElJS8bXOnT_5u6Dqgj_EYvvvA_qigSNYTrEYZcx1dJcmdufUZvv",
+                "ui4GKfP41IiY90wRSDGRQ8HHbFBACJDSBOkM4LoDth89wqMSxu14jUxSHuoeG8fH",
+                "CiSiyH71A8ZTTf5J-VA3w-_LkTF57Lxki_l64hMbCEsmZcQzAVdqKglQsi7Z4eGr",
+                "56tDPLrQxsKJTEA5A5GRzh3rGsZaDN38rLB7rQwO5PIrqJzdU7m-EKrImHLCzZqO",
+                "X46aB_9408bFBhy0CXvJ04fpXQxsZdV0TxV6OQ8Twv0",
+            ),
+            "p": concat!(
+                "9nRJywgjK_Yhe_XVF4kHLb1S7eSjPqi2EMEQij6sL_-Qmr1OaSujopxMnAsXmjnb",
+                "yhV91zAeaVFk4Bd_geM9MEWkMC31_O_DM0uuNuD_sScmBdVTE0rzmDyIl_zg-VS4",
+                "84K9SF1rZ9KKOu6jO85SlMuSmDLk4urJmyrLq-duzBCIXxgC-2My-yErxvWTzxP3",
+                "TSOtuXvGXFusx3mtXF9uJihntx-YQvXabTEeXykkVzI80XISkE0aeQUBzQf0gAjH",
+                "KAVc6OSI7Q_7_Qu29Nygzhn6XJRki7bvzbiIiiBFzFv1stFjhNaHKusHRDpHJPa0",
+                "VnX12jYD_iSoN8E3RKfVVQ",
+            ),
+            "q": concat!(
+                "06VUtnbZln_OCT4Mq9z9-o7G8k8jde3uOJfz-HONVHK6Sb8J7YzTF3vGDu73488Q",
+                "RFdFCcfssXsgFdutSkdPnCPBTw_hsTTVGX_U4njrLzdKo1WnODY9FI6uRWmzLjAP",
+                "Wts0-sPlLrFxlnGZ383m89ax2GSh1M1y32XSXa2UYOPnIilk9kVHm4oyPDBiIYIW",
+                "XOLsQlxtV2wAPPuNRhvYcWwZ7v1WxxNuKwpQF7Hu69GtjfbmXkby1skNonNI4Na8",
+                "K71XWiplnDukZaTcF-OZXyuZaz-t7EZ6ovSxXEV1qGK-gMkNd6JNK5ud6IafSVjZ",
+                "KBe9xYGoYaPCbd-IQR0eBQ",
+            ),
+            "dp": concat!(
+                "WwgDBcJgs2k7Xv0jk4jipOtsSUCNZ6SLc07MnDOgCC6eq_lGkCQ1UefniC-0rRu3",
+                "6kHb9HLqmL4ew4MKbuCPhlgC2GTxmla5RCYtGEotR7MNIVR1Vgf2SAH79h_KvW2k",
+                "zxU4cxVGidfDhRcGJh4EdL9oy1zpHEPa6bx59GoMi8WDiZRl7ZLrjsTm1Zt52CAo",
+                "1U5bacqlLxIRlLCF7zu0hNWh0YsTA5Cp-HEPJdkdejOtMTOwVaqVH6RQ0PmiPvR_",
+                "E3I_qipXxTCFRCdikbk4kEgaKL4LEWwl0Vfa3yIiheRlsYIT_0n6LUkYqwPXqXiS",
+                "jekOQWZ4PRQvKkDs-CgB7Q",
+            ),
+            "dq": concat!(
+                "lJhGstkdCcJvqSOMh_nfw9QMsEGJ9xl3IPrmyvBuMPVf_7kGQmeWKa24L_Htz18o",
+                "36KaETTx5OY6b75Nm5IXvduItHbFEcKzP3T1xY_6J6tdVvIjNp82IUHP72I38FzD",
+                "2xl-x_VT6oWg52yZCX4qAdciBHka-irI1TpLCYR-YcyBEgYYIaQgk37iqEmcDESm",
+                "aM7V6_2ysydbv3UqCigwvemVB6uNCraEqelStGA0DBOqE7teoKCVjOGaFg11BI8f",
+                "jGIie2a68ocZdFfcQ1WD8ELPM3KBLRuKrPC5ubv20KOLdyYxs2YbYlue8YvbOAcO",
+                "aEzK62mODAeK0dOYeYb09Q",
+            ),
+            "qi": concat!(
+                "TysodX9htNqDhdgcsMSDgGtxn8iGM1p7RkIVXgt-zm3IIUubUKLrb2ei365FK_22",
+                "Euz4AHEs95SkQc_netJgd6k9pfdeBvWce23pNR3yCZVpwkhxnLLf6XecRl5oZl7s",
+                "wzAlo-MGP6xiTCdV0D1ymkmc8KFFxjWhMFBYF33HFHg_OCKOXn8b6ixtVbsgQSar",
+                "xI6pOmnCy_zyoC0Cd8DhEoyxQa_-SjqcU05E6GHkn6a0jIrHBd4X2vXGe70XV3QD",
+                "_YhscZ7DYXpOPHNgttjPg-dAkVzkrDs0Y8zRo67OcHk8KZUMaUHKjtswWm5brBme",
+                "7KlLPWCJ25qiIdiJSt32SA",
+            ),
+        })
+    }
+
+    #[test]
+    #[cfg(feature = "rsa")]
+    fn rsa() {
+        let secret = rsa::RsaPrivateKey::new(&mut OsRng, 2048).unwrap();
+        let prv = crypto::Key::from(secret.clone());
+        let key = crypto::Key::from(secret.to_public_key());
+
+        let sign = |alg| {
+            let head = Unprotected {
+                alg: Some(alg),
+                ..Default::default()
+            };
+
+            let mut sig = prv
+                .sign(Some(jose_jws::Protected::default()), Some(head))
+                .unwrap();
+            sig.update(b"Hello world!").unwrap();
+            Flattened {
+                payload: Some(b"Hello world!".to_vec().into()),
+                signature: sig.finish(OsRng).unwrap(),
+            }
+        };
+
+        for alg in [Signing::Rs256, Signing::Ps256] {
+            let jws = sign(alg);
+            assert_eq!(jws.signature.signature.len(), 256);
+            verify(&key, &jws).unwrap();
+            verify(&prv, &jws).unwrap();
+
+            let mut bad = jws.clone();
+            bad.payload = Some(b"Goodbye world!".to_vec().into());
+            assert!(matches!(verify(&key, &bad), Err(Error::Invalid)));
+        }
+
+        // PKCS #1 v1.5 signatures are deterministic; PSS signatures are salted.
+        assert_eq!(
+            sign(Signing::Rs256).signature.signature,
+            sign(Signing::Rs256).signature.signature
+        );
+        assert_ne!(
+            sign(Signing::Ps256).signature.signature,
+            sign(Signing::Ps256).signature.signature
+        );
+
+        // Signing with the stronger digests requires a stronger key.
+        for alg in [
+            Signing::Rs384,
+            Signing::Rs512,
+            Signing::Ps384,
+            Signing::Ps512,
+        ] {
+            let head = Unprotected {
+                alg: Some(alg),
+                ..Default::default()
+            };
+
+            assert!(matches!(
+                prv.sign(None, Some(head)),
+                Err(Error::Key(crypto::Error::Unsupported))
+            ));
+        }
+
+        // A PKCS #1 v1.5 signature is not a valid PSS signature.
+        let mut bad = sign(Signing::Rs256);
+        bad.signature.header.as_mut().unwrap().alg = Some(Signing::Ps256);
+        assert!(matches!(verify(&key, &bad), Err(Error::Invalid)));
+    }
+
+    #[test]
+    #[cfg(feature = "rsa")]
+    fn rsa_digests() {
+        let prv = super::key(&super::jwk(rsa4096()));
+
+        let mut public = rsa4096();
+        for prm in ["d", "p", "q", "dp", "dq", "qi"] {
+            public.as_object_mut().unwrap().remove(prm);
+        }
+
+        let key = super::key(&super::jwk(public));
+
+        for alg in [
+            Signing::Rs256,
+            Signing::Rs384,
+            Signing::Rs512,
+            Signing::Ps256,
+            Signing::Ps384,
+            Signing::Ps512,
+        ] {
+            let head = Unprotected {
+                alg: Some(alg),
+                ..Default::default()
+            };
+
+            let mut sig = prv.sign(None, Some(head)).unwrap();
+            sig.update(b"Hello world!").unwrap();
+            let jws = Flattened {
+                payload: Some(b"Hello world!".to_vec().into()),
+                signature: sig.finish(OsRng).unwrap(),
+            };
+
+            assert_eq!(jws.signature.signature.len(), 512, "{alg}");
+            verify(&key, &jws).unwrap();
+            verify(&prv, &jws).unwrap();
+
+            let mut bad = jws.clone();
+            bad.payload = Some(b"Goodbye world!".to_vec().into());
+            assert!(matches!(verify(&key, &bad), Err(Error::Invalid)), "{alg}");
+
+            // The digest is bound to the algorithm.
+            let other = match alg {
+                Signing::Rs256 => Signing::Rs384,
+                Signing::Rs384 => Signing::Rs512,
+                Signing::Rs512 => Signing::Rs256,
+                Signing::Ps256 => Signing::Ps384,
+                Signing::Ps384 => Signing::Ps512,
+                _ => Signing::Ps256,
+            };
+
+            let mut bad = jws.clone();
+            bad.signature.header.as_mut().unwrap().alg = Some(other);
+            assert!(matches!(verify(&key, &bad), Err(Error::Invalid)), "{alg}");
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "ed25519")]
+mod rfc8037 {
+    use jose_b64::stream::Update;
+    use jose_jwa::Signing;
+    use jose_jwk::{crypto, Jwk};
+    use jose_jws::crypto::{Error, Signer, SigningKey, Verifier, VerifyingKey};
+    use jose_jws::{Flattened, Protected, Unprotected};
+    use rand_core::OsRng;
+
+    const JWS: &str = concat!(
+        "eyJhbGciOiJFZERTQSJ9",
+        ".",
+        "RXhhbXBsZSBvZiBFZDI1NTE5IHNpZ25pbmc",
+        ".",
+        "hgyY0il_MGCjP0JzlnLWG1PPOt7-09PGcvMg3AIbQR6dWbhijcNR4ki4iylGjg5B",
+        "hVsPt9g7sVvpAr_MuM0KAg",
+    );
+
+    fn key(private: bool) -> crypto::Key {
+        match private {
+            true => super::key(&super::ed25519()),
+            false => super::key(&super::public(super::ed25519())),
+        }
+    }
+
+    fn verify(key: &crypto::Key, jws: &Flattened) -> Result<(), Error> {
+        let mut ver = key.verify(jws)?;
+        ver.update(jws.payload.as_ref().unwrap())?;
+        ver.finish()
+    }
+
+    #[test]
+    fn a4() {
+        let jws: Flattened = JWS.parse().unwrap();
+        verify(&key(false), &jws).unwrap();
+        verify(&key(true), &jws).unwrap();
+
+        // Ed25519 signatures are deterministic.
+        let prot = Protected {
+            oth: Unprotected {
+                alg: Some(Signing::EdDsa),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let prv = key(true);
+        let mut sig = prv.sign(Some(prot), None).unwrap();
+        sig.update(b"Example of Ed25519 signing").unwrap();
+        let out: Flattened = Flattened {
+            payload: Some(b"Example of Ed25519 signing".to_vec().into()),
+            signature: sig.finish(OsRng).unwrap(),
+        };
+
+        assert_eq!(out.to_string(), JWS);
+
+        let mut bad = jws.clone();
+        bad.payload = Some(b"Example of Ed25519 signinG".to_vec().into());
+        assert!(matches!(verify(&key(false), &bad), Err(Error::Invalid)));
+    }
+
+    #[test]
+    fn public() {
+        let head = Unprotected {
+            alg: Some(Signing::EdDsa),
+            ..Default::default()
+        };
+
+        assert!(key(false).sign(None, Some(head)).is_err());
+
+        let head = Unprotected {
+            alg: Some(Signing::Es256),
+            ..Default::default()
+        };
+
+        assert!(key(true).sign(None, Some(head)).is_err());
+    }
+
+    #[test]
+    fn ed448() {
+        use jose_jwk::crypto::KeyInfo;
+
+        // RFC 8032 Section 7.4, -----Blank
+        let jwk: Jwk = serde_json::from_value(serde_json::json!({
+            "kty": "OKP",
+            "crv": "Ed448",
+            "x": "X9dEm1m0Yf0s54fsYWrUah2hNCSFpw4fig6nXYDpZ3jt8SR2m0bHBhvWeD3x5Q9s0foavq_oJWGA",
+        }))
+        .unwrap();
+
+        assert!(!jwk.is_supported(&Signing::EdDsa.into()));
+        assert!(matches!(
+            crypto::Key::try_from(&jwk.key),
+            Err(crypto::Error::Unsupported)
+        ));
+    }
+}
+
+#[cfg(test)]
+#[cfg(all(feature = "hmac", feature = "ed25519"))]
+mod builder {
+    use jose_b64::stream::Update;
+    use jose_jwa::Signing;
+    use jose_jwk::{crypto, Jwk};
+    use jose_jws::crypto::{Builder, Error, Verifier, VerifyingKey};
+    use jose_jws::{Flattened, General, Jws, Protected, Unprotected};
+    use rand_core::OsRng;
+
+    use super::{ed25519, hmac, key};
+
+    const PAYLOAD: &[u8] = b"Hello world!";
+
+    fn verify(jwk: &Jwk, jws: &General) -> Result<(), Error> {
+        let key = key(jwk);
+        let mut checked = 0;
+
+        // Skip the signatures that were made by other keys.
+        for sig in &jws.signatures {
+            let prot = sig.protected.as_ref().and_then(|p| p.oth.kid.as_ref());
+            let head = sig.header.as_ref().and_then(|h| h.kid.as_ref());
+            match (prot.or(head), jwk.prm.kid.as_ref()) {
+                (Some(kid), Some(own)) if kid != own => continue,
+                _ => checked += 1,
+            }
+
+            let mut ver = key.verify(sig)?;
+            ver.update(jws.payload.as_ref().unwrap())?;
+            ver.finish()?;
+        }
+
+        match checked {
+            0 => Err(Error::Invalid),
+            _ => Ok(()),
+        }
+    }
+
+    #[test]
+    fn compact() {
+        let jwk = hmac();
+        let compact = Builder::new(PAYLOAD)
+            .jwk(&jwk, None, None)
+            .unwrap()
+            .compact(&mut OsRng)
+            .unwrap();
+
+        let jws: Flattened = compact.parse().unwrap();
+        let prot = jws.signature.protected.as_ref().unwrap();
+        assert_eq!(prot.oth.alg, Some(Signing::Hs256));
+        assert_eq!(prot.oth.kid.as_deref(), Some("hmac"));
+        assert_eq!(&jws.payload.as_ref().unwrap()[..], PAYLOAD);
+        verify(&jwk, &jws.into()).unwrap();
+    }
+
+    #[test]
+    fn compact_refused() {
+        let (a, b) = (hmac(), ed25519());
+
+        // Multiple signatures cannot be represented.
+        let err = Builder::new(PAYLOAD)
+            .jwk(&a, None, None)
+            .unwrap()
+            .jwk(&b, None, None)
+            .unwrap()
+            .compact(&mut OsRng);
+        assert!(matches!(err, Err(Error::Serialization)));
+
+        // Neither can an unprotected header.
+        let err = Builder::new(PAYLOAD)
+            .jwk(&a, None, Some(Unprotected::default()))
+            .unwrap()
+            .compact(&mut OsRng);
+        assert!(matches!(err, Err(Error::Serialization)));
+
+        // Nor no signature at all.
+        let err = Builder::new(PAYLOAD).compact(&mut OsRng);
+        assert!(matches!(err, Err(Error::Serialization)));
+    }
+
+    #[test]
+    fn general() {
+        let (a, b) = (hmac(), ed25519());
+        let jws = Builder::new(PAYLOAD)
+            .jwk(&a, None, None)
+            .unwrap()
+            .jwk(&b, None, None)
+            .unwrap()
+            .jws(&mut OsRng)
+            .unwrap();
+
+        let Jws::General(jws) = jws else {
+            panic!("expected the general serialization");
+        };
+
+        assert_eq!(jws.signatures.len(), 2);
+        verify(&a, &jws).unwrap();
+        verify(&b, &jws).unwrap();
+
+        let kids: Vec<_> = jws
+            .signatures
+            .iter()
+            .map(|s| s.protected.as_ref().unwrap().oth.kid.clone().unwrap())
+            .collect();
+        assert_eq!(kids, ["hmac", "ed25519"]);
+
+        // A single signature produces the flattened serialization.
+        let jws = Builder::new(PAYLOAD)
+            .jwk(&a, None, None)
+            .unwrap()
+            .jws(&mut OsRng)
+            .unwrap();
+        assert!(matches!(jws, Jws::Flattened(..)));
+    }
+
+    #[test]
+    fn headers() {
+        let jwk = hmac();
+
+        // The header cannot override the algorithm the JWK is pinned to.
+        let head = Unprotected {
+            alg: Some(Signing::Hs512),
+            ..Default::default()
+        };
+
+        let err = Builder::new(PAYLOAD)
+            .jwk(&jwk, None, Some(head))
+            .unwrap()
+            .flattened(&mut OsRng);
+        assert!(matches!(err, Err(Error::AlgMismatch)));
+
+        // Other explicit header values take precedence over the JWK
+        // parameters.
+        let head = Unprotected {
+            alg: Some(Signing::Hs256),
+            kid: Some("other".into()),
+            ..Default::default()
+        };
+
+        let mut jws = Builder::new(PAYLOAD)
+            .jwk(&jwk, None, Some(head))
+            .unwrap()
+            .flattened(&mut OsRng)
+            .unwrap();
+
+        assert!(jws.signature.protected.is_none());
+        assert_eq!(jws.signature.signature.len(), 32);
+        assert!(verify(&jwk, &jws.clone().into()).is_err());
+        jws.signature.header.as_mut().unwrap().kid = None;
+        verify(&jwk, &jws.into()).unwrap();
+
+        // Bare keys have no parameters to take the algorithm from.
+        let key = crypto::Key::try_from(&jwk.key).unwrap();
+        let err = Builder::new(PAYLOAD)
+            .key(&key, None, None)
+            .general(&mut OsRng);
+        assert!(matches!(err, Err(Error::MissingAlg)));
+
+        let prot = Protected {
+            oth: Unprotected {
+                alg: Some(Signing::Hs384),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let jws = Builder::new(PAYLOAD)
+            .key(&key, Some(prot), None)
+            .general(&mut OsRng)
+            .unwrap();
+        verify(&jwk, &jws).unwrap();
+    }
+
+    #[test]
+    fn operations() {
+        for prm in [
+            serde_json::json!({ "use": "enc" }),
+            serde_json::json!({ "key_ops": ["verify"] }),
+        ] {
+            let mut val = serde_json::to_value(hmac()).unwrap();
+            val.as_object_mut()
+                .unwrap()
+                .extend(prm.as_object().unwrap().clone());
+            let jwk: Jwk = serde_json::from_value(val).unwrap();
+
+            let err = Builder::new(PAYLOAD)
+                .jwk(&jwk, None, None)
+                .unwrap()
+                .compact(&mut OsRng);
+            assert!(matches!(err, Err(Error::NoKey)), "{prm}");
+        }
+
+        let mut jwk = hmac();
+        jwk.prm.ops = Some([jose_jwk::Operations::Sign].into_iter().collect());
+        Builder::new(PAYLOAD)
+            .jwk(&jwk, None, None)
+            .unwrap()
+            .compact(&mut OsRng)
+            .unwrap();
+    }
+}
+
+#[cfg(test)]
+#[cfg(all(feature = "hmac", feature = "ed25519"))]
+mod verified {
+    use jose_jwa::Signing;
+    use jose_jwk::{crypto, Jwk};
+    use jose_jws::crypto::{Builder, Error};
+    use jose_jws::{Jws, Protected, Unprotected};
+    use rand_core::OsRng;
+
+    const PAYLOAD: &[u8] = b"Hello world!";
+
+    fn hmac() -> (Jwk, crypto::Key) {
+        let jwk = super::hmac();
+        let key = super::key(&jwk);
+        (jwk, key)
+    }
+
+    fn ed25519() -> (Jwk, crypto::Key) {
+        let jwk = super::ed25519();
+        let key = super::key(&jwk);
+        (jwk, key)
+    }
+
+    #[test]
+    fn flattened() {
+        let (jwk, key) = hmac();
+        let head = Unprotected {
+            typ: Some("example".into()),
+            ..Default::default()
+        };
+
+        let jws = Builder::new(PAYLOAD)
+            .jwk(&jwk, None, Some(head))
+            .unwrap()
+            .flattened(&mut OsRng)
+            .unwrap();
+
+        let verified = jws.verify(&key).unwrap();
+        assert_eq!(verified.payload(), PAYLOAD);
+        assert_eq!(verified.header().oth.alg, Some(Signing::Hs256));
+        assert_eq!(verified.header().oth.kid.as_deref(), Some("hmac"));
+        assert_eq!(verified.header().oth.typ.as_deref(), Some("example"));
+
+        let mut bad = jws.clone();
+        bad.payload = Some(b"Goodbye world!".to_vec().into());
+        assert!(matches!(bad.verify(&key), Err(Error::Invalid)));
+
+        // The wrong kind of key is rejected.
+        let (.., other) = ed25519();
+        assert!(jws.verify(&other).is_err());
+    }
+
+    #[test]
+    fn general() {
+        let (a, akey) = hmac();
+        let (b, bkey) = ed25519();
+
+        let jws = Builder::new(PAYLOAD)
+            .jwk(&a, None, None)
+            .unwrap()
+            .jwk(&b, None, None)
+            .unwrap()
+            .jws(&mut OsRng)
+            .unwrap();
+
+        let verified = jws.verify(&akey).unwrap();
+        assert_eq!(verified.header().oth.kid.as_deref(), Some("hmac"));
+        assert_eq!(verified.into_payload(), PAYLOAD);
+
+        let verified = jws.verify(&bkey).unwrap();
+        assert_eq!(verified.header().oth.kid.as_deref(), Some("ed25519"));
+        assert_eq!(verified.payload(), PAYLOAD);
+
+        let Jws::General(mut jws) = jws else {
+            panic!("expected the general serialization");
+        };
+
+        jws.payload = Some(b"Goodbye world!".to_vec().into());
+        assert!(matches!(jws.verify(&akey), Err(Error::Invalid)));
+        assert!(matches!(jws.verify(&bkey), Err(Error::Invalid)));
+    }
+
+    #[test]
+    fn unencoded() {
+        let (jwk, key) = hmac();
+        let prot = Protected {
+            crit: Some(vec!["b64".into()]),
+            b64: false,
+            ..Default::default()
+        };
+
+        let jws = Builder::new(PAYLOAD)
+            .jwk(&jwk, Some(prot), None)
+            .unwrap()
+            .flattened(&mut OsRng)
+            .unwrap();
+
+        let verified = jws.verify(&key).unwrap();
+        assert_eq!(verified.payload(), PAYLOAD);
+        assert!(!verified.header().b64);
+
+        // Flipping b64 changes the signing input.
+        let mut bad = jws.clone();
+        let mut prot = (**bad.signature.protected.as_ref().unwrap()).clone();
+        prot.b64 = true;
+        prot.crit = None;
+        bad.signature.protected = Some(jose_b64::serde::Json::new(prot).unwrap());
+        assert!(matches!(bad.verify(&key), Err(Error::Invalid)));
+    }
+}
+
+#[cfg(test)]
+#[cfg(all(feature = "hmac", feature = "ed25519"))]
+mod set {
+    use jose_jwk::JwkSet;
+    use jose_jws::crypto::{Builder, Error};
+    use jose_jws::{Protected, Unprotected};
+    use rand_core::OsRng;
+
+    use super::HMAC;
+
+    const PAYLOAD: &[u8] = b"Hello world!";
+
+    fn set(keys: serde_json::Value) -> JwkSet {
+        serde_json::from_value(serde_json::json!({ "keys": keys })).unwrap()
+    }
+
+    fn sign(kid: Option<&str>) -> jose_jws::Flattened {
+        let mut jwk = super::hmac();
+        jwk.prm.kid = None;
+
+        let prot = Protected {
+            oth: Unprotected {
+                kid: kid.map(Into::into),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        Builder::new(PAYLOAD)
+            .jwk(&jwk, Some(prot), None)
+            .unwrap()
+            .flattened(&mut OsRng)
+            .unwrap()
+    }
+
+    #[test]
+    fn kid() {
+        let set = set(serde_json::json!([
+            { "kty": "oct", "kid": "other", "k": "c2VjcmV0c2VjcmV0c2VjcmV0c2VjcmV0c2VjcmV0MTI" },
+            { "kty": "oct", "kid": "hmac", "k": HMAC },
+            {
+                "kty": "OKP",
+                "crv": "Ed25519",
+                "kid": "hmac",
+                "x": super::ED25519_X,
+            },
+        ]));
+
+        let jws = sign(Some("hmac"));
+        let verified = jws.verify(&set).unwrap();
+        assert_eq!(verified.payload(), PAYLOAD);
+
+        // A kid that names the wrong key is not rescued by the other keys.
+        let jws = sign(Some("other"));
+        assert!(matches!(jws.verify(&set), Err(Error::Invalid)));
+
+        // An unknown kid has no candidates at all.
+        let jws = sign(Some("unknown"));
+        assert!(matches!(jws.verify(&set), Err(Error::NoKey)));
+
+        // Without a kid, every compatible key is tried.
+        sign(None).verify(&set).unwrap();
+    }
+
+    #[test]
+    fn parameters() {
+        for (prm, ok) in [
+            (serde_json::json!({}), true),
+            (serde_json::json!({ "alg": "HS256" }), true),
+            (serde_json::json!({ "use": "sig" }), true),
+            (serde_json::json!({ "use": "enc" }), false),
+            (serde_json::json!({ "key_ops": ["sign", "verify"] }), true),
+            (serde_json::json!({ "key_ops": ["sign"] }), false),
+        ] {
+            let mut key = serde_json::json!({ "kty": "oct", "kid": "hmac", "k": HMAC });
+            key.as_object_mut()
+                .unwrap()
+                .extend(prm.as_object().unwrap().clone());
+
+            let set = set(serde_json::json!([key]));
+            let jws = sign(Some("hmac"));
+            let res = jws.verify(&set);
+            assert_eq!(res.is_ok(), ok, "{prm}");
+            if !ok {
+                assert!(matches!(res, Err(Error::NoKey)), "{prm}");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "hmac")]
+mod policy {
+    use jose_b64::serde::Json;
+    use jose_jwa::Signing;
+    use jose_jwk::{crypto, Jwk, JwkSet};
+    use jose_jws::crypto::{Builder, Error, Policy};
+    use jose_jws::{Flattened, General, Protected, Signature, Unprotected};
+    use rand_core::OsRng;
+
+    const PAYLOAD: &[u8] = b"Hello world!";
+
+    fn hmac(alg: Signing) -> Jwk {
+        let mut jwk = super::hmac();
+        jwk.prm.alg = Some(alg.into());
+        jwk
+    }
+
+    fn sign(jwk: &Jwk) -> Flattened {
+        Builder::new(PAYLOAD)
+            .jwk(jwk, None, None)
+            .unwrap()
+            .flattened(&mut OsRng)
+            .unwrap()
+    }
+
+    fn unsecured(signature: &[u8]) -> Flattened {
+        let prot = Protected {
+            oth: Unprotected {
+                alg: Some(Signing::Null),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        Flattened {
+            payload: Some(PAYLOAD.to_vec().into()),
+            signature: Signature {
+                protected: Some(Json::new(prot).unwrap()),
+                header: None,
+                signature: signature.to_vec().into(),
+            },
+        }
+    }
+
+    #[test]
+    fn allowed() {
+        let jwk = hmac(Signing::Hs256);
+        let key = crypto::Key::try_from(&jwk.key).unwrap();
+        let jws = sign(&jwk);
+
+        let policy = Policy::new([Signing::Hs256, Signing::Es256]);
+        jws.verify_with(&key, &policy).unwrap();
+        jws.verify_with(&jwk, &policy).unwrap();
+
+        let policy = Policy::new([Signing::Es256]);
+        assert!(matches!(
+            jws.verify_with(&key, &policy),
+            Err(Error::Disallowed)
+        ));
+
+        // Listing "none" does not allow unsecured signatures by itself.
+        let policy = Policy::new([Signing::Null]);
+        assert!(!policy.allows(Signing::Null));
+
+        let mut jws = jws;
+        jws.signature.protected = None;
+        let policy = Policy::new([Signing::Hs256]);
+        assert!(matches!(
+            jws.verify_with(&key, &policy),
+            Err(Error::MissingAlg)
+        ));
+    }
+
+    #[test]
+    fn null() {
+        let jwk = hmac(Signing::Hs256);
+        let key = crypto::Key::try_from(&jwk.key).unwrap();
+        let jws = unsecured(b"");
+
+        // No key supports "none" without a policy.
+        assert!(jws.verify(&key).is_err());
+
+        let policy = Policy::new([Signing::Hs256]);
+        assert!(matches!(
+            jws.verify_with(&key, &policy),
+            Err(Error::Unsecured)
+        ));
+
+        assert!(matches!(jws.unsecured(&policy), Err(Error::Unsecured)));
+
+        // Allowing unsecured signatures never makes them verified.
+        let policy = policy.unsecured(true);
+        assert!(matches!(
+            jws.verify_with(&key, &policy),
+            Err(Error::Unsecured)
+        ));
+        let detached = Flattened {
+            payload: None,
+            ..jws.clone()
+        };
+        assert!(matches!(
+            detached.verify_detached_with(&key, &policy, PAYLOAD),
+            Err(Error::Unsecured)
+        ));
+
+        let accepted = jws.unsecured(&policy).unwrap();
+        assert_eq!(accepted.payload(), PAYLOAD);
+        assert_eq!(accepted.header().oth.alg, Some(Signing::Null));
+
+        let general = General::from(jws);
+        assert_eq!(general.unsecured(&policy).unwrap().payload(), PAYLOAD);
+
+        let jws = unsecured(b"signature");
+        assert!(matches!(jws.unsecured(&policy), Err(Error::Invalid)));
+
+        // A signed JWS is not accepted as unsecured.
+        let jws = sign(&jwk);
+        let policy = Policy::new([Signing::Hs256]).unsecured(true);
+        assert!(matches!(jws.unsecured(&policy), Err(Error::NoKey)));
+    }
+
+    #[test]
+    fn jwk_alg() {
+        let jws = sign(&hmac(Signing::Hs256));
+        let policy = Policy::new([Signing::Hs256]);
+
+        // The key material is the same, but the JWK is pinned to HS512.
+        let jwk = hmac(Signing::Hs512);
+        assert!(matches!(jws.verify(&jwk), Err(Error::AlgMismatch)));
+        assert!(matches!(
+            jws.verify_with(&jwk, &policy),
+            Err(Error::AlgMismatch)
+        ));
+
+        let set = JwkSet { keys: vec![jwk] };
+        assert!(matches!(jws.verify(&set), Err(Error::AlgMismatch)));
+
+        let set = JwkSet {
+            keys: vec![hmac(Signing::Hs512), hmac(Signing::Hs256)],
+        };
+        jws.verify_with(&set, &policy).unwrap();
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "hmac")]
+mod crit {
+    use hmac::{Hmac, Mac};
+    use jose_b64::base64ct::{Base64UrlUnpadded, Encoding};
+    use jose_jwa::Signing;
+    use jose_jws::crypto::{Error, Policy};
+    use jose_jws::Flattened;
+    use sha2::Sha256;
+
+    use super::{secret, SECRET};
+
+    const IAT: &str = "http://openbanking.org.uk/iat";
+
+    // The protected header is written and signed by hand so that it can
+    // contain anything.
+    fn sign(prot: serde_json::Value) -> Flattened {
+        let prot = Base64UrlUnpadded::encode_string(prot.to_string().as_bytes());
+        let payl = Base64UrlUnpadded::encode_string(b"Hello world!");
+
+        let mut mac = Hmac::<Sha256>::new_from_slice(&SECRET).unwrap();
+        mac.update(format!("{prot}.{payl}").as_bytes());
+        let sign = Base64UrlUnpadded::encode_string(&mac.finalize().into_bytes());
+
+        format!("{prot}.{payl}.{sign}").parse().unwrap()
+    }
+
+    #[test]
+    fn understood() {
+        let key = secret();
+        let policy = Policy::new([Signing::Hs256]).critical(IAT);
+
+        let jws = sign(serde_json::json!({ "alg": "HS256" }));
+        jws.verify(&key).unwrap();
+
+        let jws = sign(serde_json::json!({ "alg": "HS256", "crit": [IAT], IAT: 1501497671 }));
+        assert!(matches!(jws.verify(&key), Err(Error::Critical)));
+        jws.verify_with(&key, &policy).unwrap();
+
+        // b64 is implemented by this crate and needs no declaration.
+        let jws = sign(serde_json::json!({ "alg": "HS256", "crit": ["b64"], "b64": true }));
+        jws.verify(&key).unwrap();
+    }
+
+    #[test]
+    fn rejected() {
+        let key = secret();
+        let policy = Policy::new([Signing::Hs256]).critical(IAT).critical("kid");
+
+        for prot in [
+            // An empty list.
+            serde_json::json!({ "alg": "HS256", "crit": [] }),
+            // A listed parameter that is missing.
+            serde_json::json!({ "alg": "HS256", "crit": [IAT] }),
+            // A parameter registered by RFC 7515.
+            serde_json::json!({ "alg": "HS256", "crit": ["kid"], "kid": "x" }),
+            // A parameter the application does not understand.
+            serde_json::json!({ "alg": "HS256", "crit": ["exp"], "exp": 0 }),
+        ] {
+            let jws = sign(prot.clone());
+            let res = jws.verify_with(&key, &policy);
+            assert!(matches!(res, Err(Error::Critical)), "{prot}");
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "hmac")]
+mod rfc7797 {
+    use jose_b64::base64ct::{Base64UrlUnpadded, Encoding};
+    use jose_jwa::Signing;
+    use jose_jwk::crypto;
+    use jose_jws::crypto::{Builder, Error, SigningKey};
+    use jose_jws::{CompactError, Flattened, General, Protected, Unprotected};
+    use rand_core::OsRng;
+
+    const PAYLOAD: &[u8] = b"$.02";
+    const PROTECTED: &str = "eyJhbGciOiJIUzI1NiIsImI2NCI6ZmFsc2UsImNyaXQiOlsiYjY0Il19";
+    const SIGNATURE: &str = "A5dxf2s96_n5FLueVuW1Z_vh161FwXZC4YLPff6dmDY";
+
+    fn key() -> crypto::Key {
+        super::key(&super::hmac())
+    }
+
+    fn encoded() -> Protected {
+        Protected {
+            oth: Unprotected {
+                alg: Some(Signing::Hs256),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    fn unencoded() -> Protected {
+        Protected {
+            crit: Some(vec!["b64".into()]),
+            b64: false,
+            ..encoded()
+        }
+    }
+
+    #[test]
+    fn s4_2() {
+        let key = key();
+
+        // The unencoded payload appears as a JSON string.
+        let jws: Flattened = serde_json::from_value(serde_json::json!({
+            "protected": PROTECTED,
+            "payload": "$.02",
+            "signature": SIGNATURE,
+        }))
+        .unwrap();
+
+        assert_eq!(&jws.payload.as_ref().unwrap()[..], PAYLOAD);
+        assert_eq!(jws.verify(&key).unwrap().payload(), PAYLOAD);
+
+        let val = serde_json::to_value(&jws).unwrap();
+        assert_eq!(val["payload"], "$.02");
+
+        // The compact serialization is detached, which to_compact rejects.
+        let compact = jws.to_string();
+        assert_eq!(compact, format!("{PROTECTED}..{SIGNATURE}"));
+        assert_eq!(jws.to_compact(), Err(CompactError::Unencoded));
+        assert_eq!(
+            General::from(jws.clone()).to_compact(),
+            Err(CompactError::Unencoded)
+        );
+
+        let mut jws: Flattened = compact.parse().unwrap();
+        assert!(jws.payload.is_none());
+        assert!(matches!(jws.verify(&key), Err(Error::Detached)));
+
+        jws.payload = Some(PAYLOAD.to_vec().into());
+        assert_eq!(jws.verify(&key).unwrap().payload(), PAYLOAD);
+    }
+
+    #[test]
+    fn serialize() {
+        // The default of true is omitted, so that ordinary headers are not
+        // changed by the extension.
+        let val = serde_json::to_value(encoded()).unwrap();
+        assert_eq!(val, serde_json::json!({ "alg": "HS256" }));
+
+        let val = serde_json::to_value(unencoded()).unwrap();
+        assert_eq!(
+            val,
+            serde_json::json!({ "alg": "HS256", "b64": false, "crit": ["b64"] })
+        );
+
+        let prot: Protected = serde_json::from_value(val).unwrap();
+        assert!(!prot.b64);
+
+        let prot: Protected = serde_json::from_str(r#"{"alg":"HS256","b64":true}"#).unwrap();
+        assert!(prot.b64);
+    }
+
+    #[test]
+    fn roundtrip() {
+        let key = key();
+
+        let jws = Builder::new(PAYLOAD)
+            .key(&key, Some(unencoded()), None)
+            .general(&mut OsRng)
+            .unwrap();
+
+        let json = serde_json::to_string(&jws).unwrap();
+        let jws: General = serde_json::from_str(&json).unwrap();
+        assert_eq!(jws.verify(&key).unwrap().payload(), PAYLOAD);
+
+        // An unencoded payload that is not UTF-8 cannot appear in JSON.
+        let jws = Builder::new(&[0xff, 0xfe])
+            .key(&key, Some(unencoded()), None)
+            .flattened(&mut OsRng)
+            .unwrap();
+
+        assert!(serde_json::to_string(&jws).is_err());
+    }
+
+    #[test]
+    fn critical() {
+        let key = key();
+
+        // b64 must be listed in crit when signing.
+        let prot = Protected {
+            crit: None,
+            ..unencoded()
+        };
+
+        assert!(matches!(key.sign(Some(prot), None), Err(Error::Critical)));
+
+        // And when verifying.
+        let prot = serde_json::json!({ "alg": "HS256", "b64": false }).to_string();
+        let prot = Base64UrlUnpadded::encode_string(prot.as_bytes());
+
+        let mut jws: Flattened = format!("{prot}..{SIGNATURE}").parse().unwrap();
+        jws.payload = Some(PAYLOAD.to_vec().into());
+        assert!(matches!(jws.verify(&key), Err(Error::Critical)));
+    }
+
+    #[test]
+    fn mixed() {
+        let key = key();
+
+        let res = Builder::new(PAYLOAD)
+            .key(&key, Some(unencoded()), None)
+            .key(&key, Some(encoded()), None)
+            .general(&mut OsRng);
+
+        assert!(matches!(res, Err(Error::MixedB64)));
+
+        let a = Builder::new(PAYLOAD)
+            .key(&key, Some(unencoded()), None)
+            .flattened(&mut OsRng)
+            .unwrap();
+
+        let b = Builder::new(PAYLOAD)
+            .key(&key, Some(encoded()), None)
+            .flattened(&mut OsRng)
+            .unwrap();
+
+        let mut jws = General::from(a);
+        jws.signatures.push(b.signature);
+        assert!(matches!(jws.verify(&key), Err(Error::MixedB64)));
+        assert!(serde_json::to_string(&jws).is_err());
+
+        let json = serde_json::json!({
+            "payload": "$.02",
+            "signatures": [
+                { "protected": PROTECTED, "signature": SIGNATURE },
+                { "protected": "eyJhbGciOiJIUzI1NiJ9", "signature": SIGNATURE },
+            ],
+        });
+
+        assert!(serde_json::from_value::<General>(json).is_err());
+    }
+}
+
+#[cfg(test)]
+#[cfg(all(feature = "hmac", feature = "ed25519"))]
+mod detached {
+    use jose_b64::stream::Update;
+    use jose_jwk::{crypto, Jwk};
+    use jose_jws::crypto::{Builder, Error, Policy};
+    use jose_jws::{Flattened, General, Jws};
+    use rand_core::OsRng;
+
+    const BODY: &[u8] = b"{\"amount\":\"10.00\",\"currency\":\"GBP\"}";
+
+    fn jwk() -> (Jwk, crypto::Key) {
+        let jwk = super::ed25519();
+        let key = super::key(&jwk);
+        (jwk, key)
+    }
+
+    #[test]
+    fn compact() {
+        let (jwk, key) = jwk();
+
+        let header = Builder::new(BODY)
+            .jwk(&jwk, None, None)
+            .unwrap()
+            .detach()
+            .compact(&mut OsRng)
+            .unwrap();
+
+        let (prot, rest) = header.split_once('.').unwrap();
+        assert!(!prot.is_empty());
+        assert!(rest.starts_with('.'));
+
+        let jws: Flattened = header.parse().unwrap();
+        assert!(jws.payload.is_none());
+
+        let prot = jws.verify_detached(&key, BODY).unwrap();
+        assert_eq!(prot.oth.kid.as_deref(), Some("ed25519"));
+
+        let policy = Policy::new([jose_jwa::Signing::EdDsa]);
+        jws.verify_detached_with(&key, &policy, BODY).unwrap();
+
+        let res = jws.verify_detached(&key, b"{}");
+        assert!(matches!(res, Err(Error::Invalid)));
+    }
+
+    #[test]
+    fn stream() {
+        let (jwk, key) = jwk();
+
+        let jws = Builder::new(BODY)
+            .jwk(&jwk, None, None)
+            .unwrap()
+            .detach()
+            .flattened(&mut OsRng)
+            .unwrap();
+
+        let mut ver = jws.detached(&key, None).unwrap();
+        for chunk in BODY.chunks(5) {
+            ver.update(chunk).unwrap();
+        }
+        ver.finish().unwrap();
+
+        let mut ver = jws.detached(&key, None).unwrap();
+        ver.update(&BODY[1..]).unwrap();
+        assert!(matches!(ver.finish(), Err(Error::Invalid)));
+
+        let policy = Policy::new([jose_jwa::Signing::Hs256]);
+        let res = jws.detached(&key, Some(&policy));
+        assert!(matches!(res, Err(Error::Disallowed)));
+
+        // The payload can be signed in chunks too.
+        let prot = jose_jws::Protected {
+            oth: jose_jws::Unprotected {
+                alg: Some(jose_jwa::Signing::EdDsa),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let mut signer = Flattened::sign_detached(&key, Some(prot), None).unwrap();
+        for chunk in BODY.chunks(7) {
+            signer.update(chunk).unwrap();
+        }
+
+        let jws = signer.finish(&mut OsRng).unwrap();
+        assert!(jws.payload.is_none());
+        assert_eq!(
+            jws.verify_detached(&key, BODY).unwrap().oth.alg,
+            Some(jose_jwa::Signing::EdDsa)
+        );
+    }
+
+    #[test]
+    fn stream_general() {
+        let (jwk, key) = jwk();
+        let hmac = super::hmac();
+
+        let jws = Builder::new(BODY)
+            .jwk(&hmac, None, None)
+            .unwrap()
+            .jwk(&jwk, None, None)
+            .unwrap()
+            .detach()
+            .general(&mut OsRng)
+            .unwrap();
+
+        // The key cannot check the HMAC signature, but verifies its own.
+        let mut ver = jws.detached(&key, None).unwrap();
+        for chunk in BODY.chunks(5) {
+            ver.update(chunk).unwrap();
+        }
+        assert_eq!(ver.finish().unwrap().oth.kid.as_deref(), Some("ed25519"));
+
+        let jws = Jws::General(jws);
+        let mut ver = jws.detached(&key, None).unwrap();
+        ver.update(&BODY[1..]).unwrap();
+        assert!(matches!(ver.finish(), Err(Error::Invalid)));
+
+        jws.verify_detached(&key, BODY).unwrap();
+        let policy = Policy::new([jose_jwa::Signing::Es256]);
+        let res = jws.verify_detached_with(&key, &policy, BODY);
+        assert!(matches!(res, Err(Error::Disallowed)));
+    }
+
+    #[test]
+    fn embedded() {
+        let (jwk, key) = jwk();
+
+        let jws = Builder::new(BODY)
+            .jwk(&jwk, None, None)
+            .unwrap()
+            .flattened(&mut OsRng)
+            .unwrap();
+
+        // An embedded payload is not silently ignored in favor of another.
+        let res = jws.verify_detached(&key, b"{}");
+        assert!(matches!(res, Err(Error::Attached)));
+        assert!(matches!(jws.detached(&key, None), Err(Error::Attached)));
+
+        let jws = General::from(jws);
+        let res = jws.verify_detached(&key, BODY);
+        assert!(matches!(res, Err(Error::Attached)));
+        assert!(matches!(jws.detached(&key, None), Err(Error::Attached)));
+    }
+
+    #[test]
+    fn general() {
+        let (jwk, key) = jwk();
+
+        let jws = Builder::new(BODY)
+            .jwk(&jwk, None, None)
+            .unwrap()
+            .detach()
+            .general(&mut OsRng)
+            .unwrap();
+
+        let json = serde_json::to_value(&jws).unwrap();
+        assert!(json.get("payload").is_none());
+
+        let jws: General = serde_json::from_value(json).unwrap();
+        jws.verify_detached(&key, BODY).unwrap();
+        assert!(matches!(jws.verify(&key), Err(Error::Detached)));
+    }
+
+    #[test]
+    fn attached() {
+        let (jwk, key) = jwk();
+
+        let jws = Builder::new(BODY)
+            .jwk(&jwk, None, None)
+            .unwrap()
+            .detach()
+            .flattened(&mut OsRng)
+            .unwrap();
+
+        // A missing payload is not mistaken for an empty one.
+        assert!(matches!(jws.verify(&key), Err(Error::Detached)));
+        let policy = Policy::new([jose_jwa::Signing::EdDsa]);
+        let res = jws.verify_with(&key, &policy);
+        assert!(matches!(res, Err(Error::Detached)));
+
+        let res = jws.cosign(&key, None, None, &mut OsRng);
+        assert!(matches!(res, Err(Error::Detached)));
+
+        let set = jose_jwk::JwkSet { keys: vec![jwk] };
+        let general = General::from(jws);
+        let report = general.report(&set, None);
+        assert!(matches!(report.outcomes(), [Err(Error::Detached)]));
+    }
+}
+
+#[cfg(test)]
+#[cfg(all(feature = "std", feature = "hmac"))]
+mod io {
+    use jose_jwa::Signing;
+    use jose_jws::crypto::{io, Error, Policy};
+    use jose_jws::{Flattened, Protected, Unprotected};
+    use rand_core::OsRng;
+
+    use super::{hs256, secret};
+
+    // Larger than the read buffer and not a multiple of three.
+    fn payload() -> Vec<u8> {
+        (0..100_000u32).map(|i| i as u8).collect()
+    }
+
+    #[test]
+    fn detached() {
+        let key = secret();
+        let payload = payload();
+
+        let sig = io::sign(&key, Some(hs256()), None, &payload[..], &mut OsRng).unwrap();
+        io::verify(&key, &sig, &payload[..]).unwrap();
+
+        let policy = Policy::new([Signing::Hs256]);
+        io::verify_with(&key, &policy, &sig, &payload[..]).unwrap();
+
+        let res = io::verify(&key, &sig, &payload[1..]);
+        assert!(matches!(res, Err(Error::Invalid)));
+
+        let policy = Policy::new([Signing::Es256]);
+        let res = io::verify_with(&key, &policy, &sig, &payload[..]);
+        assert!(matches!(res, Err(Error::Disallowed)));
+    }
+
+    #[test]
+    fn compact() {
+        let key = secret();
+        let payload = payload();
+
+        let mut out = Vec::new();
+        io::write_compact(&key, hs256(), &payload[..], &mut out, &mut OsRng).unwrap();
+
+        let jws: Flattened = String::from_utf8(out).unwrap().parse().unwrap();
+        assert_eq!(jws.verify(&key).unwrap().payload(), payload);
+    }
+
+    #[test]
+    fn unencoded() {
+        let key = secret();
+        let prot = Protected {
+            crit: Some(vec!["b64".into()]),
+            b64: false,
+            ..hs256()
+        };
+
+        let mut out = Vec::new();
+        io::write_compact(&key, prot.clone(), &b"$.02"[..], &mut out, &mut OsRng).unwrap();
+
+        let jws: Flattened = String::from_utf8(out).unwrap().parse().unwrap();
+        assert!(jws.payload.is_none());
+        jws.verify_detached(&key, b"$.02").unwrap();
+
+        let res = io::write_flattened(&key, Some(prot), None, &b""[..], Vec::new(), &mut OsRng);
+        assert!(matches!(res, Err(Error::Serialization)));
+    }
+
+    #[test]
+    fn flattened() {
+        let key = secret();
+        let payload = payload();
+        let head = Unprotected {
+            kid: Some("seven".into()),
+            ..Default::default()
+        };
+
+        let mut out = Vec::new();
+        io::write_flattened(
+            &key,
+            Some(hs256()),
+            Some(head),
+            &payload[..],
+            &mut out,
+            &mut OsRng,
+        )
+        .unwrap();
+
+        let jws: Flattened = serde_json::from_slice(&out).unwrap();
+        let verified = jws.verify(&key).unwrap();
+        assert_eq!(verified.payload(), payload);
+        assert_eq!(verified.header().oth.kid.as_deref(), Some("seven"));
+    }
+}
+
+#[cfg(test)]
+mod parse {
+    use jose_jws::{CompactError, Jws, ParseError};
+
+    const COMPACT: &str = concat!(
+        "eyJ0eXAiOiJKV1QiLA0KICJhbGciOiJIUzI1NiJ9",
+        ".",
+        "eyJpc3MiOiJqb2UiLA0KICJleHAiOjEzMDA4MTkzODAsDQogImh0dHA6Ly9leGFtcGxlLmNvbS9pc19yb290Ijp0cnVlfQ",
+        ".",
+        "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk"
+    );
+
+    #[test]
+    fn detect() {
+        let jws = Jws::parse(COMPACT).unwrap();
+        assert!(matches!(jws, Jws::Flattened(..)));
+        assert_eq!(jws.to_compact().unwrap(), COMPACT);
+
+        let json = serde_json::to_string(&jws).unwrap();
+        let jws = Jws::parse(&json).unwrap();
+        assert!(matches!(jws, Jws::Flattened(..)));
+        assert_eq!(jws.to_compact().unwrap(), COMPACT);
+
+        let Jws::Flattened(flattened) = jws else {
+            unreachable!()
+        };
+
+        let general = jose_jws::General::from(flattened);
+        let json = serde_json::to_string(&general).unwrap();
+        let jws = Jws::parse(&json).unwrap();
+        assert!(matches!(jws, Jws::General(..)));
+        assert_eq!(jws.to_compact().unwrap(), COMPACT);
+
+        // Deserialization detects the serialization in the same way.
+        let jws: Jws = serde_json::from_str(&json).unwrap();
+        assert!(matches!(jws, Jws::General(..)));
+    }
+
+    #[test]
+    fn errors() {
+        assert!(matches!(Jws::parse("a.b"), Err(ParseError::Compact(..))));
+        assert!(matches!(Jws::parse("{"), Err(ParseError::Json(..))));
+        assert!(matches!(Jws::parse("[]"), Err(ParseError::Compact(..))));
+
+        let err = Jws::parse(r#"{"payload": "e30"}"#).unwrap_err();
+        assert!(matches!(err, ParseError::Flattened(..)));
+        assert!(err.to_string().contains("signature"), "{err}");
+
+        let err = Jws::parse(r#"{"signatures": [{}]}"#).unwrap_err();
+        assert!(matches!(err, ParseError::General(..)));
+
+        #[cfg(feature = "std")]
+        {
+            use std::error::Error;
+            assert!(err.source().is_some());
+            assert!(CompactError::Protected.source().is_none());
+        }
+
+        let err = serde_json::from_str::<Jws>(r#"{"signatures": [{}]}"#).unwrap_err();
+        assert!(err.to_string().contains("general"), "{err}");
+    }
+
+    #[test]
+    fn to_compact() {
+        let Jws::Flattened(flattened) = Jws::parse(COMPACT).unwrap() else {
+            unreachable!()
+        };
+
+        let mut general = jose_jws::General::from(flattened.clone());
+        general.signatures.push(flattened.signature.clone());
+        let res = Jws::General(general.clone()).to_compact();
+        assert_eq!(res, Err(CompactError::Signatures(2)));
+
+        general.signatures.clear();
+        assert_eq!(general.to_compact(), Err(CompactError::Signatures(0)));
+
+        let mut bad = flattened.clone();
+        bad.signature.header = Some(Default::default());
+        assert_eq!(bad.to_compact(), Err(CompactError::Unprotected));
+
+        let mut bad = flattened;
+        bad.signature.protected = None;
+        assert_eq!(bad.to_compact(), Err(CompactError::Protected));
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "hmac")]
+mod header {
+    use jose_b64::base64ct::{Base64UrlUnpadded, Encoding};
+    use jose_jwa::Signing;
+    use jose_jws::crypto::{Builder, Error, SigningKey};
+    use jose_jws::{Flattened, HeaderError, Unprotected};
+    use rand_core::OsRng;
+
+    use super::{hs256, secret};
+
+    fn head(kid: &str) -> Unprotected {
+        Unprotected {
+            kid: Some(kid.into()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn merged() {
+        let key = secret();
+
+        let jws = Builder::new(b"Hello world!")
+            .key(&key, Some(hs256()), Some(head("seven")))
+            .flattened(&mut OsRng)
+            .unwrap();
+
+        jws.signature.validate().unwrap();
+
+        let header = jws.signature.header();
+        assert_eq!(header.oth.alg, Some(Signing::Hs256));
+        assert_eq!(header.oth.kid.as_deref(), Some("seven"));
+        assert!(header.b64);
+    }
+
+    #[test]
+    fn overlap() {
+        let key = secret();
+
+        let mut both = hs256();
+        both.oth.kid = Some("seven".into());
+
+        let res = key.sign(Some(both), Some(head("eight")));
+        assert!(matches!(res, Err(Error::Header(HeaderError::Overlap(n))) if n == "kid"));
+
+        let mut jws = Builder::new(b"Hello world!")
+            .key(&key, Some(hs256()), None)
+            .flattened(&mut OsRng)
+            .unwrap();
+
+        jws.signature.header = Some(Unprotected {
+            alg: Some(Signing::Hs512),
+            ..Default::default()
+        });
+
+        let res = jws.verify(&key);
+        assert!(matches!(res, Err(Error::Header(HeaderError::Overlap(n))) if n == "alg"));
+    }
+
+    #[test]
+    fn duplicate() {
+        // Duplicate registered parameters are rejected when parsing, but
+        // extension parameters are not.
+        let prot = r#"{"alg":"HS256","kid":"a","kid":"b"}"#;
+        let prot = Base64UrlUnpadded::encode_string(prot.as_bytes());
+        assert!(format!("{prot}.e30.AAAA").parse::<Flattened>().is_err());
+
+        let prot = r#"{"alg":"HS256","iat":1,"iat":2}"#;
+        let prot = Base64UrlUnpadded::encode_string(prot.as_bytes());
+        let jws: Flattened = format!("{prot}.e30.AAAA").parse().unwrap();
+
+        let res = jws.signature.validate();
+        assert!(matches!(res, Err(HeaderError::Duplicate(n)) if n == "iat"));
+
+        let res = jws.verify(&secret());
+        assert!(matches!(
+            res,
+            Err(Error::Header(HeaderError::Duplicate(..)))
+        ));
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "hmac")]
+mod ext {
+    use jose_jwa::Signing;
+    use jose_jws::crypto::{Builder, Policy};
+    use jose_jws::{Flattened, Protected, Unprotected};
+    use rand_core::OsRng;
+
+    const IAT: &str = "http://openbanking.org.uk/iat";
+
+    #[test]
+    fn roundtrip() {
+        let prot: Protected = serde_json::from_value(serde_json::json!({
+            "alg": "ES256",
+            "typ": "dpop+jwt",
+            "htm": "POST",
+            "iat": 1562262616,
+            "x5t#S256": "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA",
+        }))
+        .unwrap();
+
+        assert_eq!(prot.oth.typ.as_deref(), Some("dpop+jwt"));
+        assert!(prot.oth.x5t.s256.is_some());
+        assert_eq!(prot.oth.ext.len(), 2);
+        assert_eq!(prot.oth.ext["htm"], "POST");
+        assert_eq!(prot.oth.ext["iat"], 1562262616);
+
+        // The protected header is kept exactly as received.
+        let compact = concat!(
+            "eyJhbGciOiJIUzI1NiIsICJ6enoiOiB7ImIiOiAxLCAiYSI6IDJ9LCAidHJ1c3RfY2hhaW4iOiBbXX0",
+            ".e30.AAAA"
+        );
+
+        let jws: Flattened = compact.parse().unwrap();
+        let prot = jws.signature.protected.as_ref().unwrap();
+        assert_eq!(prot.oth.ext["zzz"]["a"], 2);
+        assert_eq!(prot.oth.ext["trust_chain"], serde_json::json!([]));
+        assert_eq!(jws.to_string(), compact);
+    }
+
+    #[test]
+    fn signed() {
+        let key = super::secret();
+
+        let mut prot = Protected {
+            crit: Some(vec![IAT.into()]),
+            oth: Unprotected {
+                alg: Some(Signing::Hs256),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        prot.oth.ext.insert(IAT.into(), 1501497671.into());
+
+        let mut head = Unprotected::default();
+        head.ext.insert("note".into(), "unprotected".into());
+
+        let jws = Builder::new(b"Hello world!")
+            .key(&key, Some(prot), Some(head))
+            .flattened(&mut OsRng)
+            .unwrap();
+
+        let json = serde_json::to_value(&jws).unwrap();
+        assert_eq!(json["header"]["note"], "unprotected");
+
+        let jws: Flattened = serde_json::from_value(json).unwrap();
+        let policy = Policy::new([Signing::Hs256]).critical(IAT);
+        let verified = jws.verify_with(&key, &policy).unwrap();
+        assert_eq!(verified.header().oth.ext[IAT], 1501497671);
+        assert_eq!(verified.header().oth.ext["note"], "unprotected");
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "hmac")]
+mod typed {
+    use jose_b64::serde::Json;
+    use jose_jws::crypto::{Builder, Error};
+    use jose_jws::{Flattened, Jws};
+    use rand_core::OsRng;
+
+    use super::{hs256, secret};
+    use serde::Deserialize;
+
+    #[derive(Clone, Debug, Deserialize)]
+    struct Claims {
+        iss: String,
+        exp: u64,
+    }
+
+    fn sign(payload: &[u8]) -> Flattened {
+        Builder::new(payload)
+            .key(&secret(), Some(hs256()), None)
+            .flattened(&mut OsRng)
+            .unwrap()
+    }
+
+    #[test]
+    fn verify() {
+        // The payload bytes are preserved exactly, whitespace included.
+        let payload = br#"{ "iss": "joe",  "exp": 1300819380 }"#;
+        let compact = sign(payload).to_string();
+
+        let jws: Flattened<Json<Claims>> = compact.parse().unwrap();
+        assert_eq!(jws.to_string(), compact);
+
+        let verified = jws.verify(&secret()).unwrap();
+        assert_eq!(verified.payload(), payload);
+
+        let claims = verified.value();
+        assert_eq!(claims.iss, "joe");
+        assert_eq!(claims.exp, 1300819380);
+
+        let json = serde_json::to_string(&jws).unwrap();
+        let jws: Jws<Json<Claims>> = serde_json::from_str(&json).unwrap();
+        let claims = jws.verify(&secret()).unwrap().value();
+        assert_eq!(claims.iss, "joe");
+    }
+
+    #[test]
+    fn convert() {
+        let jws = sign(br#"{"iss":"joe","exp":0}"#);
+        let jws = jws.try_convert::<Json<Claims>>().unwrap();
+        assert_eq!(jws.verify(&secret()).unwrap().value().iss, "joe");
+
+        let mut bad = jws.clone();
+        let eve: jose_b64::serde::Bytes = br#"{"iss":"eve","exp":0}"#.to_vec().into();
+        bad.payload = Some(Json::try_from(eve).unwrap());
+        assert!(matches!(bad.verify(&secret()), Err(Error::Invalid)));
+    }
+
+    #[test]
+    fn invalid() {
+        let compact = sign(b"not json").to_string();
+        assert!(compact.parse::<Flattened<Json<Claims>>>().is_err());
+        assert!(compact.parse::<Flattened>().is_ok());
+        assert!(Jws::<Json<Claims>>::parse_typed(&compact).is_err());
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "hmac")]
+mod quorum {
+    use jose_jwa::Signing;
+    #[cfg(feature = "ed25519")]
+    use jose_jwk::JwkSet;
+    use jose_jwk::{crypto, Jwk};
+    use jose_jws::crypto::{Builder, Error, Policy, Quorum};
+    use jose_jws::{General, Protected, Unprotected};
+    use rand_core::OsRng;
+
+    use super::signers;
+
+    const PAYLOAD: &[u8] = b"release v1.2.3";
+
+    fn sign(keys: &[&Jwk]) -> General {
+        let mut builder = Builder::new(PAYLOAD);
+        for key in keys {
+            builder = builder.jwk(key, None, None).unwrap();
+        }
+
+        builder.general(&mut OsRng).unwrap()
+    }
+
+    #[test]
+    fn satisfied() {
+        let set = signers();
+        let jws = sign(&[&set.keys[0], &set.keys[1]]);
+
+        let report = jws.report(&set, None);
+        assert_eq!(report.passed().collect::<Vec<_>>(), [0, 1]);
+        assert_eq!(report.failed().count(), 0);
+        assert_eq!(
+            report.outcomes()[1].as_ref().unwrap().prm.kid.as_deref(),
+            Some("b")
+        );
+
+        report.check(&Quorum::Any).unwrap();
+        report.check(&Quorum::All).unwrap();
+        report.check(&Quorum::Threshold(2)).unwrap();
+        report
+            .check(&Quorum::Kids(vec!["a".into(), "b".into()]))
+            .unwrap();
+
+        assert!(matches!(
+            report.check(&Quorum::Threshold(3)),
+            Err(Error::Quorum)
+        ));
+        assert!(!report.satisfies(&Quorum::Kids(vec!["c".into()])));
+
+        let policy = Policy::new([Signing::Es256]);
+        let report = jws.report(&set, Some(&policy));
+        assert!(report.failed().all(|(_, e)| matches!(e, Error::Disallowed)));
+        assert!(!report.satisfies(&Quorum::Any));
+    }
+
+    #[test]
+    fn partial() {
+        let set = signers();
+        let mut jws = sign(&[&set.keys[0], &set.keys[1]]);
+        jws.signatures[1].signature = jws.signatures[0].signature.clone();
+
+        let report = jws.report(&set, None);
+        assert_eq!(report.passed().collect::<Vec<_>>(), [0]);
+        assert!(matches!(
+            report.failed().collect::<Vec<_>>()[..],
+            [(1, Error::Invalid)]
+        ));
+
+        assert!(report.satisfies(&Quorum::Any));
+        assert!(!report.satisfies(&Quorum::All));
+        assert!(!report.satisfies(&Quorum::Threshold(2)));
+    }
+
+    #[test]
+    fn distinct() {
+        let set = signers();
+
+        // The same key signing twice counts once.
+        let jws = sign(&[&set.keys[0], &set.keys[0]]);
+        let report = jws.report(&set, None);
+        assert!(report.satisfies(&Quorum::All));
+        assert!(!report.satisfies(&Quorum::Threshold(2)));
+
+        // A signature claiming another key identifier does not count for it.
+        let key = crypto::Key::try_from(&set.keys[2].key).unwrap();
+        let prot = Protected {
+            oth: Unprotected {
+                alg: Some(Signing::Hs256),
+                kid: Some("a".into()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let jws = Builder::new(PAYLOAD)
+            .key(&key, Some(prot), None)
+            .general(&mut OsRng)
+            .unwrap();
+
+        let report = jws.report(&set, None);
+        assert!(matches!(report.outcomes(), [Err(Error::Invalid)]));
+        assert!(!report.satisfies(&Quorum::Kids(vec!["a".into()])));
+    }
+
+    #[test]
+    fn aliased() {
+        // The same key material listed under two key identifiers.
+        let mut set = signers();
+        let mut alias = set.keys[0].clone();
+        alias.prm.kid = Some("d".into());
+        set.keys.push(alias);
+
+        let jws = sign(&[&set.keys[0], &set.keys[3]]);
+        let report = jws.report(&set, None);
+        assert_eq!(report.passed().collect::<Vec<_>>(), [0, 1]);
+
+        assert!(report.satisfies(&Quorum::All));
+        assert!(report.satisfies(&Quorum::Threshold(1)));
+        assert!(!report.satisfies(&Quorum::Threshold(2)));
+        assert!(report.satisfies(&Quorum::Kids(vec!["a".into(), "a".into()])));
+        assert!(!report.satisfies(&Quorum::Kids(vec!["a".into(), "d".into()])));
+
+        let jws = sign(&[&set.keys[0], &set.keys[1]]);
+        let report = jws.report(&set, None);
+        assert!(report.satisfies(&Quorum::Threshold(2)));
+        assert!(report.satisfies(&Quorum::Kids(vec!["a".into(), "b".into()])));
+    }
+
+    #[cfg(feature = "ed25519")]
+    #[test]
+    fn keypair() {
+        // The private and the public JWK of one key, under two identifiers.
+        let mut private = super::ed25519();
+        private.prm.kid = Some("a".into());
+        let mut public = super::public(private.clone());
+        public.prm.kid = Some("b".into());
+        let set = JwkSet {
+            keys: vec![private.clone(), public],
+        };
+
+        let prot = |kid: &str| Protected {
+            oth: Unprotected {
+                alg: Some(Signing::EdDsa),
+                kid: Some(kid.into()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let key = super::key(&private);
+        let jws = Builder::new(PAYLOAD)
+            .key(&key, Some(prot("a")), None)
+            .key(&key, Some(prot("b")), None)
+            .general(&mut OsRng)
+            .unwrap();
+
+        let report = jws.report(&set, None);
+        assert_eq!(report.passed().collect::<Vec<_>>(), [0, 1]);
+        assert!(!report.satisfies(&Quorum::Threshold(2)));
+        assert!(!report.satisfies(&Quorum::Kids(vec!["a".into(), "b".into()])));
+    }
+
+    #[test]
+    fn empty() {
+        let set = signers();
+        let jws: General = General {
+            payload: Some(PAYLOAD.to_vec().into()),
+            signatures: Vec::new(),
+        };
+
+        let report = jws.report(&set, None);
+        assert!(!report.satisfies(&Quorum::All));
+        assert!(!report.satisfies(&Quorum::Threshold(0)));
+        assert!(!report.satisfies(&Quorum::Kids(Vec::new())));
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "hmac")]
+mod cosign {
+    use jose_jwa::Signing;
+    use jose_jwk::crypto;
+    use jose_jws::crypto::{Builder, Error, Quorum};
+    use jose_jws::{Flattened, General, Jws, Protected, Unprotected};
+    use rand_core::OsRng;
+
+    use super::signers;
+
+    const PAYLOAD: &[u8] = b"release v1.2.3";
+
+    fn prot(kid: &str, b64: bool) -> Protected {
+        Protected {
+            oth: Unprotected {
+                alg: Some(Signing::Hs256),
+                kid: Some(kid.into()),
+                ..Default::default()
+            },
+            crit: (!b64).then(|| vec!["b64".into()]),
+            b64,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn countersign() {
+        let set = signers();
+        let b = crypto::Key::try_from(&set.keys[1].key).unwrap();
+
+        let flattened: Flattened = Builder::new(PAYLOAD)
+            .jwk(&set.keys[0], None, None)
+            .unwrap()
+            .flattened(&mut OsRng)
+            .unwrap();
+
+        // The countersignature is made over the payload as received.
+        let json = serde_json::to_string(&flattened).unwrap();
+        let flattened: Flattened = serde_json::from_str(&json).unwrap();
+        let jws = flattened
+            .cosign(&b, Some(prot("b", true)), None, &mut OsRng)
+            .unwrap();
+
+        assert_eq!(jws.signatures.len(), 2);
+        assert_eq!(jws.payload.as_deref().map(AsRef::as_ref), Some(PAYLOAD));
+        jws.report(&set, None).check(&Quorum::All).unwrap();
+    }
+
+    #[test]
+    fn unencoded() {
+        let set = signers();
+        let a = crypto::Key::try_from(&set.keys[0].key).unwrap();
+        let b = crypto::Key::try_from(&set.keys[1].key).unwrap();
+
+        let mut jws: General = Builder::new(PAYLOAD)
+            .key(&a, Some(prot("a", false)), None)
+            .general(&mut OsRng)
+            .unwrap();
+
+        // A countersignature must not change the payload encoding.
+        assert!(matches!(
+            jws.cosign(&b, Some(prot("b", true)), None, &mut OsRng),
+            Err(Error::MixedB64)
+        ));
+        assert!(matches!(
+            jws.cosign(&b, None, None, &mut OsRng),
+            Err(Error::MixedB64)
+        ));
+        assert_eq!(jws.signatures.len(), 1);
+
+        jws.cosign(&b, Some(prot("b", false)), None, &mut OsRng)
+            .unwrap();
+        jws.report(&set, None).check(&Quorum::All).unwrap();
+
+        let json = serde_json::to_value(&jws).unwrap();
+        assert_eq!(json["payload"], "release v1.2.3");
+    }
+
+    #[test]
+    fn downgrade() {
+        let set = signers();
+        let jws: General = Builder::new(PAYLOAD)
+            .jwk(&set.keys[0], None, None)
+            .unwrap()
+            .jwk(&set.keys[1], None, None)
+            .unwrap()
+            .general(&mut OsRng)
+            .unwrap();
+
+        // The value is returned unchanged while there are two signatures.
+        let mut jws = Flattened::try_from(jws).unwrap_err();
+        assert_eq!(jws.signatures.len(), 2);
+
+        let removed = jws.signatures.remove(0);
+        assert_eq!(removed.header().oth.kid.as_deref(), Some("a"));
+
+        let flattened = Flattened::try_from(jws).unwrap();
+        assert_eq!(
+            flattened.payload.as_deref().map(AsRef::as_ref),
+            Some(PAYLOAD)
+        );
+        flattened.verify(&set.keys[1]).unwrap();
+
+        let empty: General = General {
+            payload: None,
+            signatures: Vec::new(),
+        };
+        assert!(Flattened::try_from(empty).is_err());
+    }
+
+    #[test]
+    fn remove() {
+        let set = signers();
+        let jws: General = Builder::new(PAYLOAD)
+            .jwk(&set.keys[0], None, None)
+            .unwrap()
+            .jwk(&set.keys[1], None, None)
+            .unwrap()
+            .jwk(&set.keys[2], None, None)
+            .unwrap()
+            .general(&mut OsRng)
+            .unwrap();
+
+        let jws = jws.remove(3).unwrap_err();
+        assert_eq!(jws.signatures.len(), 3);
+
+        let (removed, jws) = jws.remove(0).unwrap();
+        assert_eq!(removed.header().oth.kid.as_deref(), Some("a"));
+        let Jws::General(jws) = jws else {
+            unreachable!()
+        };
+
+        // The last signature left is flattened.
+        let (removed, jws) = jws.remove(1).unwrap();
+        assert_eq!(removed.header().oth.kid.as_deref(), Some("c"));
+        let Jws::Flattened(jws) = jws else {
+            unreachable!()
+        };
+        jws.verify(&set.keys[1]).unwrap();
+    }
+
+    #[test]
+    fn failure() {
+        let set = signers();
+        let a = crypto::Key::try_from(&set.keys[0].key).unwrap();
+        let b = crypto::Key::try_from(&set.keys[1].key).unwrap();
+
+        let jws: Flattened = Builder::new(PAYLOAD)
+            .key(&a, Some(prot("a", false)), None)
+            .flattened(&mut OsRng)
+            .unwrap();
+
+        // The JWS survives a failed countersignature.
+        let res = jws.cosign(&b, Some(prot("b", true)), None, &mut OsRng);
+        assert!(matches!(res, Err(Error::MixedB64)));
+
+        let jws = jws
+            .cosign(&b, Some(prot("b", false)), None, &mut OsRng)
+            .unwrap();
+        jws.report(&set, None).check(&Quorum::All).unwrap();
+    }
+}
+
+#[cfg(test)]
+#[cfg(all(
+    feature = "url",
+    feature = "ed25519",
+    feature = "hmac",
+    feature = "p256"
+))]
+mod acme {
+    use jose_jwa::Signing;
+    use jose_jwk::Jwk;
+    use jose_jws::crypto::acme::{self, Account, Violation};
+    use jose_jws::crypto::{Builder, Error};
+    use jose_jws::{Flattened, Protected, Unprotected};
+    use rand_core::OsRng;
+    use url::Url;
+
+    use super::{p256, signer, ED25519_X, P256_X, P256_Y};
+
+    const ACCOUNT: &str = "https://example.com/acme/acct/evOfKhNU60wg";
+    const NEW_ACCOUNT: &str = "https://example.com/acme/new-account";
+    const KEY_CHANGE: &str = "https://example.com/acme/key-change";
+    const ORDER: &str = "https://example.com/acme/order/TOlocE8rfgo";
+
+    fn ed25519() -> Jwk {
+        let mut jwk = super::ed25519();
+        jwk.prm.kid = None;
+        jwk
+    }
+
+    fn mac() -> Jwk {
+        signer("kid-1", "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA")
+    }
+
+    fn url(s: &str) -> Url {
+        s.parse().unwrap()
+    }
+
+    fn nonce() -> jose_b64::serde::Bytes {
+        b"oFvnlFP1wIhRlYS2jTaXbA".to_vec().into()
+    }
+
+    fn prot(jwk: Option<Jwk>, kid: Option<&str>) -> Protected {
+        Protected {
+            url: Some(url(ORDER)),
+            nonce: Some(nonce()),
+            oth: Unprotected {
+                alg: Some(Signing::Es256),
+                jwk,
+                kid: kid.map(Into::into),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    fn sign(prot: Protected, head: Option<Unprotected>) -> Flattened {
+        let jwk = p256();
+        Builder::new(b"{}")
+            .jwk(&jwk, Some(prot), head)
+            .unwrap()
+            .flattened(&mut OsRng)
+            .unwrap()
+    }
+
+    #[test]
+    fn new_account() {
+        let jwk = p256();
+        let payload = br#"{"termsOfServiceAgreed":true}"#;
+        let jws = acme::sign(&jwk, None, url(NEW_ACCOUNT), nonce(), payload, &mut OsRng).unwrap();
+
+        // Only the public key is embedded.
+        let header = jws.signature.header();
+        let embedded = header.oth.jwk.as_ref().unwrap();
+        assert!(header.oth.kid.is_none());
+        assert_eq!(
+            serde_json::to_value(embedded).unwrap(),
+            serde_json::json!({
+                "kty": "EC",
+                "crv": "P-256",
+                "x": P256_X,
+                "y": P256_Y,
+            })
+        );
+
+        // The server identifies the key and verifies the request with it.
+        let json = serde_json::to_string(&jws).unwrap();
+        let jws: Flattened = serde_json::from_str(&json).unwrap();
+        let Account::Jwk(key) = acme::account(&jws, &url(NEW_ACCOUNT)).unwrap() else {
+            panic!("expected an embedded key");
+        };
+
+        let verified = acme::verify(&jws, key, &url(NEW_ACCOUNT)).unwrap();
+        assert_eq!(verified.payload(), payload);
+        assert_eq!(verified.header().nonce, Some(nonce()));
+
+        assert!(matches!(
+            acme::verify(&jws, key, &url(ORDER)),
+            Err(Error::Acme(Violation::Url))
+        ));
+    }
+
+    #[test]
+    fn post_as_get() {
+        let jwk = p256();
+        let jws = acme::post_as_get(&jwk, &url(ACCOUNT), url(ORDER), nonce(), &mut OsRng).unwrap();
+
+        let json = serde_json::to_value(&jws).unwrap();
+        assert_eq!(json["payload"], "");
+
+        let jws: Flattened = serde_json::from_value(json).unwrap();
+        let Account::Kid(kid) = acme::account(&jws, &url(ORDER)).unwrap() else {
+            panic!("expected an account URL");
+        };
+
+        assert_eq!(kid, ACCOUNT);
+        let verified = acme::verify(&jws, &jwk, &url(ORDER)).unwrap();
+        assert_eq!(verified.payload(), b"");
+    }
+
+    #[test]
+    fn inferred() {
+        let check = |mut jwk: Jwk, alg| {
+            jwk.prm.alg = None;
+            let jws =
+                acme::post_as_get(&jwk, &url(ACCOUNT), url(ORDER), nonce(), &mut OsRng).unwrap();
+            assert_eq!(jws.signature.header().oth.alg, Some(alg));
+            acme::verify(&jws, &jwk, &url(ORDER)).unwrap();
+        };
+
+        check(p256(), Signing::Es256);
+        check(ed25519(), Signing::EdDsa);
+
+        let mut mac = mac();
+        mac.prm.alg = None;
+        let eab = acme::bind(&mac, &p256(), url(NEW_ACCOUNT), &mut OsRng).unwrap();
+        assert_eq!(eab.signature.header().oth.alg, Some(Signing::Hs256));
+        assert!(matches!(
+            acme::sign(&mac, None, url(ORDER), nonce(), b"{}", &mut OsRng),
+            Err(Error::Disallowed)
+        ));
+    }
+
+    #[test]
+    fn rules() {
+        let jwk = p256();
+        let public: Jwk = serde_json::from_value(serde_json::json!({
+            "kty": "EC",
+            "crv": "P-256",
+            "x": P256_X,
+            "y": P256_Y,
+        }))
+        .unwrap();
+
+        let check = |jws: &Flattened| acme::verify(jws, &jwk, &url(ORDER)).map(drop);
+
+        check(&sign(prot(None, Some(ACCOUNT)), None)).unwrap();
+        check(&sign(prot(Some(public.clone()), None), None)).unwrap();
+
+        let both = sign(prot(Some(public), Some(ACCOUNT)), None);
+        assert!(matches!(
+            check(&both),
+            Err(Error::Acme(Violation::Identity))
+        ));
+
+        let neither = sign(prot(None, None), None);
+        assert!(matches!(
+            check(&neither),
+            Err(Error::Acme(Violation::Identity))
+        ));
+
+        let private = sign(prot(Some(p256()), None), None);
+        assert!(matches!(
+            check(&private),
+            Err(Error::Acme(Violation::Private))
+        ));
+
+        let mut p = prot(None, Some(ACCOUNT));
+        p.nonce = None;
+        assert!(matches!(
+            check(&sign(p, None)),
+            Err(Error::Acme(Violation::Nonce))
+        ));
+
+        let mut p = prot(None, Some(ACCOUNT));
+        p.url = None;
+        assert!(matches!(
+            check(&sign(p, None)),
+            Err(Error::Acme(Violation::Url))
+        ));
+
+        let head = Unprotected {
+            typ: Some("JOSE".into()),
+            ..Default::default()
+        };
+        let unprotected = sign(prot(None, Some(ACCOUNT)), Some(head));
+        assert!(matches!(
+            check(&unprotected),
+            Err(Error::Acme(Violation::Unprotected))
+        ));
+
+        let mut p = prot(None, Some(ACCOUNT));
+        p.crit = Some(vec!["b64".into()]);
+        p.b64 = false;
+        assert!(matches!(
+            check(&sign(p, None)),
+            Err(Error::Acme(Violation::Unencoded))
+        ));
+
+        let mut detached = sign(prot(None, Some(ACCOUNT)), None);
+        detached.payload = None;
+        assert!(matches!(
+            check(&detached),
+            Err(Error::Acme(Violation::Payload))
+        ));
+
+        // MAC-based algorithms are not allowed for requests.
+        let mac = mac();
+        let mut p = prot(None, Some(ACCOUNT));
+        p.oth.alg = Some(Signing::Hs256);
+        let jws: Flattened = Builder::new(b"{}")
+            .jwk(&mac, Some(p), None)
+            .unwrap()
+            .flattened(&mut OsRng)
+            .unwrap();
+        assert!(matches!(
+            acme::verify(&jws, &mac, &url(ORDER)),
+            Err(Error::Disallowed)
+        ));
+        assert!(matches!(
+            acme::sign(&mac, None, url(ORDER), nonce(), b"{}", &mut OsRng),
+            Err(Error::Disallowed)
+        ));
+    }
+
+    #[test]
+    fn binding() {
+        let jwk = p256();
+        let mac = mac();
+        let eab = acme::bind(&mac, &jwk, url(NEW_ACCOUNT), &mut OsRng).unwrap();
+
+        let header = eab.signature.header();
+        assert_eq!(header.oth.alg, Some(Signing::Hs256));
+        assert_eq!(header.oth.kid.as_deref(), Some("kid-1"));
+        assert!(header.nonce.is_none());
+
+        let payload = serde_json::json!({
+            "termsOfServiceAgreed": true,
+            "externalAccountBinding": eab,
+        });
+        let payload = serde_json::to_vec(&payload).unwrap();
+        let jws = acme::sign(&jwk, None, url(NEW_ACCOUNT), nonce(), &payload, &mut OsRng).unwrap();
+
+        // The server verifies the request, then the binding.
+        let Account::Jwk(key) = acme::account(&jws, &url(NEW_ACCOUNT)).unwrap() else {
+            panic!("expected an embedded key");
+        };
+
+        let verified = acme::verify(&jws, key, &url(NEW_ACCOUNT)).unwrap();
+        let payload: serde_json::Value = serde_json::from_slice(verified.payload()).unwrap();
+        let eab: Flattened =
+            serde_json::from_value(payload["externalAccountBinding"].clone()).unwrap();
+        acme::verify_binding(&eab, &mac, key, &url(NEW_ACCOUNT)).unwrap();
+
+        // The binding must be for the account key.
+        assert!(matches!(
+            acme::verify_binding(&eab, &mac, &ed25519(), &url(NEW_ACCOUNT)),
+            Err(Error::Acme(Violation::Payload))
+        ));
+        assert!(matches!(
+            acme::verify_binding(&eab, &mac, key, &url(ORDER)),
+            Err(Error::Acme(Violation::Url))
+        ));
+
+        // A request is not a binding.
+        assert!(matches!(
+            acme::verify_binding(&jws, &mac, key, &url(NEW_ACCOUNT)),
+            Err(Error::Acme(Violation::Nonce))
+        ));
+    }
+
+    #[test]
+    fn key_change() {
+        let old = p256();
+        let new = ed25519();
+        let jws = acme::key_change(
+            &old,
+            &new,
+            &url(ACCOUNT),
+            url(KEY_CHANGE),
+            nonce(),
+            &mut OsRng,
+        )
+        .unwrap();
+
+        let key = acme::verify_key_change(&jws, &old, &url(KEY_CHANGE)).unwrap();
+        assert_eq!(
+            serde_json::to_value(&key).unwrap(),
+            serde_json::json!({
+                "kty": "OKP",
+                "crv": "Ed25519",
+                "x": ED25519_X,
+            })
+        );
+
+        // The inner JWS is signed by the new key over the old one.
+        let inner: Flattened = serde_json::from_slice(jws.payload.as_deref().unwrap()).unwrap();
+        let header = inner.signature.header();
+        assert!(header.nonce.is_none());
+        assert_eq!(header.url, Some(url(KEY_CHANGE)));
+        let payload: serde_json::Value =
+            serde_json::from_slice(inner.payload.as_deref().unwrap()).unwrap();
+        assert_eq!(payload["account"], ACCOUNT);
+        assert_eq!(payload["oldKey"]["x"], P256_X);
+
+        // The old key must be the current key of the account.
+        assert!(matches!(
+            acme::verify_key_change(&jws, &new, &url(KEY_CHANGE)),
+            Err(Error::AlgMismatch | Error::Key(..) | Error::Invalid)
+        ));
+
+        // The inner JWS must not be a request.
+        let inner = acme::sign(&new, None, url(KEY_CHANGE), nonce(), b"{}", &mut OsRng).unwrap();
+        let inner = serde_json::to_vec(&inner).unwrap();
+        let jws = acme::sign(
+            &old,
+            Some(&url(ACCOUNT)),
+            url(KEY_CHANGE),
+            nonce(),
+            &inner,
+            &mut OsRng,
+        )
+        .unwrap();
+        assert!(matches!(
+            acme::verify_key_change(&jws, &old, &url(KEY_CHANGE)),
+            Err(Error::Acme(Violation::Nonce))
+        ));
+    }
+}